@@ -12,27 +12,115 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ::Size;
+use ::{Point, Size};
+use ::graphics::Image;
+use super::CrashHandler;
+
+/// Selects one of the available monitors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MonitorSelect {
+	/// The primary monitor.
+	Primary,
+
+	/// The monitor that matches the provided name.
+	Name(String),
+
+	/// The monitor at the provided index of
+	/// [`Window.get_all_monitors`](struct.Window.html#method.get_all_monitors).
+	/// Useful when the OS does not provide monitor names.
+	Index(usize)
+}
 
 /// All the possible fullscreen configurations.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Fullscreen {
 	/// Fullscreen is disabled.
 	Disabled,
 
-	/// Uses the primary monitor for fullscreen.
+	/// Uses the primary monitor for exclusive fullscreen.
 	Primary,
 
-	/// Uses the monitor that matches the provided name for fullscreen.
-	Monitor(String)
+	/// Uses the monitor that matches the provided name for exclusive fullscreen.
+	Monitor(String),
+
+	/// Uses the monitor at the provided index for exclusive fullscreen.
+	Index(usize),
+
+	/// Uses an undecorated window that covers the selected monitor.
+	/// Switching to and from other windows is faster than exclusive fullscreen.
+	Borderless(MonitorSelect)
+}
+
+/// The initial position of a window.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Position {
+	/// The OS chooses the position.
+	Default,
+
+	/// The top-left corner of the window is placed at the provided point on the desktop.
+	At(Point),
+
+	/// The window is centered on the selected monitor.
+	Centered(MonitorSelect)
+}
+
+/// The OpenGL API and version of the context.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GlVersion {
+	/// Desktop OpenGL core profile. Must be at least 3.2.
+	Gl(u8, u8),
+
+	/// OpenGL ES. Must be 2.0 or at least 3.0.
+	Gles(u8, u8)
+}
+
+/// The renderer used to draw to the window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Renderer {
+	/// Draws with OpenGL.
+	Gl,
+
+	/// Draws on the CPU and only uses OpenGL to show the result.
+	/// Slower, but works around most driver bugs.
+	Software
+}
+
+/// How OpenGL errors are handled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GlErrors {
+	/// Panics with the name of the error and the call that caused it.
+	Panic,
+
+	/// Returns the first error since the last update from
+	/// [`Window.update`](struct.Window.html#method.update) as
+	/// [`WindowError::GraphicsError`](enum.WindowError.html#variant.GraphicsError).
+	Report,
+
+	/// Errors are not checked.
+	/// This avoids the cost of `glGetError`.
+	Ignore
 }
 
 /// The configuration options when creating a window.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Config {
 	/// The title of the window.
 	pub title: String,
 
+	/// The icon of the window.
+	/// If `None`, the default icon of the OS is used.
+	/// Not serialized.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	pub icon: Option<Image>,
+
 	/// The initial size of the window.
 	pub size: Size,
 
@@ -42,34 +130,111 @@ pub struct Config {
 	/// The maximum size of the window.
 	pub max_size: Option<Size>,
 
+	/// The initial position of the window.
+	/// Ignored if fullscreen is enabled.
+	pub position: Position,
+
+	/// Whether or not the window is initially visible.
+	pub visible: bool,
+
 	/// Whether or not the window starts maximized.
 	pub maximized: bool,
 
 	/// Whether or not the window is resizable.
 	pub resizable: bool,
 
+	/// Whether or not the window has a title bar and borders.
+	pub decorations: bool,
+
+	/// Whether or not the framebuffer has an alpha channel
+	/// that lets the desktop show through the window.
+	pub transparent: bool,
+
+	/// Whether or not the window is always above other windows.
+	pub always_on_top: bool,
+
 	/// The fullscreen configuration.
 	pub fullscreen: Fullscreen,
 
 	/// Whether or not V-sync is enabled.
 	pub vsync: bool,
 
-	/// The MSAA amount. Must be a power of two.
-	pub msaa: u16
+	/// The MSAA amount. Must be 0 to disable it, or a power of two.
+	pub msaa: u16,
+
+	/// The renderer used to draw to the window.
+	pub renderer: Renderer,
+
+	/// The OpenGL API and version of the context.
+	pub gl_version: GlVersion,
+
+	/// Whether or not a debug OpenGL context is created.
+	/// If `KHR_debug` is available, debug messages are written with [`log`](fn.log.html).
+	pub gl_debug: bool,
+
+	/// How OpenGL errors are handled.
+	pub gl_errors: GlErrors,
+
+	/// The panic handler configuration.
+	/// Not serialized.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	pub crash_handler: CrashHandler
 }
 
 impl Default for Config {
 	fn default() -> Config {
 		Config {
 			title: String::new(),
+			icon: None,
 			size: Size {width: 640.0, height: 480.0},
 			min_size: None,
 			max_size: None,
+			position: Position::Default,
+			visible: true,
 			maximized: false,
 			resizable: true,
+			decorations: true,
+			transparent: false,
+			always_on_top: false,
 			fullscreen: Fullscreen::Disabled,
 			vsync: true,
-			msaa: 0
+			msaa: 0,
+			renderer: Renderer::Gl,
+			gl_version: GlVersion::Gl(3, 2),
+			gl_debug: cfg!(debug_assertions),
+			gl_errors: if cfg!(debug_assertions) {
+				GlErrors::Panic
+			} else {
+				GlErrors::Report
+			},
+			crash_handler: CrashHandler::default()
 		}
 	}
+}
+
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+	use super::*;
+
+	extern crate serde_json;
+
+	#[test]
+	fn serde_round_trips() {
+		let config = Config {
+			title: String::from("Game"),
+			size: Size {width: 800.0, height: 600.0},
+			position: Position::Centered(MonitorSelect::Name(String::from("DP-1"))),
+			fullscreen: Fullscreen::Borderless(MonitorSelect::Index(1)),
+			gl_version: GlVersion::Gles(3, 0),
+			renderer: Renderer::Software,
+			..Config::default()
+		};
+		let json = serde_json::to_string(&config).unwrap();
+		assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+
+		// Missing fields use the default.
+		let config: Config = serde_json::from_str(r#"{"title": "Game"}"#).unwrap();
+		assert_eq!(config, Config {title: String::from("Game"), ..Config::default()});
+	}
 }
\ No newline at end of file
@@ -0,0 +1,339 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate backtrace;
+use self::backtrace::Backtrace;
+
+extern crate msgbox;
+use self::msgbox::IconType;
+
+use std::fmt::{self, Debug, Display, Formatter};
+use std::collections::VecDeque;
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Once};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LOG_CAPACITY: usize = 256;
+
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static LOG_SINK: Mutex<Option<LogSink>> = Mutex::new(None);
+static RENDERER: Mutex<Option<String>> = Mutex::new(None);
+
+// The handler used by the panic hook, which is only installed once.
+static HANDLER: Mutex<Option<CrashHandler>> = Mutex::new(None);
+static INSTALL: Once = Once::new();
+
+/// A callback that is called with every line written with [`log`](fn.log.html).
+pub type LogSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Keeps a line so it can be included in crash reports, and writes it to stderr
+/// or the sink set with [`set_log_sink`](fn.set_log_sink.html).
+pub fn log<S: Into<String>>(line: S) {
+	let line = line.into();
+	let sink = LOG_SINK.lock().ok().and_then(|sink| sink.clone());
+	match sink {
+		Some(sink) => sink(&line),
+		None => eprintln!("{}", line)
+	}
+	if let Ok(mut log) = LOG.lock() {
+		if log.len() == LOG_CAPACITY {
+			log.pop_front();
+		}
+		log.push_back(line);
+	}
+}
+
+/// Sets the callback that lines written with [`log`](fn.log.html) are sent to
+/// instead of stderr, or writes them to stderr again if `None`.
+/// Lines are kept for crash reports whether or not there is a sink.
+pub fn set_log_sink(sink: Option<LogSink>) {
+	if let Ok(mut log_sink) = LOG_SINK.lock() {
+		*log_sink = sink;
+	}
+}
+
+/// Gets the most recent lines written with [`log`](fn.log.html), oldest first.
+pub fn get_log() -> Vec<String> {
+	LOG.lock().ok().map_or(Vec::new(), |log| log.iter().cloned().collect())
+}
+
+pub(crate) fn set_renderer(renderer: String) {
+	if let Ok(mut info) = RENDERER.lock() {
+		*info = Some(renderer);
+	}
+}
+
+/// A callback that is called with the crash report when a panic occurs.
+pub type CrashCallback = Arc<dyn Fn(&CrashReport) + Send + Sync>;
+
+/// Information about a panic, passed to
+/// [`CrashHandler.callback`](struct.CrashHandler.html#structfield.callback)
+/// and written to the crash log.
+#[derive(Clone, Debug)]
+pub struct CrashReport {
+	/// The panic message.
+	pub message: String,
+
+	/// The source location of the panic, if known.
+	pub location: Option<String>,
+
+	/// The time of the panic in UTC.
+	pub timestamp: String,
+
+	/// The operating system and architecture.
+	pub os: String,
+
+	/// The OpenGL renderer, if a window has been created.
+	pub renderer: Option<String>,
+
+	/// The last lines written with [`log`](fn.log.html).
+	pub log: Vec<String>,
+
+	/// The backtrace of the panic.
+	pub backtrace: Backtrace
+}
+
+impl Display for CrashReport {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		writeln!(f, "Panic: {}", self.message)?;
+		if let Some(ref location) = self.location {
+			writeln!(f, "Location: {}", location)?;
+		}
+		writeln!(f, "Time: {}", self.timestamp)?;
+		writeln!(f, "OS: {}", self.os)?;
+		if let Some(ref renderer) = self.renderer {
+			writeln!(f, "Renderer: {}", renderer)?;
+		}
+
+		if !self.log.is_empty() {
+			writeln!(f, "\nLog:")?;
+			for line in &self.log {
+				writeln!(f, "{}", line)?;
+			}
+		}
+		write!(f, "\nBacktrace:\n{:?}", self.backtrace)
+	}
+}
+
+/// The configuration of the panic handler installed when creating a window.
+#[derive(Clone)]
+pub struct CrashHandler {
+	/// Whether or not the handler is installed.
+	pub enabled: bool,
+
+	/// The directory to write `panic.log` to.
+	/// If `None`, no log file is written.
+	pub log_dir: Option<PathBuf>,
+
+	/// The amount of lines written with [`log`](fn.log.html)
+	/// that are included in the report.
+	pub log_lines: usize,
+
+	/// Whether or not a message box is shown.
+	pub message_box: bool,
+
+	/// Whether or not the panic hook that was installed before mint2d's is also called.
+	pub chain: bool,
+
+	/// A custom callback that is called with the report.
+	pub callback: Option<CrashCallback>
+}
+
+impl CrashHandler {
+	/// Makes this the handler used when a panic occurs.
+	/// The panic hook is only installed the first time, so creating more windows
+	/// replaces the handler rather than adding more hooks.
+	pub(crate) fn install(&self) {
+		self.install_with(install_hook);
+	}
+
+	fn install_with<F: FnOnce()>(&self, install_hook: F) {
+		if let Ok(mut handler) = HANDLER.lock() {
+			*handler = if self.enabled {Some(self.clone())} else {None};
+		}
+		if !self.enabled {
+			return;
+		}
+
+		INSTALL.call_once(install_hook);
+	}
+
+	fn handle(&self, panic: &PanicHookInfo, previous: &(dyn Fn(&PanicHookInfo) + Send + Sync)) {
+		let message = if let Some(message) = panic.payload().downcast_ref::<&str>() {
+			String::from(*message)
+		} else if let Some(message) = panic.payload().downcast_ref::<String>() {
+			message.clone()
+		} else {
+			String::from("Box<Any>")
+		};
+
+		let report = CrashReport {
+			message,
+			location: panic.location().map(|location| ToString::to_string(location)),
+			timestamp: timestamp(),
+			os: format!("{} {}", env::consts::OS, env::consts::ARCH),
+			renderer: RENDERER.try_lock().ok().and_then(|renderer| renderer.clone()),
+			log: LOG.try_lock().ok().map_or(Vec::new(), |log| {
+				let skip = log.len().saturating_sub(self.log_lines);
+				log.iter().skip(skip).cloned().collect()
+			}),
+			backtrace: Backtrace::new()
+		};
+
+		if self.chain {
+			previous(panic);
+		}
+
+		let mut error = "See the console for details";
+		if let Some(ref dir) = self.log_dir {
+			error = "Could not create panic.log";
+			if fs::create_dir_all(dir).is_ok() {
+				if let Ok(mut file) = File::create(dir.join("panic.log")) {
+					if write!(&mut file, "{}", report).is_ok() {
+						error = "See panic.log for details";
+					}
+				}
+			}
+		}
+
+		if let Some(ref callback) = self.callback {
+			callback(&report);
+		}
+
+		if self.message_box {
+			msgbox::create("Panic!", error, IconType::ERROR);
+		}
+	}
+}
+
+/// Installs the panic hook that calls the current handler.
+fn install_hook() {
+	// The hook that was installed before mint2d, usually the default hook.
+	let previous = panic::take_hook();
+	panic::set_hook(Box::new(move |panic| {
+		let handler = HANDLER.try_lock().ok().and_then(|handler| handler.clone());
+		match handler {
+			Some(handler) => handler.handle(panic, &previous),
+			None => previous(panic)
+		}
+	}));
+}
+
+impl Default for CrashHandler {
+	fn default() -> CrashHandler {
+		CrashHandler {
+			enabled: true,
+			log_dir: Some(PathBuf::from(".")),
+			log_lines: 32,
+			message_box: true,
+			chain: true,
+			callback: None
+		}
+	}
+}
+
+impl Debug for CrashHandler {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		f.debug_struct("CrashHandler")
+			.field("enabled", &self.enabled)
+			.field("log_dir", &self.log_dir)
+			.field("log_lines", &self.log_lines)
+			.field("message_box", &self.message_box)
+			.field("chain", &self.chain)
+			.field("callback", &self.callback.as_ref().map(|_| ".."))
+			.finish()
+	}
+}
+
+impl PartialEq for CrashHandler {
+	fn eq(&self, other: &CrashHandler) -> bool {
+		self.enabled == other.enabled
+			&& self.log_dir == other.log_dir
+			&& self.log_lines == other.log_lines
+			&& self.message_box == other.message_box
+			&& self.chain == other.chain
+			&& match (&self.callback, &other.callback) {
+				(&Some(ref a), &Some(ref b)) => Arc::ptr_eq(a, b),
+				(&None, &None) => true,
+				_ => false
+			}
+	}
+}
+
+fn timestamp() -> String {
+	let secs = SystemTime::now().duration_since(UNIX_EPOCH)
+		.map_or(0, |time| time.as_secs());
+	let days = (secs / 86400) as i64;
+	let secs = secs % 86400;
+
+	// Converts days since the epoch to a civil date.
+	// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+	let z = days + 719468;
+	let era = z.div_euclid(146097);
+	let doe = z - era * 146097;
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+	format!(
+		"{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+		year, month, day,
+		secs / 3600, secs / 60 % 60, secs % 60
+	)
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	#[test]
+	fn installing_twice_installs_the_hook_once() {
+		// Counts installs instead of replacing the hook of the test process.
+		let installs = AtomicUsize::new(0);
+		let handler = CrashHandler {
+			log_dir: None,
+			message_box: false,
+			callback: Some(Arc::new(|_: &CrashReport| ())),
+			..CrashHandler::default()
+		};
+		handler.install_with(|| {installs.fetch_add(1, Ordering::SeqCst);});
+		handler.install_with(|| {installs.fetch_add(1, Ordering::SeqCst);});
+		assert_eq!(installs.load(Ordering::SeqCst), 1);
+		assert_eq!(*HANDLER.lock().unwrap(), Some(handler));
+
+		CrashHandler {enabled: false, ..CrashHandler::default()}.install_with(|| panic!("installed again"));
+		assert_eq!(*HANDLER.lock().unwrap(), None);
+	}
+
+	#[test]
+	fn log_sends_lines_to_the_sink() {
+		let lines = Arc::new(Mutex::new(Vec::new()));
+		let sink = lines.clone();
+		set_log_sink(Some(Arc::new(move |line: &str| sink.lock().unwrap().push(line.to_string()))));
+		log("sink test");
+		set_log_sink(None);
+
+		assert!(lines.lock().unwrap().contains(&"sink test".to_string()));
+		assert!(get_log().contains(&"sink test".to_string()));
+	}
+}
\ No newline at end of file
@@ -16,40 +16,49 @@
 
 extern crate glutin;
 use self::glutin::{EventsLoop, Event, WindowEvent, ElementState};
-use self::glutin::{GlWindow, GlContext, GlRequest, Api};
-use self::glutin::{WindowBuilder, ContextBuilder, dpi::LogicalSize};
+use self::glutin::{GlWindow, GlContext, GlRequest, GlProfile, Api, Icon};
+use self::glutin::{WindowBuilder, ContextBuilder, dpi::{LogicalSize, LogicalPosition}};
 
 extern crate backtrace;
 use self::backtrace::Backtrace;
 
-extern crate msgbox;
-use self::msgbox::IconType;
-
 mod monitor;
 pub use self::monitor::*;
 
 mod config;
 pub use self::config::*;
 
+mod crash;
+pub use self::crash::*;
+
+mod settings;
+pub use self::settings::*;
+
 use ::{Point, Size};
 use ::input::Input;
-use ::graphics::Graphics;
+use ::graphics::{Graphics, GraphicsError};
 
 use std::fmt::{Display, Formatter, Error};
-use std::panic;
-use std::boxed::Box;
-use std::fs::File;
-use std::io::Write;
+use std::error;
 use std::rc::Rc;
 use std::cell::RefCell;
 
 /// Possible errors that can occur from window related actions.
 #[derive(Clone, Debug)]
 pub enum WindowError {
-	/// [`Fullscreen::Monitor`](enum.Fullscreen.html#variant.Monitor)
-	/// didn't match any monitor name.
+	/// The monitor selected by
+	/// [`Config.fullscreen`](struct.Config.html#structfield.fullscreen)
+	/// didn't match any monitor name or index.
 	UnknownMonitor(Backtrace),
 
+	/// [`Config.gl_version`](struct.Config.html#structfield.gl_version)
+	/// is not a supported version.
+	UnsupportedGlVersion(Backtrace),
+
+	/// An OpenGL error occurred.
+	/// See [`Config.gl_errors`](struct.Config.html#structfield.gl_errors).
+	GraphicsError(GraphicsError),
+
 	/// An unknown internal error occurred.
 	InternalError(String, Backtrace)
 }
@@ -58,11 +67,34 @@ impl Display for WindowError {
 	fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
 		match self {
 			&WindowError::UnknownMonitor(..) => write!(f, "Unknown monitor"),
+			&WindowError::UnsupportedGlVersion(..) => write!(f, "Unsupported OpenGL version"),
+			&WindowError::GraphicsError(ref error) => write!(f, "{}", error),
 			&WindowError::InternalError(ref error, ..) => write!(f, "{}", error)
 		}
 	}
 }
 
+impl error::Error for WindowError {
+	fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+		match self {
+			&WindowError::GraphicsError(ref error) => Some(error),
+			_ => None
+		}
+	}
+}
+
+impl WindowError {
+	/// Gets the backtrace captured when the error occurred.
+	pub fn backtrace(&self) -> &Backtrace {
+		match self {
+			&WindowError::UnknownMonitor(ref backtrace) => backtrace,
+			&WindowError::UnsupportedGlVersion(ref backtrace) => backtrace,
+			&WindowError::GraphicsError(ref error) => error.backtrace(),
+			&WindowError::InternalError(_, ref backtrace) => backtrace
+		}
+	}
+}
+
 pub(crate) struct WindowImpl {
 	pub window: GlWindow,
 	pub events: EventsLoop
@@ -86,31 +118,38 @@ pub struct Window {
 impl Window {
 	/// # Errors
 	/// If [`Config.fullscreen`](struct.Config.html#structfield.fullscreen)
-	/// is [`Fullscreen::Monitor`](enum.Fullscreen.html#variant.Monitor)
-	/// and it doesn't match any monitor name, this will return with
+	/// selects a monitor that doesn't match any monitor name or index, this will return with
 	/// [`WindowError::UnknownMonitor`](enum.WindowError.html#variant.UnknownMonitor).
+	///
+	/// If [`Config.gl_version`](struct.Config.html#structfield.gl_version)
+	/// is not a supported version, this will return with
+	/// [`WindowError::UnsupportedGlVersion`](enum.WindowError.html#variant.UnsupportedGlVersion).
 	pub fn new(config: Config) -> Result<Window, WindowError> {
-		panic::set_hook(Box::new(|panic| {
-			let mut error = "Could not create panic.log";
-			if let Ok(mut file) = File::create("panic.log") {
-				if write!(&mut file, "{}\n{:?}", panic, Backtrace::new()).is_ok() {
-					error = "See panic.log for details";
-				}
-			}
-
-			msgbox::create("Panic!", error, IconType::ERROR);
-		}));
+		config.crash_handler.install();
 
 		let events = EventsLoop::new();
 		let mut window = WindowBuilder::new()
-			.with_title(config.title)
+			.with_title(config.title.clone())
 			.with_maximized(config.maximized)
 			.with_resizable(config.resizable)
+			.with_decorations(config.decorations)
+			.with_transparency(config.transparent)
+			.with_always_on_top(config.always_on_top)
+			.with_visibility(false)
 			.with_dimensions(LogicalSize {
 				width: config.size.width,
 				height: config.size.height
 			});
 
+		if let Some(ref icon) = config.icon {
+			let icon = Icon::from_rgba(icon.get_data().to_vec(), icon.get_width(), icon.get_height())
+				.map_err(|error| WindowError::InternalError(
+					ToString::to_string(&error),
+					Backtrace::new()
+				))?;
+			window = window.with_window_icon(Some(icon));
+		}
+
 		if let Some(size) = config.min_size {
 			window = window.with_min_dimensions(LogicalSize {
 				width: size.width,
@@ -125,34 +164,85 @@ impl Window {
 			});
 		}
 
-		window = window.with_fullscreen(match config.fullscreen {
+		let mut position = None;
+		let fullscreen = match config.fullscreen.clone() {
 			Fullscreen::Disabled => None,
-			Fullscreen::Primary => Some(events.get_primary_monitor()),
-			Fullscreen::Monitor(name) => {
-				let mut result = Err(WindowError::UnknownMonitor(Backtrace::new()));
-				for monitor in events.get_available_monitors() {
-					if let Some(n) = monitor.get_name() {
-						if name == n {
-							result = Ok(monitor);
-							break;
-						}
-					}
-				}
-				Some(result?)
+			Fullscreen::Primary => Some(select_monitor(&events, &MonitorSelect::Primary)?),
+			Fullscreen::Monitor(name) => Some(select_monitor(&events, &MonitorSelect::Name(name))?),
+			Fullscreen::Index(index) => Some(select_monitor(&events, &MonitorSelect::Index(index))?),
+			Fullscreen::Borderless(select) => {
+				let monitor = select_monitor(&events, &select)?;
+				let size = monitor.get_size();
+				position = Some(monitor.get_position());
+				window = window
+					.with_decorations(false)
+					.with_maximized(false)
+					.with_resizable(false)
+					.with_dimensions(LogicalSize {
+						width: size.width,
+						height: size.height
+					});
+				None
 			}
-		});
-
-		let context = ContextBuilder::new()
-			.with_gl(GlRequest::Specific(Api::OpenGl, (3, 2)))
+		};
+		window = window.with_fullscreen(fullscreen.map(|monitor| monitor.get_id().clone()));
+
+		let request = match config.gl_version {
+			GlVersion::Gl(major, minor) if (major, minor) >= (3, 2) =>
+				GlRequest::Specific(Api::OpenGl, (major, minor)),
+			GlVersion::Gles(major, minor) if (major, minor) == (2, 0) || major >= 3 =>
+				GlRequest::Specific(Api::OpenGlEs, (major, minor)),
+			_ => return Err(WindowError::UnsupportedGlVersion(Backtrace::new()))
+		};
+
+		let mut context = ContextBuilder::new()
+			.with_gl(request)
+			.with_gl_debug_flag(config.gl_debug)
+			.with_stencil_buffer(8)
 			.with_vsync(config.vsync)
 			.with_multisampling(config.msaa);
 
+		if let GlVersion::Gl(..) = config.gl_version {
+			context = context.with_gl_profile(GlProfile::Core);
+		}
+
+		if config.transparent {
+			context = context.with_pixel_format(24, 8);
+		}
+
 		let window = GlWindow::new(window, context, &events)
 			.map_err(|error| WindowError::InternalError(
 				ToString::to_string(&error),
 				Backtrace::new()
 			))?;
 
+		if config.fullscreen == Fullscreen::Disabled {
+			position = match config.position {
+				Position::Default => None,
+				Position::At(point) => Some(point),
+				Position::Centered(ref select) => {
+					let monitor = select_monitor(&events, select)?;
+					let size = window.get_outer_size()
+						.map_or(config.size, |size| Size {
+							width: size.width,
+							height: size.height
+						});
+					Some(monitor.get_rect().center() - size / 2.0)
+				}
+			};
+		}
+
+		if let Some(position) = position {
+			window.set_position(LogicalPosition {
+				x: position.x,
+				y: position.y
+			});
+		}
+
+		if config.visible {
+			window.show();
+		}
+
 		unsafe {
 			window.make_current()
 				.map_err(|error| WindowError::InternalError(
@@ -165,7 +255,7 @@ impl Window {
 		Ok(Window {
 			rc: Rc::clone(&rc),
 			input: Input::new(Rc::clone(&rc)),
-			graphics: Graphics::new(Rc::clone(&rc))
+			graphics: Graphics::new(Rc::clone(&rc), &config)
 		})
 	}
 
@@ -173,7 +263,7 @@ impl Window {
 	/// Will return false if the window has been closed,
 	/// true otherwise.
 	pub fn update(&mut self) -> Result<bool, WindowError> {
-		self.graphics.update()?;
+		self.graphics.update(&self.input)?;
 		let input = &mut self.input;
 
 		let mut result = true;
@@ -214,6 +304,8 @@ impl Window {
 	}
 
 	/// Gets an iterator of all the monitors.
+	/// The position of a monitor in this iterator can be used with
+	/// [`MonitorSelect::Index`](enum.MonitorSelect.html#variant.Index).
 	pub fn get_all_monitors(&self) -> MonitorIter {
 		MonitorIter::new(self.rc.borrow().events.get_available_monitors())
 	}
@@ -230,6 +322,16 @@ impl Window {
 			})
 	}
 
+	/// Shows or hides the window.
+	pub fn set_visible(&mut self, visible: bool) {
+		let window = &self.rc.borrow().window;
+		if visible {
+			window.show();
+		} else {
+			window.hide();
+		}
+	}
+
 	/// Gets the [`Input`](../input/struct.Input.html) struct for the window.
 	pub fn input(&mut self) -> &mut Input {
 		&mut self.input
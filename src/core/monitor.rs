@@ -13,9 +13,13 @@
 // limitations under the License.
 
 extern crate glutin;
-use self::glutin::{MonitorId, AvailableMonitorsIter};
+use self::glutin::{EventsLoop, MonitorId, AvailableMonitorsIter};
 
-use ::Size;
+extern crate backtrace;
+use self::backtrace::Backtrace;
+
+use ::{Point, Size, Rect};
+use super::{MonitorSelect, WindowError};
 
 /// A monitor or display.
 #[derive(Clone, Debug)]
@@ -28,12 +32,27 @@ impl Monitor {
 		Monitor {monitor}
 	}
 
+	pub(crate) fn get_id(&self) -> &MonitorId {
+		&self.monitor
+	}
+
 	/// Gets the name of the monitor.
 	/// This can be used to create a fullscreen window.
+	/// Returns an empty string if the OS does not provide a name.
 	pub fn get_name(&self) -> String {
 		self.monitor.get_name().unwrap_or(String::new())
 	}
 
+	/// Gets the position of the monitor on the desktop.
+	pub fn get_position(&self) -> Point {
+		let position = self.monitor.get_position()
+			.to_logical(self.monitor.get_hidpi_factor());
+		Point {
+			x: position.x,
+			y: position.y
+		}
+	}
+
 	/// Gets the size of the monitor.
 	pub fn get_size(&self) -> Size {
 		let size = self.monitor.get_dimensions()
@@ -43,6 +62,26 @@ impl Monitor {
 			height: size.height
 		}
 	}
+
+	/// Gets the area the monitor covers on the desktop.
+	pub fn get_rect(&self) -> Rect {
+		Rect {
+			origin: self.get_position(),
+			size: self.get_size()
+		}
+	}
+}
+
+pub(crate) fn select_monitor(events: &EventsLoop, select: &MonitorSelect) -> Result<Monitor, WindowError> {
+	let monitor = match select {
+		&MonitorSelect::Primary => Some(events.get_primary_monitor()),
+		&MonitorSelect::Name(ref name) => events.get_available_monitors()
+			.find(|monitor| monitor.get_name().as_ref() == Some(name)),
+		&MonitorSelect::Index(index) => events.get_available_monitors().nth(index)
+	};
+	monitor
+		.map(Monitor::new)
+		.ok_or_else(|| WindowError::UnknownMonitor(Backtrace::new()))
 }
 
 /// An iterator over monitors.
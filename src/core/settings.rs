@@ -0,0 +1,558 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate backtrace;
+use self::backtrace::Backtrace;
+
+use ::{Point, Size};
+use super::{Config, Fullscreen, MonitorSelect, Position};
+use super::{GlVersion, GlErrors, Renderer};
+use super::log;
+
+use std::fmt::{self, Display, Formatter};
+use std::error;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Possible errors that can occur when loading or saving a config.
+#[derive(Clone, Debug)]
+pub enum ConfigError {
+	/// The file could not be read or written.
+	/// Contains the error from the OS.
+	Io(String, Backtrace),
+
+	/// A line of the file is not a `key = value` pair.
+	/// Contains the line number, starting at 1.
+	Syntax(usize, Backtrace),
+
+	/// A value could not be parsed.
+	/// Contains the key or option and the value.
+	InvalidValue(String, String, Backtrace),
+
+	/// A command-line option is missing its value.
+	/// Contains the option.
+	MissingValue(String, Backtrace)
+}
+
+impl Display for ConfigError {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			&ConfigError::Io(ref error, ..) => write!(f, "{}", error),
+			&ConfigError::Syntax(line, ..) => write!(f, "Syntax error on line {}", line),
+			&ConfigError::InvalidValue(ref key, ref value, ..) =>
+				write!(f, "Invalid value for {}: {}", key, value),
+			&ConfigError::MissingValue(ref option, ..) => write!(f, "Missing value for {}", option)
+		}
+	}
+}
+
+impl error::Error for ConfigError {}
+
+impl ConfigError {
+	/// Gets the backtrace captured when the error occurred.
+	pub fn backtrace(&self) -> &Backtrace {
+		match self {
+			&ConfigError::Io(_, ref backtrace) => backtrace,
+			&ConfigError::Syntax(_, ref backtrace) => backtrace,
+			&ConfigError::InvalidValue(_, _, ref backtrace) => backtrace,
+			&ConfigError::MissingValue(_, ref backtrace) => backtrace
+		}
+	}
+}
+
+// Command-line options that don't take a value.
+const FLAGS: &[&str] = &[
+	"--fullscreen", "--borderless", "--windowed",
+	"--vsync", "--no-vsync", "--gl-debug", "--no-gl-debug"
+];
+
+fn invalid(key: &str, value: &str) -> ConfigError {
+	ConfigError::InvalidValue(key.to_string(), value.to_string(), Backtrace::new())
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+	match value {
+		"true" => Some(true),
+		"false" => Some(false),
+		_ => None
+	}
+}
+
+fn parse_pair(value: &str, separator: char) -> Option<(f64, f64)> {
+	let mut split = value.splitn(2, separator);
+	let a: f64 = split.next()?.trim().parse().ok()?;
+	let b: f64 = split.next()?.trim().parse().ok()?;
+	if a.is_finite() && b.is_finite() {
+		Some((a, b))
+	} else {
+		None
+	}
+}
+
+fn parse_size(value: &str) -> Option<Size> {
+	parse_pair(value, 'x').map(|(width, height)| Size {width, height})
+}
+
+// Names that would be read as `primary` or an index start with a `\`.
+fn parse_monitor(value: &str) -> MonitorSelect {
+	if let Some(name) = value.strip_prefix('\\') {
+		MonitorSelect::Name(name.to_string())
+	} else if value == "primary" {
+		MonitorSelect::Primary
+	} else if let Some(index) = value.strip_prefix('#').and_then(|index| index.parse().ok()) {
+		MonitorSelect::Index(index)
+	} else {
+		MonitorSelect::Name(value.to_string())
+	}
+}
+
+fn format_monitor(select: &MonitorSelect) -> String {
+	match select {
+		&MonitorSelect::Primary => "primary".to_string(),
+		&MonitorSelect::Name(ref name) => match parse_monitor(name) {
+			MonitorSelect::Name(ref parsed) if parsed == name => name.clone(),
+			_ => format!("\\{}", name)
+		},
+		&MonitorSelect::Index(index) => format!("#{}", index)
+	}
+}
+
+fn parse_position(value: &str) -> Option<Position> {
+	if value == "default" {
+		Some(Position::Default)
+	} else if value == "centered" {
+		Some(Position::Centered(MonitorSelect::Primary))
+	} else if let Some(monitor) = value.strip_prefix("centered:") {
+		Some(Position::Centered(parse_monitor(monitor)))
+	} else {
+		parse_pair(value, ',').map(|(x, y)| Position::At(Point {x, y}))
+	}
+}
+
+fn parse_fullscreen(value: &str) -> Option<Fullscreen> {
+	let mut split = value.splitn(2, ':');
+	let kind = split.next()?;
+	let rest = split.next();
+	match (kind, rest) {
+		("disabled", None) => Some(Fullscreen::Disabled),
+		("primary", None) => Some(Fullscreen::Primary),
+		("monitor", Some(name)) => Some(Fullscreen::Monitor(name.to_string())),
+		("index", Some(index)) => index.parse().ok().map(Fullscreen::Index),
+		("borderless", None) => Some(Fullscreen::Borderless(MonitorSelect::Primary)),
+		("borderless", Some(monitor)) => Some(Fullscreen::Borderless(parse_monitor(monitor))),
+		_ => None
+	}
+}
+
+fn format_fullscreen(fullscreen: &Fullscreen) -> String {
+	match fullscreen {
+		&Fullscreen::Disabled => "disabled".to_string(),
+		&Fullscreen::Primary => "primary".to_string(),
+		&Fullscreen::Monitor(ref name) => format!("monitor:{}", name),
+		&Fullscreen::Index(index) => format!("index:{}", index),
+		&Fullscreen::Borderless(ref select) => format!("borderless:{}", format_monitor(select))
+	}
+}
+
+fn parse_renderer(value: &str) -> Option<Renderer> {
+	match value {
+		"gl" => Some(Renderer::Gl),
+		"software" => Some(Renderer::Software),
+		_ => None
+	}
+}
+
+fn parse_gl_version(value: &str) -> Option<GlVersion> {
+	let (api, version) = if let Some(version) = value.strip_prefix("gles ") {
+		(true, version)
+	} else if let Some(version) = value.strip_prefix("gl ") {
+		(false, version)
+	} else {
+		return None;
+	};
+
+	let mut split = version.trim().splitn(2, '.');
+	let major = split.next()?.parse().ok()?;
+	let minor = split.next().map_or(Some(0), |minor| minor.parse().ok())?;
+	Some(if api {
+		GlVersion::Gles(major, minor)
+	} else {
+		GlVersion::Gl(major, minor)
+	})
+}
+
+fn format_gl_version(version: GlVersion) -> String {
+	match version {
+		GlVersion::Gl(major, minor) => format!("gl {}.{}", major, minor),
+		GlVersion::Gles(major, minor) => format!("gles {}.{}", major, minor)
+	}
+}
+
+fn parse_gl_errors(value: &str) -> Option<GlErrors> {
+	match value {
+		"panic" => Some(GlErrors::Panic),
+		"report" => Some(GlErrors::Report),
+		"ignore" => Some(GlErrors::Ignore),
+		_ => None
+	}
+}
+
+fn format_gl_errors(errors: GlErrors) -> &'static str {
+	match errors {
+		GlErrors::Panic => "panic",
+		GlErrors::Report => "report",
+		GlErrors::Ignore => "ignore"
+	}
+}
+
+fn parse_string(value: &str) -> Option<String> {
+	if !value.starts_with('"') {
+		return Some(value.trim().to_string());
+	}
+
+	let mut result = String::new();
+	let mut chars = value[1..].chars();
+	loop {
+		match chars.next()? {
+			'"' => break,
+			'\\' => result.push(match chars.next()? {
+				'n' => '\n',
+				't' => '\t',
+				c => c
+			}),
+			c => result.push(c)
+		}
+	}
+
+	if chars.as_str().trim().is_empty() {
+		Some(result)
+	} else {
+		None
+	}
+}
+
+fn format_string(value: &str) -> String {
+	let mut result = String::from("\"");
+	for c in value.chars() {
+		match c {
+			'"' => result.push_str("\\\""),
+			'\\' => result.push_str("\\\\"),
+			'\n' => result.push_str("\\n"),
+			'\t' => result.push_str("\\t"),
+			c => result.push(c)
+		}
+	}
+	result.push('"');
+	result
+}
+
+impl Config {
+	/// Sets a single option by its key, as used in config files.
+	/// Unknown keys are logged with [`log`](fn.log.html) and ignored
+	/// so config files can be shared between versions.
+	pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+		let error = || invalid(key, value);
+		match key {
+			"title" => self.title = value.to_string(),
+			"size" => self.size = parse_size(value).ok_or_else(error)?,
+			"min_size" => self.min_size = match value {
+				"none" => None,
+				_ => Some(parse_size(value).ok_or_else(error)?)
+			},
+			"max_size" => self.max_size = match value {
+				"none" => None,
+				_ => Some(parse_size(value).ok_or_else(error)?)
+			},
+			"position" => self.position = parse_position(value).ok_or_else(error)?,
+			"visible" => self.visible = parse_bool(value).ok_or_else(error)?,
+			"maximized" => self.maximized = parse_bool(value).ok_or_else(error)?,
+			"resizable" => self.resizable = parse_bool(value).ok_or_else(error)?,
+			"decorations" => self.decorations = parse_bool(value).ok_or_else(error)?,
+			"transparent" => self.transparent = parse_bool(value).ok_or_else(error)?,
+			"always_on_top" => self.always_on_top = parse_bool(value).ok_or_else(error)?,
+			"fullscreen" => self.fullscreen = parse_fullscreen(value).ok_or_else(error)?,
+			"vsync" => self.vsync = parse_bool(value).ok_or_else(error)?,
+			"msaa" => self.msaa = match value.parse::<u16>() {
+				Ok(msaa) if msaa == 0 || msaa.is_power_of_two() => msaa,
+				_ => return Err(error())
+			},
+			"renderer" => self.renderer = parse_renderer(value).ok_or_else(error)?,
+			"gl_version" => self.gl_version = parse_gl_version(value).ok_or_else(error)?,
+			"gl_debug" => self.gl_debug = parse_bool(value).ok_or_else(error)?,
+			"gl_errors" => self.gl_errors = parse_gl_errors(value).ok_or_else(error)?,
+			_ => log(format!("Unknown config option: {}", key))
+		}
+		Ok(())
+	}
+
+	/// Parses `key = value` lines and sets the options they contain.
+	/// Empty lines and lines starting with `#` are ignored.
+	/// Values run to the end of the line, so `#` after a value is part of it.
+	/// Quoted values can contain `\"`, `\\`, `\n` and `\t` escapes.
+	/// Options that are not in the text keep their current value.
+	pub fn read(&mut self, text: &str) -> Result<(), ConfigError> {
+		for (index, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let syntax = || ConfigError::Syntax(index + 1, Backtrace::new());
+			let mut split = line.splitn(2, '=');
+			let key = split.next().unwrap().trim();
+			let value = split.next().ok_or_else(syntax)?.trim();
+			if key.is_empty() {
+				return Err(syntax());
+			}
+			self.set(key, &parse_string(value).ok_or_else(syntax)?)?;
+		}
+		Ok(())
+	}
+
+	/// Loads a config file written by [`save`](#method.save) or by hand.
+	/// See [`read`](#method.read) for the format.
+	pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConfigError> {
+		let text = fs::read_to_string(path)
+			.map_err(|error| ConfigError::Io(error.to_string(), Backtrace::new()))?;
+		self.read(&text)
+	}
+
+	/// Saves all options except the icon and crash handler to a config file.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+		fs::write(path, self.to_string())
+			.map_err(|error| ConfigError::Io(error.to_string(), Backtrace::new()))
+	}
+
+	/// Applies command-line overrides and returns the arguments that
+	/// were not recognised, in order, so the game can handle them.
+	///
+	/// The recognised options are `--fullscreen`, `--borderless`, `--windowed`,
+	/// `--monitor NAME`, `--size WxH`, `--position X,Y`, `--vsync`, `--no-vsync`,
+	/// `--msaa N`, `--renderer gl|software`, `--gl-debug`, `--no-gl-debug` and
+	/// `--set KEY=VALUE` for any other config option.
+	/// Values can also be passed as `--option=value`, but flags like `--vsync`
+	/// don't take a value, so `--vsync=false` is an error.
+	/// `--monitor` accepts a monitor name, `#INDEX` or `primary`
+	/// and selects the monitor used for fullscreen,
+	/// or the monitor to center on when windowed.
+	/// A `\` before a name keeps it a name, for monitors called `primary` or `#1`.
+	pub fn parse_args<I, S>(&mut self, args: I) -> Result<Vec<String>, ConfigError>
+		where I: IntoIterator<Item = S>, S: Into<String> {
+		let mut args = args.into_iter().map(Into::into);
+		let mut rest = Vec::new();
+		let mut monitor = None;
+
+		while let Some(arg) = args.next() {
+			let (option, inline) = match arg.find('=') {
+				Some(index) if arg.starts_with("--") => (arg[..index].to_string(), Some(arg[index + 1..].to_string())),
+				_ => (arg.clone(), None)
+			};
+
+			if let Some(ref inline) = inline {
+				if FLAGS.contains(&option.as_str()) {
+					return Err(invalid(&option, inline));
+				}
+			}
+
+			let value = |args: &mut dyn Iterator<Item = String>| match inline.clone() {
+				Some(value) => Ok(value),
+				None => args.next().ok_or_else(|| ConfigError::MissingValue(option.clone(), Backtrace::new()))
+			};
+
+			match option.as_str() {
+				"--fullscreen" => self.fullscreen = Fullscreen::Primary,
+				"--borderless" => self.fullscreen = Fullscreen::Borderless(MonitorSelect::Primary),
+				"--windowed" => self.fullscreen = Fullscreen::Disabled,
+				"--monitor" => monitor = Some(parse_monitor(&value(&mut args)?)),
+				"--size" => self.set("size", &value(&mut args)?)?,
+				"--position" => self.set("position", &value(&mut args)?)?,
+				"--vsync" => self.vsync = true,
+				"--no-vsync" => self.vsync = false,
+				"--msaa" => self.set("msaa", &value(&mut args)?)?,
+				"--renderer" => self.set("renderer", &value(&mut args)?)?,
+				"--gl-debug" => self.gl_debug = true,
+				"--no-gl-debug" => self.gl_debug = false,
+				"--set" => {
+					let pair = value(&mut args)?;
+					let mut split = pair.splitn(2, '=');
+					let key = split.next().unwrap();
+					let value = split.next().ok_or_else(|| invalid("--set", &pair))?;
+					self.set(key.trim(), value.trim())?;
+				},
+				_ => rest.push(arg)
+			}
+		}
+
+		if let Some(select) = monitor {
+			self.fullscreen = match self.fullscreen.clone() {
+				Fullscreen::Disabled => {
+					self.position = Position::Centered(select);
+					Fullscreen::Disabled
+				},
+				Fullscreen::Primary | Fullscreen::Monitor(_) | Fullscreen::Index(_) => match select {
+					MonitorSelect::Primary => Fullscreen::Primary,
+					MonitorSelect::Name(name) => Fullscreen::Monitor(name),
+					MonitorSelect::Index(index) => Fullscreen::Index(index)
+				},
+				Fullscreen::Borderless(_) => Fullscreen::Borderless(select)
+			};
+		}
+		Ok(rest)
+	}
+
+	/// Applies the command-line overrides from the arguments of the process.
+	/// See [`parse_args`](#method.parse_args).
+	pub fn parse_env_args(&mut self) -> Result<Vec<String>, ConfigError> {
+		self.parse_args(env::args().skip(1))
+	}
+}
+
+/// Formats the config in the format read by [`Config.read`](struct.Config.html#method.read).
+impl Display for Config {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let size = |size: Size| format_string(&format!("{}x{}", size.width, size.height));
+		writeln!(f, "title = {}", format_string(&self.title))?;
+		writeln!(f, "size = {}", size(self.size))?;
+		writeln!(f, "min_size = {}", self.min_size.map_or(format_string("none"), size))?;
+		writeln!(f, "max_size = {}", self.max_size.map_or(format_string("none"), size))?;
+		writeln!(f, "position = {}", format_string(&match self.position {
+			Position::Default => "default".to_string(),
+			Position::At(point) => format!("{},{}", point.x, point.y),
+			Position::Centered(ref select) => format!("centered:{}", format_monitor(select))
+		}))?;
+		writeln!(f, "visible = {}", self.visible)?;
+		writeln!(f, "maximized = {}", self.maximized)?;
+		writeln!(f, "resizable = {}", self.resizable)?;
+		writeln!(f, "decorations = {}", self.decorations)?;
+		writeln!(f, "transparent = {}", self.transparent)?;
+		writeln!(f, "always_on_top = {}", self.always_on_top)?;
+		writeln!(f, "fullscreen = {}", format_string(&format_fullscreen(&self.fullscreen)))?;
+		writeln!(f, "vsync = {}", self.vsync)?;
+		writeln!(f, "msaa = {}", self.msaa)?;
+		writeln!(f, "renderer = {}", format_string(match self.renderer {
+			Renderer::Gl => "gl",
+			Renderer::Software => "software"
+		}))?;
+		writeln!(f, "gl_version = {}", format_string(&format_gl_version(self.gl_version)))?;
+		writeln!(f, "gl_debug = {}", self.gl_debug)?;
+		writeln!(f, "gl_errors = {}", format_string(format_gl_errors(self.gl_errors)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn display_round_trips_through_read() {
+		let config = Config {
+			title: String::from("My #1 \"Game\"\n"),
+			size: Size {width: 1280.0, height: 720.0},
+			min_size: Some(Size {width: 320.0, height: 180.0}),
+			max_size: None,
+			position: Position::Centered(MonitorSelect::Index(2)),
+			visible: false,
+			maximized: true,
+			resizable: false,
+			decorations: false,
+			transparent: true,
+			always_on_top: true,
+			fullscreen: Fullscreen::Borderless(MonitorSelect::Name(String::from("DP-1: Main"))),
+			vsync: false,
+			msaa: 4,
+			renderer: Renderer::Software,
+			gl_version: GlVersion::Gles(3, 0),
+			gl_debug: true,
+			gl_errors: GlErrors::Ignore,
+			..Config::default()
+		};
+
+		let mut read = Config::default();
+		read.read(&config.to_string()).unwrap();
+		assert_eq!(read, config);
+	}
+
+	#[test]
+	fn comments_only_start_lines() {
+		let mut config = Config::default();
+		config.read("# A comment\n  # Another comment\ntitle = My #1 Game\nfullscreen = borderless:#1").unwrap();
+		assert_eq!(config.title, "My #1 Game");
+		assert_eq!(config.fullscreen, Fullscreen::Borderless(MonitorSelect::Index(1)));
+
+		assert!(config.read("title = \"Game\" # comment").is_err());
+		assert!(config.read("title").is_err());
+	}
+
+	#[test]
+	fn parse_args_applies_overrides() {
+		let mut config = Config::default();
+		let rest = config.parse_args(vec![
+			"--borderless", "--monitor", "#1", "--size=800x600", "--no-vsync",
+			"--set", "title=Game", "--level", "2"
+		]).unwrap();
+		assert_eq!(rest, vec!["--level", "2"]);
+		assert_eq!(config.fullscreen, Fullscreen::Borderless(MonitorSelect::Index(1)));
+		assert_eq!(config.size, Size {width: 800.0, height: 600.0});
+		assert!(!config.vsync);
+		assert_eq!(config.title, "Game");
+
+		assert!(config.parse_args(vec!["--msaa"]).is_err());
+		assert!(config.parse_args(vec!["--size", "big"]).is_err());
+	}
+
+	#[test]
+	fn flags_reject_values() {
+		let mut config = Config::default();
+		config.vsync = false;
+		assert!(config.parse_args(vec!["--vsync=false"]).is_err());
+		assert!(!config.vsync);
+		assert!(config.parse_args(vec!["--fullscreen=true"]).is_err());
+	}
+
+	#[test]
+	fn monitor_names_round_trip() {
+		for name in &["DP-1", "primary", "#1", "#name", "\\primary", ""] {
+			let select = MonitorSelect::Name(name.to_string());
+			assert_eq!(parse_monitor(&format_monitor(&select)), select);
+
+			let config = Config {fullscreen: Fullscreen::Borderless(select.clone()), ..Config::default()};
+			let mut read = Config::default();
+			read.read(&config.to_string()).unwrap();
+			assert_eq!(read.fullscreen, config.fullscreen);
+		}
+		assert_eq!(parse_monitor("primary"), MonitorSelect::Primary);
+		assert_eq!(parse_monitor("#1"), MonitorSelect::Index(1));
+		assert_eq!(parse_monitor("\\#1"), MonitorSelect::Name(String::from("#1")));
+	}
+
+	#[test]
+	fn invalid_numbers_are_rejected() {
+		let mut config = Config::default();
+		for &msaa in &["0", "1", "2", "16"] {
+			assert!(config.set("msaa", msaa).is_ok());
+		}
+		for &msaa in &["3", "6", "-2", "x"] {
+			assert!(config.set("msaa", msaa).is_err());
+		}
+		assert_eq!(config.msaa, 16);
+
+		for &size in &["NaNx100", "100xinf", "-infx1"] {
+			assert!(config.set("size", size).is_err());
+			assert!(config.set("min_size", size).is_err());
+		}
+		assert!(config.set("position", "NaN,0").is_err());
+		assert!(config.parse_args(vec!["--size", "infx600"]).is_err());
+	}
+}
\ No newline at end of file
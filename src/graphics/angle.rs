@@ -0,0 +1,238 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ::Point;
+
+use std::f64::consts::PI;
+use std::ops::{Add, Sub, Neg, Mul, Div, AddAssign, SubAssign, MulAssign, DivAssign};
+
+const RAD_PER_DEG: f64 = PI / 180.0;
+const TAU: f64 = PI * 2.0;
+
+/// An angle of something, either in degrees or radians.
+/// Serialized as radians.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Angle(f64);
+
+impl Angle {
+	/// Creates an angle from radians.
+	pub fn from_radians(rad: f64) -> Angle {
+		Angle(rad)
+	}
+
+	/// Creates an angle from degrees.
+	pub fn from_degrees(deg: f64) -> Angle {
+		Angle(deg * RAD_PER_DEG)
+	}
+
+	/// Gets the angle of the direction from one point to another.
+	/// An angle of 0 points along the positive x-axis, and
+	/// angles increase clockwise since y points down.
+	pub fn between(from: Point, to: Point) -> Angle {
+		Angle((to.y - from.y).atan2(to.x - from.x))
+	}
+
+	/// Converts an angle to radians.
+	pub fn as_radians(&self) -> f64 {
+		self.0
+	}
+
+	/// Converts an angle to degrees.
+	pub fn as_degrees(&self) -> f64 {
+		self.0 / RAD_PER_DEG
+	}
+
+	/// The sine of the angle.
+	pub fn sin(&self) -> f64 {
+		self.0.sin()
+	}
+
+	/// The cosine of the angle.
+	pub fn cos(&self) -> f64 {
+		self.0.cos()
+	}
+
+	/// The unit vector pointing in the direction of the angle.
+	pub fn to_point(&self) -> Point {
+		Point {
+			x: self.cos(),
+			y: self.sin()
+		}
+	}
+
+	/// Wraps the angle to be at least 0 and less than a full turn.
+	pub fn normalize(&self) -> Angle {
+		let rad = self.0 % TAU;
+		if rad < 0.0 {
+			// Adding can round up to exactly a full turn.
+			let rad = rad + TAU;
+			Angle(if rad >= TAU {0.0} else {rad})
+		} else {
+			Angle(rad)
+		}
+	}
+
+	/// Wraps the angle to be greater than negative half a turn
+	/// and at most half a turn.
+	pub fn normalize_signed(&self) -> Angle {
+		let rad = self.normalize().0;
+		Angle(if rad > PI {rad - TAU} else {rad})
+	}
+
+	/// The signed shortest turn from this angle to the other,
+	/// between negative half a turn and half a turn.
+	pub fn difference(&self, other: Angle) -> Angle {
+		(other - *self).normalize_signed()
+	}
+
+	/// Interpolates along the shortest turn between two angles,
+	/// where an amount of 0 is `self` and 1 is `other`.
+	pub fn lerp(&self, other: Angle, amount: f64) -> Angle {
+		*self + self.difference(other) * amount
+	}
+}
+
+impl Add for Angle {
+	type Output = Angle;
+	fn add(self, other: Angle) -> Angle {
+		Angle(self.0 + other.0)
+	}
+}
+
+impl Sub for Angle {
+	type Output = Angle;
+	fn sub(self, other: Angle) -> Angle {
+		Angle(self.0 - other.0)
+	}
+}
+
+impl Neg for Angle {
+	type Output = Angle;
+	fn neg(self) -> Angle {
+		Angle(-self.0)
+	}
+}
+
+impl Mul<f64> for Angle {
+	type Output = Angle;
+	fn mul(self, scalar: f64) -> Angle {
+		Angle(self.0 * scalar)
+	}
+}
+
+impl Mul<Angle> for f64 {
+	type Output = Angle;
+	fn mul(self, angle: Angle) -> Angle {
+		angle * self
+	}
+}
+
+impl Div<f64> for Angle {
+	type Output = Angle;
+	fn div(self, scalar: f64) -> Angle {
+		Angle(self.0 / scalar)
+	}
+}
+
+impl AddAssign for Angle {
+	fn add_assign(&mut self, other: Angle) {
+		self.0 += other.0;
+	}
+}
+
+impl SubAssign for Angle {
+	fn sub_assign(&mut self, other: Angle) {
+		self.0 -= other.0;
+	}
+}
+
+impl MulAssign<f64> for Angle {
+	fn mul_assign(&mut self, scalar: f64) {
+		self.0 *= scalar;
+	}
+}
+
+impl DivAssign<f64> for Angle {
+	fn div_assign(&mut self, scalar: f64) {
+		self.0 /= scalar;
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_near(a: Angle, b: f64) {
+		assert!((a.as_radians() - b).abs() < 1e-9, "{} is not {}", a.as_radians(), b);
+	}
+
+	#[test]
+	fn normalize_stays_below_a_full_turn() {
+		assert_near(Angle::from_radians(0.0).normalize(), 0.0);
+		assert_near(Angle::from_radians(TAU).normalize(), 0.0);
+		assert_near(Angle::from_radians(-TAU).normalize(), 0.0);
+		assert_near(Angle::from_radians(3.0 * PI).normalize(), PI);
+		assert_near(Angle::from_radians(-PI / 2.0).normalize(), 1.5 * PI);
+		assert_near(Angle::from_degrees(-720.0 - 90.0).normalize(), 1.5 * PI);
+
+		// Adding a full turn to a tiny negative angle rounds to exactly a full turn.
+		assert_eq!(Angle::from_radians(-1e-17).normalize().as_radians(), 0.0);
+
+		for step in -1000..1000 {
+			let rad = Angle::from_radians(step as f64 * 0.0123 - 1e-16).normalize().as_radians();
+			assert!(rad >= 0.0 && rad < TAU, "{} is not in a turn", rad);
+		}
+	}
+
+	#[test]
+	fn normalize_signed_includes_half_a_turn() {
+		assert_near(Angle::from_radians(PI).normalize_signed(), PI);
+		assert_near(Angle::from_radians(-PI).normalize_signed(), PI);
+		assert_near(Angle::from_radians(1.5 * PI).normalize_signed(), -0.5 * PI);
+		assert_near(Angle::from_radians(-0.25 * PI).normalize_signed(), -0.25 * PI);
+		assert_near(Angle::from_radians(TAU).normalize_signed(), 0.0);
+		assert_eq!(Angle::from_radians(-1e-17).normalize_signed().as_radians(), 0.0);
+	}
+
+	#[test]
+	fn difference_takes_the_shortest_turn() {
+		let degrees = |deg: f64| Angle::from_degrees(deg);
+		assert_near(degrees(350.0).difference(degrees(10.0)), 20.0 * RAD_PER_DEG);
+		assert_near(degrees(10.0).difference(degrees(350.0)), -20.0 * RAD_PER_DEG);
+		assert_near(degrees(-170.0).difference(degrees(170.0)), -20.0 * RAD_PER_DEG);
+		assert_near(degrees(0.0).difference(degrees(720.0)), 0.0);
+		assert_near(degrees(0.0).difference(degrees(180.0)), PI);
+	}
+
+	#[test]
+	fn lerp_takes_the_shortest_turn() {
+		let degrees = |deg: f64| Angle::from_degrees(deg);
+		assert_near(degrees(350.0).lerp(degrees(10.0), 0.0), 350.0 * RAD_PER_DEG);
+		assert_near(degrees(350.0).lerp(degrees(10.0), 0.5).normalize(), 0.0);
+		assert_near(degrees(350.0).lerp(degrees(10.0), 1.0), 370.0 * RAD_PER_DEG);
+		assert_near(degrees(90.0).lerp(degrees(0.0), 0.5), 45.0 * RAD_PER_DEG);
+	}
+
+	#[test]
+	fn between_increases_clockwise() {
+		let origin = Point {x: 1.0, y: 1.0};
+		assert_near(Angle::between(origin, Point {x: 2.0, y: 1.0}), 0.0);
+		assert_near(Angle::between(origin, Point {x: 1.0, y: 2.0}), PI / 2.0);
+		assert_near(Angle::between(origin, Point {x: 0.0, y: 1.0}), PI);
+		assert_near(Angle::between(origin, Point {x: 1.0, y: 0.0}), -PI / 2.0);
+		assert_near(Angle::between(origin, origin), 0.0);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,360 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate backtrace;
+use self::backtrace::Backtrace;
+
+use ::{Size, Point, Rect};
+use super::{Graphics, Image, Texture, TextureFilter, GraphicsError};
+
+use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::path::Path;
+use std::rc::Rc;
+
+/// A named region of a texture in an [`Atlas`](struct.Atlas.html).
+/// Drawn with [`Graphics.draw_sprite`](struct.Graphics.html#method.draw_sprite).
+#[derive(Clone)]
+pub struct Sprite {
+	texture: Rc<Texture>,
+	region: Rect
+}
+
+impl Sprite {
+	/// Gets the texture the sprite is part of.
+	pub fn get_texture(&self) -> &Texture {
+		&self.texture
+	}
+
+	/// Gets the area of the texture the sprite covers, in pixels.
+	pub fn get_region(&self) -> Rect {
+		self.region
+	}
+
+	/// Gets the size of the sprite in pixels.
+	pub fn get_size(&self) -> Size {
+		self.region.size
+	}
+}
+
+/// Collects images to be packed into an atlas by
+/// [`Graphics.create_atlas`](struct.Graphics.html#method.create_atlas).
+#[derive(Clone, Debug)]
+pub struct AtlasBuilder {
+	images: Vec<(String, Image)>,
+	max_size: u32,
+	padding: u32,
+	filter: TextureFilter
+}
+
+impl AtlasBuilder {
+	/// Creates a builder with no images, where textures are at most 2048 pixels wide and high,
+	/// images are 1 pixel apart and textures use
+	/// [`TextureFilter::Nearest`](enum.TextureFilter.html#variant.Nearest).
+	pub fn new() -> AtlasBuilder {
+		AtlasBuilder {
+			images: Vec::new(),
+			max_size: 2048,
+			padding: 1,
+			filter: TextureFilter::Nearest
+		}
+	}
+
+	/// Adds an image with a name, replacing any image that was added with the name before.
+	/// Pixel buffers can be added with [`Image::from_rgba`](struct.Image.html#method.from_rgba).
+	pub fn add<S: Into<String>>(&mut self, name: S, image: Image) -> &mut AtlasBuilder {
+		let name = name.into();
+		self.images.retain(|&(ref other, _)| *other != name);
+		self.images.push((name, image));
+		self
+	}
+
+	/// Loads an image from a file and adds it with a name.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidImage`](enum.GraphicsError.html#variant.InvalidImage)
+	/// if the file could not be read or decoded.
+	pub fn add_file<S: Into<String>, P: AsRef<Path>>(&mut self, name: S, path: P) -> Result<&mut AtlasBuilder, GraphicsError> {
+		let image = Image::from_file(path)?;
+		Ok(self.add(name, image))
+	}
+
+	/// Sets the largest width and height of each texture.
+	/// Images that don't fit in one texture are packed into more.
+	pub fn max_size(&mut self, max_size: u32) -> &mut AtlasBuilder {
+		self.max_size = max_size;
+		self
+	}
+
+	/// Sets how many empty pixels are left between images,
+	/// so filtering doesn't blend them together.
+	pub fn padding(&mut self, padding: u32) -> &mut AtlasBuilder {
+		self.padding = padding;
+		self
+	}
+
+	/// Sets the filter the textures use when scaled.
+	pub fn filter(&mut self, filter: TextureFilter) -> &mut AtlasBuilder {
+		self.filter = filter;
+		self
+	}
+
+	/// Packs the images into textures.
+	pub(crate) fn build(&self, graphics: &mut Graphics) -> Result<Atlas, GraphicsError> {
+		if let Some(&(ref name, ref image)) = self.images.iter()
+			.find(|&&(_, ref image)| image.get_width() > self.max_size || image.get_height() > self.max_size) {
+			return Err(GraphicsError::InvalidImage(
+				format!("{} is {}x{}, which is larger than the atlas size of {}",
+					name, image.get_width(), image.get_height(), self.max_size),
+				Backtrace::new()
+			));
+		}
+
+		let pages = self.pack();
+		let mut sprites = HashMap::with_capacity(self.images.len());
+		let mut textures = Vec::with_capacity(pages.len());
+		for page in pages {
+			let mut image = Image::from_rgba(vec![0; page.width as usize * page.height as usize * 4], page.width, page.height)?;
+			for &(index, x, y) in &page.images {
+				image.blit(&self.images[index].1, x, y);
+			}
+			let texture = Rc::new(graphics.create_texture_with_filter(&image, self.filter));
+			for &(index, x, y) in &page.images {
+				let (ref name, ref image) = self.images[index];
+				sprites.insert(name.clone(), Sprite {
+					texture: texture.clone(),
+					region: Rect {
+						origin: Point {x: x as f64, y: y as f64},
+						size: image.get_size()
+					}
+				});
+			}
+			textures.push(texture);
+		}
+		Ok(Atlas {sprites, textures})
+	}
+
+	/// Places the images on shelves, tallest first, starting a new page
+	/// when an image doesn't fit on any of the others.
+	fn pack(&self) -> Vec<Page> {
+		let padding = self.padding;
+		let mut order: Vec<usize> = (0..self.images.len()).collect();
+		order.sort_by_key(|&index| {
+			let image = &self.images[index].1;
+			(Reverse(image.get_height()), Reverse(image.get_width()))
+		});
+
+		// Pages are roughly square, but at least as wide as the widest image.
+		let area: u64 = self.images.iter()
+			.map(|&(_, ref image)| (image.get_width() + padding) as u64 * (image.get_height() + padding) as u64)
+			.sum();
+		let widest = self.images.iter().map(|&(_, ref image)| image.get_width()).max().unwrap_or(0);
+		let width = ((area as f64).sqrt().ceil() as u32).next_power_of_two()
+			.max(widest)
+			.min(self.max_size);
+
+		let mut pages: Vec<Page> = Vec::new();
+		for index in order {
+			let image = &self.images[index].1;
+			let (image_width, image_height) = (image.get_width(), image.get_height());
+
+			let placed = pages.iter_mut().any(|page| page.place(index, image_width, image_height, padding));
+			if !placed {
+				let mut page = Page {
+					width,
+					height: 0,
+					shelves: Vec::new(),
+					images: Vec::new(),
+					max_height: self.max_size
+				};
+				page.place(index, image_width, image_height, padding);
+				pages.push(page);
+			}
+		}
+
+		for page in &mut pages {
+			page.width = page.images.iter()
+				.map(|&(index, x, _)| x + self.images[index].1.get_width())
+				.max()
+				.unwrap_or(0)
+				.max(1);
+			page.height = page.height.max(1);
+		}
+		pages
+	}
+}
+
+impl Default for AtlasBuilder {
+	fn default() -> AtlasBuilder {
+		AtlasBuilder::new()
+	}
+}
+
+/// A row of images on a page.
+#[derive(Copy, Clone, Debug)]
+struct Shelf {
+	y: u32,
+	height: u32,
+	// Where the next image goes.
+	x: u32
+}
+
+/// A texture being packed, with the index and position of each image.
+#[derive(Clone, Debug)]
+struct Page {
+	width: u32,
+	height: u32,
+	shelves: Vec<Shelf>,
+	images: Vec<(usize, u32, u32)>,
+	max_height: u32
+}
+
+impl Page {
+	/// Places an image on the first shelf it fits on,
+	/// or on a new shelf if there is space below the others.
+	fn place(&mut self, index: usize, width: u32, height: u32, padding: u32) -> bool {
+		let page_width = self.width;
+		let shelf = self.shelves.iter_mut()
+			.find(|shelf| shelf.height >= height && shelf.x + width <= page_width);
+		let (x, y) = match shelf {
+			Some(shelf) => {
+				shelf.x += width + padding;
+				(shelf.x - width - padding, shelf.y)
+			},
+			None => {
+				let y = self.shelves.last().map_or(0, |shelf| shelf.y + shelf.height + padding);
+				if y + height > self.max_height || width > page_width {
+					return false;
+				}
+				self.shelves.push(Shelf {y, height, x: width + padding});
+				(0, y)
+			}
+		};
+		self.height = self.height.max(y + height);
+		self.images.push((index, x, y));
+		true
+	}
+}
+
+/// Images packed into as few textures as possible, so drawing them
+/// doesn't flush the batch every time the texture changes.
+/// Created with [`Graphics.create_atlas`](struct.Graphics.html#method.create_atlas).
+pub struct Atlas {
+	sprites: HashMap<String, Sprite>,
+	textures: Vec<Rc<Texture>>
+}
+
+impl Atlas {
+	/// Gets the sprite that was added with the name.
+	pub fn get(&self, name: &str) -> Option<&Sprite> {
+		self.sprites.get(name)
+	}
+
+	/// Gets the names of every sprite, in no particular order.
+	pub fn get_names(&self) -> Vec<&str> {
+		self.sprites.keys().map(|name| name.as_str()).collect()
+	}
+
+	/// Gets how many textures the images were packed into.
+	pub fn get_texture_count(&self) -> usize {
+		self.textures.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn image(width: u32, height: u32) -> Image {
+		Image::from_rgba(vec![255; width as usize * height as usize * 4], width, height).unwrap()
+	}
+
+	fn with_sizes(sizes: &[(u32, u32)], max_size: u32, padding: u32) -> AtlasBuilder {
+		let mut builder = AtlasBuilder::new();
+		builder.max_size(max_size).padding(padding);
+		for (index, &(width, height)) in sizes.iter().enumerate() {
+			builder.add(index.to_string(), image(width, height));
+		}
+		builder
+	}
+
+	/// Gets the region of each image on each page, in the order they were placed.
+	fn regions(builder: &AtlasBuilder) -> Vec<Vec<(u32, u32, u32, u32)>> {
+		builder.pack().iter().map(|page| {
+			assert!(page.width <= builder.max_size && page.height <= builder.max_size);
+			page.images.iter().map(|&(index, x, y)| {
+				let image = &builder.images[index].1;
+				assert!(x + image.get_width() <= page.width && y + image.get_height() <= page.height);
+				(x, y, image.get_width(), image.get_height())
+			}).collect()
+		}).collect()
+	}
+
+	#[test]
+	fn full_pages_overflow_onto_another() {
+		let builder = with_sizes(&[(2, 2); 5], 4, 0);
+		assert_eq!(regions(&builder), [
+			vec![(0, 0, 2, 2), (2, 0, 2, 2), (0, 2, 2, 2), (2, 2, 2, 2)],
+			vec![(0, 0, 2, 2)]
+		]);
+
+		let mut graphics = Graphics::software(1, 1);
+		let atlas = graphics.create_atlas(&builder).unwrap();
+		assert_eq!(atlas.get_texture_count(), 2);
+		assert_eq!(atlas.get_names().len(), 5);
+	}
+
+	#[test]
+	fn images_can_be_as_wide_as_the_maximum() {
+		let builder = with_sizes(&[(8, 2), (8, 3)], 8, 1);
+		assert_eq!(regions(&builder), [vec![(0, 0, 8, 3), (0, 4, 8, 2)]]);
+
+		let mut graphics = Graphics::software(1, 1);
+		assert!(graphics.create_atlas(&builder).is_ok());
+		let builder = with_sizes(&[(9, 1)], 8, 1);
+		assert!(graphics.create_atlas(&builder).is_err());
+	}
+
+	#[test]
+	fn zero_sized_images_are_packed() {
+		let builder = with_sizes(&[(0, 0), (2, 2), (3, 0)], 8, 1);
+		let pages = builder.pack();
+		assert_eq!(pages.len(), 1);
+		assert_eq!(pages[0].images.len(), 3);
+
+		let empty = with_sizes(&[(0, 0)], 8, 1).pack();
+		assert_eq!((empty[0].width, empty[0].height), (1, 1));
+
+		let mut graphics = Graphics::software(1, 1);
+		let atlas = graphics.create_atlas(&builder).unwrap();
+		assert_eq!(atlas.get("0").unwrap().get_size(), Size {width: 0.0, height: 0.0});
+		assert_eq!(atlas.get_texture_count(), 1);
+	}
+
+	#[test]
+	fn neighbours_are_padded_apart() {
+		let sizes = [(5, 4), (3, 4), (4, 2), (2, 2), (1, 1), (6, 3), (2, 5)];
+		for &padding in &[0, 1, 3] {
+			for page in regions(&with_sizes(&sizes, 16, padding)) {
+				for (index, &(x, y, width, height)) in page.iter().enumerate() {
+					for &(other_x, other_y, other_width, other_height) in &page[index + 1..] {
+						let apart = x + width + padding <= other_x || other_x + other_width + padding <= x ||
+							y + height + padding <= other_y || other_y + other_height + padding <= y;
+						assert!(apart, "{:?} and {:?} are closer than {}",
+							(x, y, width, height), (other_x, other_y, other_width, other_height), padding);
+					}
+				}
+			}
+		}
+	}
+}
\ No newline at end of file
@@ -0,0 +1,170 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ::{Size, Rect};
+use ::core::WindowError;
+use super::{Color, Image, GraphicsError, BlendMode};
+
+/// A 2D point stored as 32-bit floats.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[repr(C)]
+pub struct Point32 {
+	/// The x-coordinate of the point.
+	pub x: f32,
+
+	/// The y-coordinate of the point.
+	pub y: f32
+}
+
+/// An RGBA color stored as 8-bit integers.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[repr(C)]
+pub struct Color8 {
+	/// The red channel.
+	pub red:   u8,
+
+	/// The green channel.
+	pub green: u8,
+
+	/// The blue channel.
+	pub blue:  u8,
+
+	/// The alpha channel.
+	pub alpha: u8
+}
+
+impl From<Color> for Color8 {
+	/// Converts the color, clamping channels outside of 0 and 1.
+	fn from(color: Color) -> Color8 {
+		let [red, green, blue, alpha] = color.to_rgba8();
+		Color8 {red, green, blue, alpha}
+	}
+}
+
+/// How a texture is sampled between its pixels.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TextureFilter {
+	/// Uses the closest pixel, which keeps pixel art sharp.
+	Nearest,
+
+	/// Blends the four closest pixels, which keeps scaled images smooth.
+	Linear
+}
+
+/// How drawing interacts with the mask started by
+/// [`Graphics.begin_mask`](struct.Graphics.html#method.begin_mask).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MaskMode {
+	/// The mask is ignored.
+	Disabled,
+
+	/// Drawing adds to the mask instead of changing any colors.
+	Write,
+
+	/// Drawing is only visible inside the mask.
+	Inside,
+
+	/// Drawing is only visible outside the mask.
+	Outside
+}
+
+/// The state that applies to a whole batch of triangles.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DrawState {
+	/// The texture created with
+	/// [`RenderBackend.create_texture`](trait.RenderBackend.html#tymethod.create_texture).
+	pub texture: u32,
+
+	/// How the triangles are blended with the target.
+	pub blend: BlendMode,
+
+	/// The area outside of which nothing is drawn, in whole pixels from the
+	/// top-left of the target. If `None`, the whole target is drawn to.
+	pub clip: Option<Rect>,
+
+	/// How the triangles interact with the mask.
+	pub mask: MaskMode
+}
+
+/// A vertex sent to a [`RenderBackend`](trait.RenderBackend.html).
+/// The point has already been transformed by the current state.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[repr(C)]
+pub struct Vertex {
+	/// The position of the vertex, in pixels from the top-left of the target.
+	pub point: Point32,
+
+	/// The texture coordinate of the vertex, between 0 and 1.
+	pub texcoord: Point32,
+
+	/// The color that the texture is multiplied with.
+	pub color: Color8
+}
+
+/// Draws the batched triangles created by [`Graphics`](struct.Graphics.html).
+///
+/// Textures are identified by the number returned from
+/// [`create_texture`](#tymethod.create_texture).
+pub trait RenderBackend {
+	/// Gets the size of the render target in pixels.
+	fn get_size(&self) -> Size;
+
+	/// Uploads an image and returns the texture that can be used with
+	/// [`draw`](#tymethod.draw).
+	/// Texture coordinates outside of 0 and 1 are clamped to the edge.
+	fn create_texture(&mut self, image: &Image) -> u32;
+
+	/// Uploads an image like [`create_texture`](#tymethod.create_texture)
+	/// that is sampled with the filter when scaled.
+	/// By default the filter is ignored, for backends that only have one.
+	fn create_texture_with_filter(&mut self, image: &Image, _filter: TextureFilter) -> u32 {
+		self.create_texture(image)
+	}
+
+	/// Replaces part of a texture with an image,
+	/// with the top-left of the image at the pixel.
+	fn update_texture(&mut self, texture: u32, x: u32, y: u32, image: &Image);
+
+	/// Deletes a texture created with
+	/// [`create_texture`](#tymethod.create_texture).
+	fn delete_texture(&mut self, texture: u32);
+
+	/// Clears the whole target with the color.
+	fn clear(&mut self, color: Color);
+
+	/// Clears the mask so nothing is inside it.
+	fn clear_mask(&mut self);
+
+	/// Draws triangles with the state.
+	/// Every three elements index the vertices of a triangle.
+	fn draw(&mut self, state: &DrawState, vertices: &[Vertex], elements: &[u16]);
+
+	/// Reads the pixels of the target, from the top-left.
+	fn read_pixels(&mut self) -> Image;
+
+	/// Shows the target on the screen, if there is one,
+	/// and prepares for the next frame.
+	fn present(&mut self) -> Result<(), WindowError> {
+		Ok(())
+	}
+
+	/// Returns the first error since the last call, if any.
+	fn take_error(&mut self) -> Option<GraphicsError> {
+		None
+	}
+
+	/// Informs the backend of the public method that is currently drawing
+	/// so it can be included in debug messages.
+	fn set_call(&mut self, _call: &'static str) {}
+}
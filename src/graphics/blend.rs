@@ -0,0 +1,82 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::backend::Color8;
+
+/// How drawn colors are combined with the colors already drawn.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BlendMode {
+	/// Draws over the target using the alpha as the opacity.
+	Alpha,
+
+	/// Like [`Alpha`](#variant.Alpha), but for textures and colors whose
+	/// red, green and blue have already been multiplied by the alpha.
+	Premultiplied,
+
+	/// Adds to the target, scaled by the alpha.
+	/// Useful for glow, fire and particles.
+	Additive,
+
+	/// Multiplies the target, scaled by the alpha.
+	/// Useful for shadows and tinting.
+	Multiply,
+
+	/// The inverse of multiplying the inverses, which brightens the target.
+	Screen,
+
+	/// Replaces the target, including its alpha.
+	Replace
+}
+
+impl Default for BlendMode {
+	fn default() -> BlendMode {
+		BlendMode::Alpha
+	}
+}
+
+fn div255(value: u32) -> u32 {
+	(value + 127) / 255
+}
+
+/// Blends a source color into a target pixel in the same way as the OpenGL backend.
+pub fn blend(mode: BlendMode, source: Color8, target: &mut [u8]) {
+	let alpha = source.alpha as u32;
+	let inverse = 255 - alpha;
+	let source = [source.red as u32, source.green as u32, source.blue as u32];
+	let target_alpha = target[3] as u32;
+
+	for i in 0..3 {
+		let s = source[i];
+		let d = target[i] as u32;
+		let result = match mode {
+			BlendMode::Alpha => div255(s * alpha + d * inverse),
+			BlendMode::Premultiplied => s + div255(d * inverse),
+			BlendMode::Additive => d + div255(s * alpha),
+			BlendMode::Multiply => div255(div255(s * alpha) * d + d * inverse),
+			BlendMode::Screen => {
+				let s = div255(s * alpha);
+				s + div255(d * (255 - s))
+			},
+			BlendMode::Replace => s
+		};
+		target[i] = result.min(255) as u8;
+	}
+
+	target[3] = match mode {
+		BlendMode::Alpha | BlendMode::Premultiplied => alpha + div255(target_alpha * inverse),
+		BlendMode::Replace => alpha,
+		_ => target_alpha
+	}.min(255) as u8;
+}
\ No newline at end of file
@@ -0,0 +1,169 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ::{Size, Point, Rect};
+use super::Angle;
+
+/// A view of the world drawn into part of the target.
+/// Apply it with [`Graphics.camera`](struct.Graphics.html#method.camera).
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Camera {
+	/// The point in the world shown at the center of the viewport.
+	pub position: Point,
+
+	/// How much the world is scaled, where 2 shows everything twice as big.
+	pub zoom: f64,
+
+	/// How much the view is rotated clockwise,
+	/// which turns the world the other way.
+	pub rotation: Angle,
+
+	/// The area of the target that is drawn to.
+	pub viewport: Rect
+}
+
+impl Camera {
+	/// Creates a camera that shows the world unchanged in the viewport.
+	pub fn new(viewport: Rect) -> Camera {
+		Camera {
+			position: viewport.center(),
+			zoom: 1.0,
+			rotation: Angle::default(),
+			viewport
+		}
+	}
+
+	/// Converts a point in the world to where it is drawn in the viewport.
+	pub fn to_screen(&self, point: Point) -> Point {
+		((point - self.position) * self.zoom).rotate(-self.rotation) + self.viewport.center()
+	}
+
+	/// Converts a point in the viewport, such as the cursor, to the point in the world.
+	pub fn to_world(&self, point: Point) -> Point {
+		((point - self.viewport.center()) / self.zoom).rotate(self.rotation) + self.position
+	}
+
+	/// Gets the bounds of the area of the world that can be seen.
+	pub fn get_bounds(&self) -> Rect {
+		let viewport = self.viewport;
+		let corners = [
+			self.to_world(viewport.top_left()),
+			self.to_world(viewport.top_right()),
+			self.to_world(viewport.bottom_left()),
+			self.to_world(viewport.bottom_right())
+		];
+		corners.iter().skip(1).fold(Rect::from_points(corners[0], corners[0]), |bounds, &corner| {
+			bounds.union(&Rect::from_points(corner, corner))
+		})
+	}
+}
+
+/// How a virtual resolution is scaled to fit the target.
+/// See [`Graphics.set_virtual_size`](struct.Graphics.html#method.set_virtual_size).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Scaling {
+	/// Scales as much as fits while keeping the aspect ratio,
+	/// filling the remaining space with letterboxing.
+	Fit,
+
+	/// Scales by the largest whole number that fits, at least 1,
+	/// so every virtual pixel is the same size.
+	Integer,
+
+	/// Stretches to fill the whole target.
+	Stretch
+}
+
+impl Default for Scaling {
+	fn default() -> Scaling {
+		Scaling::Fit
+	}
+}
+
+impl Scaling {
+	/// Gets the area of the target that the virtual size is scaled to.
+	/// The virtual size must be positive.
+	pub fn get_viewport(self, virtual_size: Size, target: Size) -> Rect {
+		let width = target.width / virtual_size.width;
+		let height = target.height / virtual_size.height;
+		let scale = match self {
+			Scaling::Fit => {
+				let scale = width.min(height);
+				Size {width: scale, height: scale}
+			},
+			Scaling::Integer => {
+				let scale = width.min(height).floor().max(1.0);
+				Size {width: scale, height: scale}
+			},
+			Scaling::Stretch => Size {width, height}
+		};
+		let size = Size {
+			width: virtual_size.width * scale.width,
+			height: virtual_size.height * scale.height
+		};
+		Rect {
+			origin: Point {
+				x: ((target.width - size.width) / 2.0).floor(),
+				y: ((target.height - size.height) / 2.0).floor()
+			},
+			size
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::Graphics;
+
+	#[test]
+	fn scaling_fits_the_target() {
+		let target = Size::new(100.0, 60.0);
+		let size = Size::new(40.0, 20.0);
+		assert_eq!(Scaling::Fit.get_viewport(size, target), Rect::new(0.0, 5.0, 100.0, 50.0));
+		assert_eq!(Scaling::Integer.get_viewport(size, target), Rect::new(10.0, 10.0, 80.0, 40.0));
+		assert_eq!(Scaling::Stretch.get_viewport(size, target), Rect::new(0.0, 0.0, 100.0, 60.0));
+
+		// Integer scaling never shrinks below one target pixel per virtual pixel.
+		assert_eq!(Scaling::Integer.get_viewport(size, Size::new(30.0, 10.0)), Rect::new(-5.0, -5.0, 40.0, 20.0));
+	}
+
+	#[test]
+	fn virtual_size_must_be_positive() {
+		let mut graphics = Graphics::software(8, 8);
+		assert!(graphics.set_virtual_size(Size::new(0.0, 4.0), Scaling::Fit).is_err());
+		assert!(graphics.set_virtual_size(Size::new(4.0, -1.0), Scaling::Fit).is_err());
+		assert!(graphics.set_virtual_size(Size::new(::std::f64::NAN, 4.0), Scaling::Fit).is_err());
+		assert_eq!(graphics.get_viewport(), Rect::new(0.0, 0.0, 8.0, 8.0));
+
+		graphics.set_virtual_size(Size::new(4.0, 2.0), Scaling::Fit).unwrap();
+		assert_eq!(graphics.get_viewport(), Rect::new(0.0, 2.0, 8.0, 4.0));
+		assert_eq!(graphics.to_virtual(Point::new(4.0, 4.0)), Point::new(2.0, 1.0));
+	}
+
+	#[test]
+	fn camera_converts_both_ways() {
+		let mut camera = Camera::new(Rect::new(0.0, 0.0, 100.0, 50.0));
+		camera.position = Point::new(10.0, 20.0);
+		camera.zoom = 2.0;
+		camera.rotation = Angle::from_degrees(30.0);
+		assert_eq!(camera.to_screen(camera.position), Point::new(50.0, 25.0));
+
+		let point = Point::new(-3.0, 7.0);
+		let back = camera.to_world(camera.to_screen(point));
+		assert!(back.distance(point) < 1e-9);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,412 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::Angle;
+
+use std::ops::{Mul, MulAssign};
+
+/// An RGBA color of something.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Color {
+	/// The red channel.
+	/// Should be between 0 and 1.
+	pub red: f64,
+
+	/// The green channel.
+	/// Should be between 0 and 1.
+	pub green: f64,
+
+	/// The blue channel.
+	/// Should be between 0 and 1.
+	pub blue: f64,
+
+	/// The alpha channel.
+	/// Should be between 0 and 1.
+	pub alpha: f64
+}
+
+fn channel_to_u8(value: f64) -> u8 {
+	(value.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+fn srgb_to_linear(value: f64) -> f64 {
+	if value <= 0.04045 {
+		value / 12.92
+	} else {
+		((value + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+fn linear_to_srgb(value: f64) -> f64 {
+	if value <= 0.0031308 {
+		value * 12.92
+	} else {
+		1.055 * value.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+// Gets the red, green and blue channels from a hue, chroma and offset,
+// which is shared by the HSV and HSL conversions.
+fn from_hue(hue: Angle, chroma: f64, offset: f64, alpha: f64) -> Color {
+	let hue = (hue.as_degrees() % 360.0 + 360.0) % 360.0 / 60.0;
+	let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+	let (red, green, blue) = match hue as u32 {
+		0 => (chroma, x, 0.0),
+		1 => (x, chroma, 0.0),
+		2 => (0.0, chroma, x),
+		3 => (0.0, x, chroma),
+		4 => (x, 0.0, chroma),
+		_ => (chroma, 0.0, x)
+	};
+	Color::new(red + offset, green + offset, blue + offset, alpha)
+}
+
+impl Color {
+	/// Fully transparent black.
+	pub const TRANSPARENT: Color = Color {red: 0.0, green: 0.0, blue: 0.0, alpha: 0.0};
+
+	/// Opaque black.
+	pub const BLACK: Color = Color {red: 0.0, green: 0.0, blue: 0.0, alpha: 1.0};
+
+	/// Opaque white.
+	pub const WHITE: Color = Color {red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0};
+
+	/// Opaque 50% gray.
+	pub const GRAY: Color = Color {red: 0.5, green: 0.5, blue: 0.5, alpha: 1.0};
+
+	/// Opaque red.
+	pub const RED: Color = Color {red: 1.0, green: 0.0, blue: 0.0, alpha: 1.0};
+
+	/// Opaque green.
+	pub const GREEN: Color = Color {red: 0.0, green: 1.0, blue: 0.0, alpha: 1.0};
+
+	/// Opaque blue.
+	pub const BLUE: Color = Color {red: 0.0, green: 0.0, blue: 1.0, alpha: 1.0};
+
+	/// Opaque yellow.
+	pub const YELLOW: Color = Color {red: 1.0, green: 1.0, blue: 0.0, alpha: 1.0};
+
+	/// Opaque cyan.
+	pub const CYAN: Color = Color {red: 0.0, green: 1.0, blue: 1.0, alpha: 1.0};
+
+	/// Opaque magenta.
+	pub const MAGENTA: Color = Color {red: 1.0, green: 0.0, blue: 1.0, alpha: 1.0};
+
+	/// A utility method that makes it neater to create a color.
+	pub fn new(red: f64, green: f64, blue: f64, alpha: f64) -> Color {
+		Color {red, green, blue, alpha}
+	}
+
+	/// Creates a color from channels between 0 and 255.
+	pub fn from_rgba8(red: u8, green: u8, blue: u8, alpha: u8) -> Color {
+		Color {
+			red:   red   as f64 / 255.0,
+			green: green as f64 / 255.0,
+			blue:  blue  as f64 / 255.0,
+			alpha: alpha as f64 / 255.0
+		}
+	}
+
+	/// Converts the color to channels between 0 and 255.
+	/// Channels outside of 0 and 1 are clamped.
+	pub fn to_rgba8(&self) -> [u8; 4] {
+		[
+			channel_to_u8(self.red),
+			channel_to_u8(self.green),
+			channel_to_u8(self.blue),
+			channel_to_u8(self.alpha)
+		]
+	}
+
+	/// Parses a CSS-style hex color, with or without the leading `#`.
+	/// Supports the `rgb`, `rgba`, `rrggbb` and `rrggbbaa` forms.
+	/// Returns `None` if the text is not a valid hex color.
+	pub fn from_hex(hex: &str) -> Option<Color> {
+		let hex = hex.strip_prefix('#').unwrap_or(hex);
+		if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+			return None;
+		}
+
+		let short = |index: usize| u8::from_str_radix(&hex[index..index + 1], 16).ok().map(|v| v * 17);
+		let long = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+		match hex.len() {
+			3 => Some(Color::from_rgba8(short(0)?, short(1)?, short(2)?, 255)),
+			4 => Some(Color::from_rgba8(short(0)?, short(1)?, short(2)?, short(3)?)),
+			6 => Some(Color::from_rgba8(long(0)?, long(2)?, long(4)?, 255)),
+			8 => Some(Color::from_rgba8(long(0)?, long(2)?, long(4)?, long(6)?)),
+			_ => None
+		}
+	}
+
+	/// Formats the color as `#rrggbbaa`.
+	pub fn to_hex(&self) -> String {
+		let [red, green, blue, alpha] = self.to_rgba8();
+		format!("#{:02x}{:02x}{:02x}{:02x}", red, green, blue, alpha)
+	}
+
+	/// Creates a color from a hue, saturation and value.
+	/// The saturation and value should be between 0 and 1.
+	pub fn from_hsv(hue: Angle, saturation: f64, value: f64, alpha: f64) -> Color {
+		let chroma = value * saturation;
+		from_hue(hue, chroma, value - chroma, alpha)
+	}
+
+	/// Creates a color from a hue, saturation and lightness.
+	/// The saturation and lightness should be between 0 and 1.
+	pub fn from_hsl(hue: Angle, saturation: f64, lightness: f64, alpha: f64) -> Color {
+		let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+		from_hue(hue, chroma, lightness - chroma / 2.0, alpha)
+	}
+
+	// Gets the hue, max and min channels that are shared by the HSV and HSL conversions.
+	fn hue(&self) -> (Angle, f64, f64) {
+		let max = self.red.max(self.green).max(self.blue);
+		let min = self.red.min(self.green).min(self.blue);
+		let chroma = max - min;
+		let hue = if chroma == 0.0 {
+			0.0
+		} else if max == self.red {
+			((self.green - self.blue) / chroma + 6.0) % 6.0
+		} else if max == self.green {
+			(self.blue - self.red) / chroma + 2.0
+		} else {
+			(self.red - self.green) / chroma + 4.0
+		};
+		(Angle::from_degrees(hue * 60.0), max, min)
+	}
+
+	/// Converts the color to a hue, saturation and value.
+	/// The alpha is ignored.
+	pub fn to_hsv(&self) -> (Angle, f64, f64) {
+		let (hue, max, min) = self.hue();
+		let saturation = if max == 0.0 {0.0} else {(max - min) / max};
+		(hue, saturation, max)
+	}
+
+	/// Converts the color to a hue, saturation and lightness.
+	/// The alpha is ignored.
+	pub fn to_hsl(&self) -> (Angle, f64, f64) {
+		let (hue, max, min) = self.hue();
+		let lightness = (max + min) / 2.0;
+		let saturation = if max == min {
+			0.0
+		} else {
+			(max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+		};
+		(hue, saturation, lightness)
+	}
+
+	/// Returns the color with a different alpha.
+	pub fn with_alpha(&self, alpha: f64) -> Color {
+		Color {alpha, ..*self}
+	}
+
+	/// Linearly interpolates every channel between two colors,
+	/// where an amount of 0 is `self` and 1 is `other`.
+	pub fn lerp(&self, other: Color, amount: f64) -> Color {
+		let lerp = |a: f64, b: f64| a + (b - a) * amount;
+		Color {
+			red:   lerp(self.red,   other.red),
+			green: lerp(self.green, other.green),
+			blue:  lerp(self.blue,  other.blue),
+			alpha: lerp(self.alpha, other.alpha)
+		}
+	}
+
+	/// Multiplies the red, green and blue channels by the alpha.
+	pub fn premultiply(&self) -> Color {
+		Color {
+			red:   self.red   * self.alpha,
+			green: self.green * self.alpha,
+			blue:  self.blue  * self.alpha,
+			alpha: self.alpha
+		}
+	}
+
+	/// Converts the red, green and blue channels from sRGB to linear.
+	/// The alpha is already linear.
+	pub fn to_linear(&self) -> Color {
+		Color {
+			red:   srgb_to_linear(self.red),
+			green: srgb_to_linear(self.green),
+			blue:  srgb_to_linear(self.blue),
+			alpha: self.alpha
+		}
+	}
+
+	/// Converts the red, green and blue channels from linear to sRGB.
+	/// The alpha is kept linear.
+	pub fn to_srgb(&self) -> Color {
+		Color {
+			red:   linear_to_srgb(self.red),
+			green: linear_to_srgb(self.green),
+			blue:  linear_to_srgb(self.blue),
+			alpha: self.alpha
+		}
+	}
+}
+
+/// Multiplies every channel, which is how
+/// [`Graphics.tint`](struct.Graphics.html#method.tint) works.
+impl Mul for Color {
+	type Output = Color;
+	fn mul(self, other: Color) -> Color {
+		Color {
+			red:   self.red   * other.red,
+			green: self.green * other.green,
+			blue:  self.blue  * other.blue,
+			alpha: self.alpha * other.alpha
+		}
+	}
+}
+
+impl MulAssign for Color {
+	fn mul_assign(&mut self, other: Color) {
+		*self = *self * other;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn from_hex_parses_every_form() {
+		assert_eq!(Color::from_hex("#f80"), Some(Color::from_rgba8(255, 136, 0, 255)));
+		assert_eq!(Color::from_hex("f808"), Some(Color::from_rgba8(255, 136, 0, 136)));
+		assert_eq!(Color::from_hex("#ff8800"), Some(Color::from_rgba8(255, 136, 0, 255)));
+		assert_eq!(Color::from_hex("ff880080"), Some(Color::from_rgba8(255, 136, 0, 128)));
+	}
+
+	#[test]
+	fn from_hex_rejects_invalid_text() {
+		assert_eq!(Color::from_hex("##fff"), None);
+		assert_eq!(Color::from_hex("#"), None);
+		assert_eq!(Color::from_hex("#ff"), None);
+		assert_eq!(Color::from_hex("#ggg"), None);
+		assert_eq!(Color::from_hex("#+ff"), None);
+		assert_eq!(Color::from_hex("#ffé"), None);
+	}
+
+	#[test]
+	fn to_hex_round_trips() {
+		let color = Color::from_rgba8(18, 52, 86, 120);
+		assert_eq!(color.to_hex(), "#12345678");
+		assert_eq!(Color::from_hex(&color.to_hex()), Some(color));
+	}
+
+	fn assert_near(a: f64, b: f64) {
+		assert!((a - b).abs() < 1e-9, "{} is not {}", a, b);
+	}
+
+	fn assert_color(a: Color, b: Color) {
+		assert!((a.red - b.red).abs() < 1e-9 && (a.green - b.green).abs() < 1e-9
+			&& (a.blue - b.blue).abs() < 1e-9 && (a.alpha - b.alpha).abs() < 1e-9, "{:?} is not {:?}", a, b);
+	}
+
+	fn assert_hue(actual: (Angle, f64, f64), expected: (f64, f64, f64)) {
+		assert_near(actual.0.as_degrees(), expected.0);
+		assert_near(actual.1, expected.1);
+		assert_near(actual.2, expected.2);
+	}
+
+	const PRIMARIES: [(f64, Color); 6] = [
+		(0.0, Color::RED), (60.0, Color::YELLOW), (120.0, Color::GREEN),
+		(180.0, Color::CYAN), (240.0, Color::BLUE), (300.0, Color::MAGENTA)
+	];
+
+	#[test]
+	fn hsv_and_hsl_make_the_primaries() {
+		for &(hue, color) in &PRIMARIES {
+			assert_color(Color::from_hsv(Angle::from_degrees(hue), 1.0, 1.0, 1.0), color);
+			assert_color(Color::from_hsl(Angle::from_degrees(hue), 1.0, 0.5, 1.0), color);
+			assert_hue(color.to_hsv(), (hue, 1.0, 1.0));
+			assert_hue(color.to_hsl(), (hue, 1.0, 0.5));
+		}
+		assert_eq!(Color::from_hsv(Angle::from_degrees(0.0), 1.0, 1.0, 0.25).alpha, 0.25);
+	}
+
+	#[test]
+	fn grays_have_no_saturation() {
+		for &hue in &[0.0, 90.0, 200.0] {
+			assert_color(Color::from_hsv(Angle::from_degrees(hue), 0.0, 0.5, 1.0), Color::GRAY);
+			assert_color(Color::from_hsl(Angle::from_degrees(hue), 0.0, 0.5, 1.0), Color::GRAY);
+		}
+		assert_hue(Color::GRAY.to_hsv(), (0.0, 0.0, 0.5));
+		assert_hue(Color::GRAY.to_hsl(), (0.0, 0.0, 0.5));
+		assert_hue(Color::BLACK.to_hsv(), (0.0, 0.0, 0.0));
+		assert_hue(Color::WHITE.to_hsl(), (0.0, 0.0, 1.0));
+	}
+
+	#[test]
+	fn hues_wrap_around() {
+		let hsv = |hue: f64| Color::from_hsv(Angle::from_degrees(hue), 1.0, 1.0, 1.0);
+		assert_color(hsv(360.0), Color::RED);
+		assert_color(hsv(480.0), Color::GREEN);
+		assert_color(hsv(-120.0), Color::BLUE);
+		assert_color(hsv(-1e-15), Color::RED);
+		assert_color(hsv(359.999999999), Color::RED);
+
+		// Hues below red wrap to the top instead of being negative.
+		assert_hue(Color::new(1.0, 0.0, 0.5, 1.0).to_hsv(), (330.0, 1.0, 1.0));
+	}
+
+	#[test]
+	fn hsv_and_hsl_round_trip() {
+		for &color in &[Color::new(0.2, 0.4, 0.6, 1.0), Color::new(0.9, 0.1, 0.3, 1.0), Color::new(0.5, 0.5, 0.1, 1.0)] {
+			let (hue, saturation, value) = color.to_hsv();
+			assert_color(Color::from_hsv(hue, saturation, value, 1.0), color);
+			let (hue, saturation, lightness) = color.to_hsl();
+			assert_color(Color::from_hsl(hue, saturation, lightness, 1.0), color);
+		}
+	}
+
+	#[test]
+	fn srgb_uses_a_linear_segment_near_black() {
+		// Both sides of the threshold meet, so there is no jump.
+		assert_near(srgb_to_linear(0.04045), 0.04045 / 12.92);
+		assert!((srgb_to_linear(0.04045 + 1e-12) - 0.04045 / 12.92).abs() < 1e-7);
+		assert_near(linear_to_srgb(0.0031308), 0.0031308 * 12.92);
+		assert!((linear_to_srgb(0.0031308 + 1e-12) - 0.0031308 * 12.92).abs() < 1e-7);
+
+		let color = Color::new(0.5, 0.02, 1.0, 0.5);
+		let linear = color.to_linear();
+		assert!((linear.red - 0.214041).abs() < 1e-6);
+		assert_near(linear.green, 0.02 / 12.92);
+		assert_near(linear.blue, 1.0);
+		assert_eq!(linear.alpha, 0.5);
+		assert_color(linear.to_srgb(), color);
+		assert_color(Color::BLACK.to_linear(), Color::BLACK);
+	}
+
+	#[test]
+	fn lerp_and_premultiply() {
+		let a = Color::new(0.0, 0.2, 1.0, 1.0);
+		let b = Color::new(1.0, 0.4, 0.0, 0.0);
+		assert_color(a.lerp(b, 0.0), a);
+		assert_color(a.lerp(b, 1.0), b);
+		assert_color(a.lerp(b, 0.5), Color::new(0.5, 0.3, 0.5, 0.5));
+
+		assert_color(Color::new(1.0, 0.5, 0.25, 0.5).premultiply(), Color::new(0.5, 0.25, 0.125, 0.5));
+		assert_color(Color::WHITE.with_alpha(0.0).premultiply(), Color::TRANSPARENT);
+	}
+
+	#[test]
+	fn to_rgba8_clamps_and_rounds() {
+		assert_eq!(Color::new(-1.0, 2.0, 0.5, 1.0).to_rgba8(), [0, 255, 128, 255]);
+		assert_eq!(Color::new(0.001, 0.999, 0.25, 0.0).to_rgba8(), [0, 255, 64, 0]);
+		assert_eq!(Color::from_rgba8(1, 2, 3, 4).to_rgba8(), [1, 2, 3, 4]);
+	}
+}
\ No newline at end of file
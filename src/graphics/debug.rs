@@ -0,0 +1,96 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::gl::{self, Gl, types::*};
+
+use ::core;
+
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::slice;
+
+/// Keeps track of the mint2d call that is currently issuing GL commands
+/// so it can be included in debug messages and errors.
+pub struct Debug {
+	call: Cell<&'static str>
+}
+
+impl Debug {
+	pub fn new() -> Box<Debug> {
+		Box::new(Debug {
+			call: Cell::new("Graphics::new")
+		})
+	}
+
+	pub fn call(&self, call: &'static str) {
+		self.call.set(call);
+	}
+
+	pub fn get_call(&self) -> &'static str {
+		self.call.get()
+	}
+
+	/// Installs the `KHR_debug` message callback if it is available.
+	/// The callback keeps a pointer to `self`, so it must be uninstalled
+	/// before `self` is dropped.
+	pub unsafe fn install(&self, gl: &Gl) -> bool {
+		if !gl.DebugMessageCallback.is_loaded() {
+			return false;
+		}
+
+		gl.Enable(gl::DEBUG_OUTPUT);
+		gl.Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+		gl.DebugMessageCallback(callback, self as *const Debug as *const _);
+		true
+	}
+
+	pub unsafe fn uninstall(&self, gl: &Gl) {
+		if gl.DebugMessageCallback.is_loaded() {
+			gl.Disable(gl::DEBUG_OUTPUT);
+		}
+	}
+}
+
+extern "system" fn callback(
+	source: GLenum,
+	kind: GLenum,
+	_id: GLuint,
+	severity: GLenum,
+	length: GLsizei,
+	message: *const GLchar,
+	user: *mut c_void
+) {
+	if severity == gl::DEBUG_SEVERITY_NOTIFICATION {
+		return;
+	}
+
+	unsafe {
+		let debug = &*(user as *const Debug);
+		let message = if length < 0 {
+			CStr::from_ptr(message).to_string_lossy()
+		} else {
+			String::from_utf8_lossy(slice::from_raw_parts(message as *const u8, length as usize))
+		};
+
+		core::log(format!(
+			"GL {} {} ({} severity) in {}: {}",
+			gl::debug_source_name(source),
+			gl::debug_type_name(kind),
+			gl::debug_severity_name(severity),
+			debug.get_call(),
+			message
+		));
+	}
+}
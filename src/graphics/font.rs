@@ -0,0 +1,606 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate backtrace;
+use self::backtrace::Backtrace;
+
+use ::{Size, Point, Rect};
+use super::{Graphics, Texture, GraphicsError, TextLayout, LayoutOptions};
+use super::truetype::TrueType;
+use super::glyphs::GlyphCache;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::char;
+use std::fs;
+use std::str;
+
+/// A character in a font page.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct Glyph {
+	pub page: usize,
+	/// The area of the page in pixels.
+	pub region: Rect,
+	/// Where the region is drawn relative to the top of the line.
+	pub offset: Point,
+	pub advance: f64
+}
+
+/// The contents of a font file before the pages are loaded.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub(crate) struct FontData {
+	pub pages: Vec<String>,
+	pub glyphs: HashMap<char, Glyph>,
+	pub fallback: Option<Glyph>,
+	pub kerning: HashMap<(char, char), f64>,
+	/// The size the font was generated at, in pixels.
+	pub size: f64,
+	pub line_height: f64,
+	pub base: f64
+}
+
+struct BitmapFont {
+	pages: Vec<Texture>,
+	data: FontData
+}
+
+impl BitmapFont {
+	/// Gets the glyph for the character, using the fallback character
+	/// or `?` if the font does not have it.
+	fn get_glyph(&self, c: char) -> Option<&Glyph> {
+		self.data.glyphs.get(&c)
+			.or(self.data.fallback.as_ref())
+			.or_else(|| self.data.glyphs.get(&'?'))
+	}
+}
+
+#[derive(Clone)]
+enum FontKind {
+	Bitmap(Rc<BitmapFont>),
+	TrueType(Rc<RefCell<GlyphCache>>)
+}
+
+/// Where a glyph is drawn, relative to the top of the line at the pen position.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct GlyphQuad {
+	pub texture: u32,
+	pub texture_size: Size,
+	/// The area of the texture in pixels.
+	pub region: Rect,
+	pub rect: Rect
+}
+
+/// A font drawn with [`Graphics.print`](struct.Graphics.html#method.print).
+///
+/// Bitmap fonts are loaded from AngelCode BMFont files with
+/// [`Graphics.load_font`](struct.Graphics.html#method.load_font),
+/// and are scaled when used at a different size than they were made at.
+/// TrueType and OpenType fonts are loaded with
+/// [`from_truetype_file`](#method.from_truetype_file) and are rasterized at
+/// the exact size as characters are drawn.
+///
+/// Cloning a font, or changing its size with [`with_size`](#method.with_size),
+/// shares the textures and glyphs that have already been loaded.
+#[derive(Clone)]
+pub struct Font {
+	kind: FontKind,
+	size: f64
+}
+
+impl Font {
+	pub(crate) fn new(data: FontData, pages: Vec<Texture>) -> Result<Font, GraphicsError> {
+		if pages.len() != data.pages.len() {
+			return Err(invalid(format!("Expected {} pages, found {}", data.pages.len(), pages.len())));
+		}
+		let glyphs = data.glyphs.values().chain(data.fallback.iter());
+		if glyphs.clone().any(|glyph| glyph.page >= pages.len()) {
+			return Err(invalid("Character uses a page that does not exist".to_string()));
+		}
+		Ok(Font {
+			size: data.size,
+			kind: FontKind::Bitmap(Rc::new(BitmapFont {pages, data}))
+		})
+	}
+
+	/// Creates a font from a TrueType or OpenType file in memory.
+	/// The size is the height of an em in pixels.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidFont`](enum.GraphicsError.html#variant.InvalidFont)
+	/// if the data could not be parsed.
+	pub fn from_truetype(data: Vec<u8>, size: f64) -> Result<Font, GraphicsError> {
+		let font = TrueType::new(data)?;
+		Ok(Font {
+			kind: FontKind::TrueType(Rc::new(RefCell::new(GlyphCache::new(font)))),
+			size
+		})
+	}
+
+	/// Loads a TrueType or OpenType file, or the first font of a collection.
+	/// The size is the height of an em in pixels.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidFont`](enum.GraphicsError.html#variant.InvalidFont)
+	/// if the file could not be read or parsed.
+	pub fn from_truetype_file<P: AsRef<Path>>(path: P, size: f64) -> Result<Font, GraphicsError> {
+		let path = path.as_ref();
+		let data = fs::read(path)
+			.map_err(|error| invalid(format!("{}: {}", path.display(), error)))?;
+		Font::from_truetype(data, size)
+	}
+
+	/// Creates a copy of the font that is drawn at a different size in pixels.
+	pub fn with_size(&self, size: f64) -> Font {
+		Font {
+			kind: self.kind.clone(),
+			size
+		}
+	}
+
+	/// Gets the size of the font in pixels.
+	pub fn get_size(&self) -> f64 {
+		self.size
+	}
+
+	/// Gets the distance between the top of each line.
+	pub fn get_line_height(&self) -> f64 {
+		match self.kind {
+			FontKind::Bitmap(ref font) => font.data.line_height * self.size / font.data.size,
+			FontKind::TrueType(ref cache) => {
+				let font = &cache.borrow().font;
+				font.get_line_height() * font.get_scale(self.size)
+			}
+		}
+	}
+
+	/// Gets the distance from the top of a line to where the characters sit.
+	pub fn get_base(&self) -> f64 {
+		match self.kind {
+			FontKind::Bitmap(ref font) => font.data.base * self.size / font.data.size,
+			FontKind::TrueType(ref cache) => {
+				let font = &cache.borrow().font;
+				font.get_ascent() * font.get_scale(self.size)
+			}
+		}
+	}
+
+	/// Gets the size of text when it is printed, where each `\n` starts a new line.
+	/// Use a [`TextLayout`](struct.TextLayout.html) to measure wrapped text.
+	pub fn measure(&self, text: &str) -> Size {
+		TextLayout::new(self, text, LayoutOptions::default()).get_size()
+	}
+
+	/// Checks if the font has the character, without using the fallback character.
+	pub fn has_char(&self, c: char) -> bool {
+		match self.kind {
+			FontKind::Bitmap(ref font) => font.data.glyphs.contains_key(&c),
+			FontKind::TrueType(ref cache) => cache.borrow().font.get_glyph(c) != 0
+		}
+	}
+
+	/// Gets how far to move after the character.
+	pub(crate) fn get_advance(&self, c: char) -> f64 {
+		match self.kind {
+			FontKind::Bitmap(ref font) => font.get_glyph(c)
+				.map_or(0.0, |glyph| glyph.advance * self.size / font.data.size),
+			FontKind::TrueType(ref cache) => {
+				let font = &cache.borrow().font;
+				font.get_advance(font.get_glyph(c)) * font.get_scale(self.size)
+			}
+		}
+	}
+
+	/// Gets how much to move between two characters, on top of the advance.
+	pub(crate) fn get_kerning(&self, first: char, second: char) -> f64 {
+		match self.kind {
+			FontKind::Bitmap(ref font) => font.data.kerning.get(&(first, second))
+				.map_or(0.0, |&kerning| kerning * self.size / font.data.size),
+			FontKind::TrueType(ref cache) => {
+				let font = &cache.borrow().font;
+				font.get_kerning(font.get_glyph(first), font.get_glyph(second)) * font.get_scale(self.size)
+			}
+		}
+	}
+
+	/// Loads anything needed to draw the text, which rasterizes
+	/// the characters of TrueType fonts that have not been drawn at this size.
+	pub(crate) fn prepare(&self, graphics: &mut Graphics, text: &str) {
+		if let FontKind::TrueType(ref cache) = self.kind {
+			cache.borrow_mut().prepare(graphics, text, self.size);
+		}
+	}
+
+	/// Gets where to draw a character that has been prepared,
+	/// or nothing if it has no outline.
+	pub(crate) fn get_quad(&self, c: char) -> Option<GlyphQuad> {
+		match self.kind {
+			FontKind::Bitmap(ref font) => {
+				let scale = self.size / font.data.size;
+				let glyph = font.get_glyph(c)?;
+				let page = &font.pages[glyph.page];
+				Some(GlyphQuad {
+					texture: page.get_id(),
+					texture_size: page.get_size(),
+					region: glyph.region,
+					rect: Rect {
+						origin: glyph.offset * scale,
+						size: glyph.region.size * scale
+					}
+				})
+			},
+			FontKind::TrueType(ref cache) => {
+				let cache = cache.borrow();
+				let glyph = cache.get(c, self.size)?;
+				let texture = cache.get_texture()?;
+				if glyph.region.size.width == 0.0 || glyph.region.size.height == 0.0 {
+					return None;
+				}
+				let base = cache.font.get_ascent() * cache.font.get_scale(self.size);
+				Some(GlyphQuad {
+					texture: texture.get_id(),
+					texture_size: texture.get_size(),
+					region: glyph.region,
+					rect: Rect {
+						origin: glyph.offset + Point {x: 0.0, y: base},
+						size: glyph.region.size
+					}
+				})
+			}
+		}
+	}
+}
+
+fn invalid(error: String) -> GraphicsError {
+	GraphicsError::InvalidFont(error, Backtrace::new())
+}
+
+/// Parses a BMFont file in either the text or binary format.
+pub(crate) fn parse(data: &[u8]) -> Result<FontData, GraphicsError> {
+	let mut font = if data.starts_with(b"BMF") {
+		parse_binary(data)?
+	} else {
+		let text = str::from_utf8(data)
+			.map_err(|_| invalid("Font file is not text or binary BMFont".to_string()))?;
+		parse_text(text)?
+	};
+	// Fonts without a size are treated as the size of a line.
+	if font.size == 0.0 {
+		font.size = if font.line_height > 0.0 {font.line_height} else {1.0};
+	}
+	Ok(font)
+}
+
+/// Adds a character with the BMFont id, where -1 is used for missing characters.
+fn add_glyph(font: &mut FontData, id: i64, glyph: Glyph) {
+	if id == -1 {
+		font.fallback = Some(glyph);
+	} else if let Some(c) = char::from_u32(id as u32) {
+		font.glyphs.insert(c, glyph);
+	}
+}
+
+fn add_kerning(font: &mut FontData, first: u32, second: u32, amount: f64) {
+	if let (Some(first), Some(second)) = (char::from_u32(first), char::from_u32(second)) {
+		font.kerning.insert((first, second), amount);
+	}
+}
+
+/// Splits a line into its tag and `key=value` pairs, where values can be quoted.
+fn split_line(line: &str) -> Option<(&str, Vec<(&str, &str)>)> {
+	let line = line.trim();
+	let tag_end = line.find(char::is_whitespace).unwrap_or(line.len());
+	let tag = &line[..tag_end];
+	let mut rest = &line[tag_end..];
+
+	let mut pairs = Vec::new();
+	loop {
+		rest = rest.trim_start();
+		if rest.is_empty() {
+			return Some((tag, pairs));
+		}
+
+		let equals = rest.find('=')?;
+		let key = rest[..equals].trim();
+		rest = &rest[equals + 1..];
+		let value = if rest.starts_with('"') {
+			let end = rest[1..].find('"')? + 1;
+			let value = &rest[1..end];
+			rest = &rest[end + 1..];
+			value
+		} else {
+			let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+			let value = &rest[..end];
+			rest = &rest[end..];
+			value
+		};
+		pairs.push((key, value));
+	}
+}
+
+fn parse_text(text: &str) -> Result<FontData, GraphicsError> {
+	let mut font = FontData::default();
+	// Pages can be listed in any order, but their ids have to cover every page.
+	let page_count = text.lines()
+		.filter(|line| split_line(line).map_or(false, |(tag, _)| tag == "page"))
+		.count();
+	font.pages = vec![String::new(); page_count];
+
+	for (index, line) in text.lines().enumerate() {
+		let line_error = || invalid(format!("Invalid font data on line {}", index + 1));
+		let (tag, pairs) = split_line(line).ok_or_else(line_error)?;
+		let value = |key: &str| -> Result<&str, GraphicsError> {
+			pairs.iter()
+				.find(|pair| pair.0 == key)
+				.map(|pair| pair.1)
+				.ok_or_else(|| invalid(format!("Missing {} on line {}", key, index + 1)))
+		};
+		let number = |key: &str| -> Result<i64, GraphicsError> {
+			value(key)?.parse().map_err(|_| line_error())
+		};
+
+		match tag {
+			"info" => font.size = (number("size")? as f64).abs(),
+			"common" => {
+				font.line_height = number("lineHeight")? as f64;
+				font.base = number("base")? as f64;
+			},
+			"page" => {
+				let id = number("id")?;
+				if id < 0 || id >= page_count as i64 {
+					return Err(invalid(format!(
+						"Page id {} on line {} is not below the {} pages", id, index + 1, page_count
+					)));
+				}
+				let page = &mut font.pages[id as usize];
+				if !page.is_empty() {
+					return Err(invalid(format!("Page id {} on line {} is used twice", id, index + 1)));
+				}
+				*page = value("file")?.to_string();
+				if page.is_empty() {
+					return Err(line_error());
+				}
+			},
+			"char" => {
+				let page = if value("page").is_ok() {number("page")?} else {0};
+				add_glyph(&mut font, number("id")?, Glyph {
+					page: page as usize,
+					region: Rect::new(
+						number("x")? as f64,
+						number("y")? as f64,
+						number("width")? as f64,
+						number("height")? as f64
+					),
+					offset: Point {
+						x: number("xoffset")? as f64,
+						y: number("yoffset")? as f64
+					},
+					advance: number("xadvance")? as f64
+				});
+			},
+			"kerning" => {
+				let first = number("first")? as u32;
+				let second = number("second")? as u32;
+				add_kerning(&mut font, first, second, number("amount")? as f64);
+			},
+			_ => ()
+		}
+	}
+	Ok(font)
+}
+
+/// Reads little-endian values from binary data.
+struct Reader<'a> {
+	data: &'a [u8]
+}
+
+impl<'a> Reader<'a> {
+	fn bytes(&mut self, length: usize) -> Result<&'a [u8], GraphicsError> {
+		if self.data.len() < length {
+			return Err(invalid("Unexpected end of font data".to_string()));
+		}
+		let (bytes, rest) = self.data.split_at(length);
+		self.data = rest;
+		Ok(bytes)
+	}
+
+	fn u8(&mut self) -> Result<u8, GraphicsError> {
+		Ok(self.bytes(1)?[0])
+	}
+
+	fn u16(&mut self) -> Result<u16, GraphicsError> {
+		let bytes = self.bytes(2)?;
+		Ok(bytes[0] as u16 | (bytes[1] as u16) << 8)
+	}
+
+	fn i16(&mut self) -> Result<i16, GraphicsError> {
+		Ok(self.u16()? as i16)
+	}
+
+	fn u32(&mut self) -> Result<u32, GraphicsError> {
+		let low = self.u16()? as u32;
+		let high = self.u16()? as u32;
+		Ok(low | high << 16)
+	}
+}
+
+fn parse_binary(data: &[u8]) -> Result<FontData, GraphicsError> {
+	let mut reader = Reader {data};
+	reader.bytes(3)?;
+	let version = reader.u8()?;
+	if version != 3 {
+		return Err(invalid(format!("Unsupported BMFont version {}", version)));
+	}
+
+	let mut font = FontData::default();
+	while !reader.data.is_empty() {
+		let kind = reader.u8()?;
+		let length = reader.u32()? as usize;
+		let mut block = Reader {data: reader.bytes(length)?};
+		match kind {
+			1 => font.size = (block.i16()? as f64).abs(),
+			2 => {
+				font.line_height = block.u16()? as f64;
+				font.base = block.u16()? as f64;
+			},
+			3 => {
+				// Each page name is null terminated.
+				font.pages = block.data.split(|&byte| byte == 0)
+					.filter(|name| !name.is_empty())
+					.map(|name| String::from_utf8_lossy(name).into_owned())
+					.collect();
+			},
+			4 => while !block.data.is_empty() {
+				let id = block.u32()?;
+				let region = Rect::new(
+					block.u16()? as f64,
+					block.u16()? as f64,
+					block.u16()? as f64,
+					block.u16()? as f64
+				);
+				let offset = Point {
+					x: block.i16()? as f64,
+					y: block.i16()? as f64
+				};
+				let advance = block.i16()? as f64;
+				let page = block.u8()? as usize;
+				block.u8()?;
+				add_glyph(&mut font, id as i32 as i64, Glyph {page, region, offset, advance});
+			},
+			5 => while !block.data.is_empty() {
+				let first = block.u32()?;
+				let second = block.u32()?;
+				let amount = block.i16()? as f64;
+				add_kerning(&mut font, first, second, amount);
+			},
+			_ => ()
+		}
+	}
+	Ok(font)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{Image, TextureFilter};
+
+	use std::env;
+
+	const TEXT: &str = "info face=\"Test\" size=-8\n\
+		common lineHeight=10 base=8 pages=2\n\
+		page id=1 file=\"b.png\"\n\
+		page id=0 file=\"a.png\"\n\
+		char id=65 x=1 y=2 width=3 height=4 xoffset=-1 yoffset=2 xadvance=5 page=1\n\
+		char id=-1 x=0 y=0 width=1 height=1 xoffset=0 yoffset=0 xadvance=2\n\
+		kerning first=65 second=65 amount=-1\n";
+
+	fn binary() -> Vec<u8> {
+		let mut data = b"BMF\x03".to_vec();
+		let mut block = |kind: u8, bytes: &[u8]| {
+			data.push(kind);
+			data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+			data.extend_from_slice(bytes);
+		};
+		block(1, &[8, 0]);
+		block(2, &[10, 0, 8, 0]);
+		block(3, b"a.png\0b.png\0");
+		// The id, region, offset, advance, page and channel of one character.
+		let mut chars = 65u32.to_le_bytes().to_vec();
+		chars.extend_from_slice(&[1, 0, 2, 0, 3, 0, 4, 0, 255, 255, 2, 0, 5, 0, 1, 0]);
+		block(4, &chars);
+		let mut kerning = Vec::new();
+		kerning.extend_from_slice(&65u32.to_le_bytes());
+		kerning.extend_from_slice(&65u32.to_le_bytes());
+		kerning.extend_from_slice(&[255, 255]);
+		block(5, &kerning);
+		data
+	}
+
+	fn error(data: &[u8]) -> String {
+		match parse(data) {
+			Err(GraphicsError::InvalidFont(message, _)) => message,
+			other => panic!("parsed as {:?}", other)
+		}
+	}
+
+	#[test]
+	fn text_and_binary_formats_match() {
+		let text = parse(TEXT.as_bytes()).unwrap();
+		assert_eq!(text.pages, vec!["a.png", "b.png"]);
+		assert_eq!(text.size, 8.0);
+		assert_eq!(text.line_height, 10.0);
+		assert_eq!(text.glyphs[&'A'], Glyph {
+			page: 1,
+			region: Rect::new(1.0, 2.0, 3.0, 4.0),
+			offset: Point {x: -1.0, y: 2.0},
+			advance: 5.0
+		});
+		assert_eq!(text.fallback.map(|glyph| glyph.advance), Some(2.0));
+		assert_eq!(text.kerning[&('A', 'A')], -1.0);
+
+		let binary = parse(&binary()).unwrap();
+		assert_eq!(binary.pages, text.pages);
+		assert_eq!(binary.glyphs, text.glyphs);
+		assert_eq!(binary.kerning, text.kerning);
+		assert_eq!((binary.size, binary.line_height, binary.base), (text.size, text.line_height, text.base));
+	}
+
+	#[test]
+	fn page_ids_must_be_listed() {
+		assert_eq!(error(b"page id=-1 file=a.png"), "Page id -1 on line 1 is not below the 1 pages");
+		assert_eq!(error(b"page id=0 file=a.png\npage id=4000000000 file=b.png"),
+			"Page id 4000000000 on line 2 is not below the 2 pages");
+		assert_eq!(error(b"page id=0 file=a.png\npage id=0 file=b.png"), "Page id 0 on line 2 is used twice");
+		assert_eq!(error(b"page id=0 file=\"\""), "Invalid font data on line 1");
+	}
+
+	#[test]
+	fn truncated_binary_is_an_error() {
+		let data = binary();
+		for length in 0..data.len() {
+			// Cutting between blocks leaves a valid font with fewer blocks.
+			let _ = parse(&data[..length]);
+		}
+		assert_eq!(error(&data[..data.len() - 1]), "Unexpected end of font data");
+		assert_eq!(error(b"BMF\x02"), "Unsupported BMFont version 2");
+	}
+
+	#[test]
+	fn characters_must_use_existing_pages() {
+		let mut graphics = Graphics::software(1, 1);
+		let page = Image::from_rgba(vec![0; 4], 1, 1).unwrap();
+		assert!(graphics.create_font(TEXT.as_bytes(), &[page.clone(), page.clone()], TextureFilter::Nearest).is_ok());
+		assert!(graphics.create_font(TEXT.as_bytes(), &[page.clone()], TextureFilter::Nearest).is_err());
+
+		let data = b"page id=0 file=a.png\nchar id=65 x=0 y=0 width=1 height=1 xoffset=0 yoffset=0 xadvance=1 page=-1";
+		assert!(graphics.create_font(data, &[page], TextureFilter::Nearest).is_err());
+	}
+
+	#[test]
+	fn pages_must_be_inside_the_directory() {
+		let mut graphics = Graphics::software(1, 1);
+		let directory = env::temp_dir();
+		for (index, page) in ["../a.png", "/a.png", "pages/../../a.png"].iter().enumerate() {
+			let path = directory.join(format!("mint2d_font_test_{}.fnt", index));
+			fs::write(&path, format!("page id=0 file=\"{}\"", page)).unwrap();
+			let result = graphics.load_font(&path, TextureFilter::Nearest);
+			fs::remove_file(&path).unwrap();
+			match result {
+				Err(GraphicsError::InvalidFont(message, _)) => assert!(message.ends_with(&format!(
+					"The page {} is outside of the directory of the font", page
+				))),
+				_ => panic!("{} was loaded", page)
+			}
+		}
+	}
+}
\ No newline at end of file
@@ -14,17 +14,52 @@
 
 include!(concat!(env!("OUT_DIR"), "/gl.rs"));
 
-pub trait CheckError {
-	fn check_error(&self);
+/// Gets the name of an error returned by `glGetError`.
+pub fn error_name(error: types::GLenum) -> &'static str {
+	match error {
+		NO_ERROR => "GL_NO_ERROR",
+		INVALID_ENUM => "GL_INVALID_ENUM",
+		INVALID_VALUE => "GL_INVALID_VALUE",
+		INVALID_OPERATION => "GL_INVALID_OPERATION",
+		INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+		OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
+		STACK_UNDERFLOW => "GL_STACK_UNDERFLOW",
+		STACK_OVERFLOW => "GL_STACK_OVERFLOW",
+		_ => "unknown GL error"
+	}
+}
+
+/// Gets the name of a `KHR_debug` message source.
+pub fn debug_source_name(source: types::GLenum) -> &'static str {
+	match source {
+		DEBUG_SOURCE_API => "API",
+		DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+		DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+		DEBUG_SOURCE_THIRD_PARTY => "third party",
+		DEBUG_SOURCE_APPLICATION => "application",
+		_ => "other"
+	}
 }
 
-impl CheckError for Gl {
-	fn check_error(&self) {
-		unsafe {
-			let error = self.GetError();
-			if error != NO_ERROR {
-				panic!("glGetError = 0x{:X}", error)
-			}
-		}
+/// Gets the name of a `KHR_debug` message type.
+pub fn debug_type_name(kind: types::GLenum) -> &'static str {
+	match kind {
+		DEBUG_TYPE_ERROR => "error",
+		DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+		DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+		DEBUG_TYPE_PORTABILITY => "portability",
+		DEBUG_TYPE_PERFORMANCE => "performance",
+		DEBUG_TYPE_MARKER => "marker",
+		_ => "other"
 	}
-}
\ No newline at end of file
+}
+
+/// Gets the name of a `KHR_debug` message severity.
+pub fn debug_severity_name(severity: types::GLenum) -> &'static str {
+	match severity {
+		DEBUG_SEVERITY_HIGH => "high",
+		DEBUG_SEVERITY_MEDIUM => "medium",
+		DEBUG_SEVERITY_LOW => "low",
+		_ => "notification"
+	}
+}
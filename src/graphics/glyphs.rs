@@ -0,0 +1,305 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ::{Point, Rect};
+use super::{Graphics, Image, Texture, TextureFilter, CURVE_TOLERANCE};
+use super::truetype::{self, TrueType};
+
+use std::collections::HashMap;
+use std::mem;
+
+// The atlas starts small and doubles in size until it reaches the maximum.
+const INITIAL_SIZE: u32 = 256;
+const MAX_SIZE: u32 = 2048;
+
+// Space between glyphs so filtering doesn't blend them together.
+const PADDING: u32 = 1;
+
+// How many glyphs are cached before the ones without outlines that weren't just used
+// are removed. Other glyphs are limited by the space in the atlas.
+const MAX_GLYPHS: usize = 4096;
+
+/// A row of glyphs in the atlas.
+#[derive(Copy, Clone, Debug)]
+struct Shelf {
+	y: u32,
+	height: u32,
+	// Where the next glyph goes.
+	x: u32
+}
+
+/// A glyph that has been drawn into the atlas.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct CachedGlyph {
+	/// The area of the atlas in pixels, which is empty for glyphs with no outline.
+	pub region: Rect,
+	/// Where the region is drawn relative to the origin on the baseline.
+	pub offset: Point,
+	used: u64
+}
+
+impl CachedGlyph {
+	fn is_empty(&self) -> bool {
+		self.region.size.width == 0.0 || self.region.size.height == 0.0
+	}
+}
+
+/// Rasterizes the glyphs of a font into an atlas as they are needed.
+///
+/// When the atlas can't grow any more, the glyphs used least recently are evicted.
+pub(crate) struct GlyphCache {
+	pub font: TrueType,
+	image: Image,
+	texture: Option<Texture>,
+	shelves: Vec<Shelf>,
+	// The glyphs for each glyph index and size in 1/64 pixels.
+	glyphs: HashMap<(u16, u32), CachedGlyph>,
+	// The rows of the image that changed since the texture was updated.
+	dirty: Option<(u32, u32)>,
+	// Increased every time text is prepared, to find which glyphs were used recently.
+	generation: u64
+}
+
+fn size_key(size: f64) -> u32 {
+	(size * 64.0).round() as u32
+}
+
+fn blank_image(size: u32) -> Image {
+	Image::from_rgba(vec![0; size as usize * size as usize * 4], size, size).unwrap()
+}
+
+impl GlyphCache {
+	pub fn new(font: TrueType) -> GlyphCache {
+		GlyphCache {
+			font,
+			image: blank_image(INITIAL_SIZE),
+			texture: None,
+			shelves: Vec::new(),
+			glyphs: HashMap::new(),
+			dirty: None,
+			generation: 0
+		}
+	}
+
+	pub fn get_texture(&self) -> Option<&Texture> {
+		self.texture.as_ref()
+	}
+
+	/// Gets a glyph that was cached by [`prepare`](#method.prepare).
+	pub fn get(&self, c: char, size: f64) -> Option<&CachedGlyph> {
+		self.glyphs.get(&(self.font.get_glyph(c), size_key(size)))
+	}
+
+	/// Makes sure the glyphs of the text are in the atlas and the texture is up to date.
+	pub fn prepare(&mut self, graphics: &mut Graphics, text: &str, size: f64) {
+		self.generation += 1;
+		let key = size_key(size);
+		for c in text.chars().filter(|c| !c.is_control()) {
+			let glyph = self.font.get_glyph(c);
+			if let Some(cached) = self.glyphs.get_mut(&(glyph, key)) {
+				cached.used = self.generation;
+				continue;
+			}
+			// Glyphs that didn't fit are tried again the next time.
+			if let Some(cached) = self.rasterize(glyph, size) {
+				self.glyphs.insert((glyph, key), cached);
+			}
+		}
+
+		if self.glyphs.len() > MAX_GLYPHS {
+			let generation = self.generation;
+			self.glyphs.retain(|_, glyph| glyph.used == generation || !glyph.is_empty());
+		}
+		self.upload(graphics);
+	}
+
+	/// Draws a glyph into the atlas, or returns `None` if there is no space for it.
+	fn rasterize(&mut self, glyph: u16, size: f64) -> Option<CachedGlyph> {
+		let path = self.font.get_outline(glyph, self.font.get_scale(size));
+		let mut polygons = path.flatten(CURVE_TOLERANCE);
+		let points = || polygons.iter().flat_map(|polygon| polygon.0.iter());
+		if points().next().is_none() {
+			return Some(CachedGlyph {
+				region: Rect::default(),
+				offset: Point::default(),
+				used: self.generation
+			});
+		}
+		let left = points().map(|point| point.x).fold(::std::f64::INFINITY, f64::min).floor();
+		let top = points().map(|point| point.y).fold(::std::f64::INFINITY, f64::min).floor();
+		let right = points().map(|point| point.x).fold(::std::f64::NEG_INFINITY, f64::max).ceil();
+		let bottom = points().map(|point| point.y).fold(::std::f64::NEG_INFINITY, f64::max).ceil();
+		let width = (right - left) as u32;
+		let height = (bottom - top) as u32;
+		if width > MAX_SIZE - PADDING * 2 || height > MAX_SIZE - PADDING * 2 {
+			return None;
+		}
+		let (x, y) = self.allocate(width, height)?;
+
+		for polygon in &mut polygons {
+			for point in &mut polygon.0 {
+				point.x -= left;
+				point.y -= top;
+			}
+		}
+		let coverage = truetype::rasterize(&polygons, width as usize, height as usize);
+		let mut data = Vec::with_capacity(coverage.len() * 4);
+		for alpha in coverage {
+			data.extend_from_slice(&[255, 255, 255, alpha]);
+		}
+		let bitmap = Image::from_rgba(data, width, height).unwrap();
+		self.image.blit(&bitmap, x, y);
+		self.mark_dirty(y, y + height);
+
+		Some(CachedGlyph {
+			region: Rect::new(x as f64, y as f64, width as f64, height as f64),
+			offset: Point {x: left, y: top},
+			used: self.generation
+		})
+	}
+
+	/// Finds space for a glyph, growing the atlas or evicting glyphs if there is none.
+	fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+		let mut evicted = false;
+		loop {
+			if let Some(point) = self.pack(width, height, self.image.get_height()) {
+				return Some(point);
+			}
+
+			if self.image.get_width() < MAX_SIZE {
+				let mut image = blank_image(self.image.get_width() * 2);
+				image.blit(&self.image, 0, 0);
+				self.image = image;
+			} else if !evicted {
+				self.evict();
+				evicted = true;
+			} else {
+				return None;
+			}
+		}
+	}
+
+	/// Places a glyph on the shortest shelf it fits on, or starts a new shelf
+	/// if it fits above the limit.
+	fn pack(&mut self, width: u32, height: u32, limit: u32) -> Option<(u32, u32)> {
+		let (width, height) = (width + PADDING, height + PADDING);
+		let atlas_width = self.image.get_width();
+		let shelf = self.shelves.iter_mut()
+			.filter(|shelf| shelf.height >= height && shelf.x + width <= atlas_width)
+			.min_by_key(|shelf| shelf.height);
+		if let Some(shelf) = shelf {
+			shelf.x += width;
+			return Some((shelf.x - width, shelf.y));
+		}
+
+		// The edges are left empty too, so clamping doesn't stretch the glyphs touching them.
+		let y = self.shelves.last().map_or(PADDING, |shelf| shelf.y + shelf.height);
+		if y + height > limit || PADDING + width > atlas_width {
+			return None;
+		}
+		self.shelves.push(Shelf {y, height, x: PADDING + width});
+		Some((PADDING, y))
+	}
+
+	/// Repacks the atlas with the glyphs that were used most recently,
+	/// keeping at most half of the atlas unless they are needed for the current text.
+	/// Glyphs without outlines are only kept if they are needed for the current text.
+	fn evict(&mut self) {
+		let mut glyphs: Vec<_> = self.glyphs.drain().collect();
+		glyphs.sort_by(|a, b| b.1.used.cmp(&a.1.used));
+
+		let size = self.image.get_width();
+		let old = mem::replace(&mut self.image, blank_image(size));
+		self.shelves.clear();
+		for (key, mut glyph) in glyphs {
+			let region = glyph.region;
+			if glyph.is_empty() {
+				if glyph.used == self.generation {
+					self.glyphs.insert(key, glyph);
+				}
+				continue;
+			}
+
+			let limit = if glyph.used == self.generation {size} else {size / 2};
+			let (width, height) = (region.size.width as u32, region.size.height as u32);
+			if let Some((x, y)) = self.pack(width, height, limit) {
+				let bitmap = old.crop(region.origin.x as u32, region.origin.y as u32, width, height);
+				self.image.blit(&bitmap, x, y);
+				glyph.region.origin = Point {x: x as f64, y: y as f64};
+				self.glyphs.insert(key, glyph);
+			}
+		}
+		self.mark_dirty(0, size);
+	}
+
+	fn mark_dirty(&mut self, top: u32, bottom: u32) {
+		self.dirty = Some(match self.dirty {
+			Some((dirty_top, dirty_bottom)) => (dirty_top.min(top), dirty_bottom.max(bottom)),
+			None => (top, bottom)
+		});
+	}
+
+	fn upload(&mut self, graphics: &mut Graphics) {
+		// The texture is created again if it is too small or used by other graphics.
+		let recreate = self.texture.as_ref().map_or(true, |texture| {
+			texture.get_size() != self.image.get_size() || !graphics.owns_texture(texture)
+		});
+		if recreate {
+			self.texture = Some(graphics.create_texture_with_filter(&self.image, TextureFilter::Linear));
+			self.dirty = None;
+		} else if let Some((top, bottom)) = self.dirty.take() {
+			let rows = self.image.crop(0, top, self.image.get_width(), bottom - top);
+			graphics.update_texture(self.texture.as_ref().unwrap(), 0, top, &rows);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::truetype::tests::{glyf_font, cmap4};
+
+	fn cache() -> GlyphCache {
+		GlyphCache::new(TrueType::new(glyf_font(true, cmap4())).unwrap())
+	}
+
+	#[test]
+	fn glyphs_that_do_not_fit_are_not_cached() {
+		let mut graphics = Graphics::software(1, 1);
+		let mut cache = cache();
+		cache.prepare(&mut graphics, "A", 10000.0);
+		assert!(cache.get('A', 10000.0).is_none());
+
+		cache.prepare(&mut graphics, "A", 20.0);
+		let glyph = cache.get('A', 20.0).unwrap();
+		assert_eq!(glyph.region.size, ::Size::new(10.0, 10.0));
+		assert_eq!(glyph.offset, Point {x: 2.0, y: -10.0});
+	}
+
+	#[test]
+	fn glyphs_without_outlines_are_removed() {
+		let mut graphics = Graphics::software(1, 1);
+		let mut cache = cache();
+		for size in 0..MAX_GLYPHS + 100 {
+			cache.prepare(&mut graphics, " ", 1.0 + size as f64);
+		}
+		assert!(cache.glyphs.len() <= MAX_GLYPHS + 1);
+
+		cache.prepare(&mut graphics, " A", 12.0);
+		cache.prepare(&mut graphics, "A", 12.0);
+		cache.evict();
+		assert!(cache.get(' ', 12.0).is_none());
+		assert!(cache.get('A', 12.0).is_some());
+	}
+}
\ No newline at end of file
@@ -0,0 +1,243 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ::Point;
+use super::{Color, Image};
+
+// The resolution of the textures that gradients are drawn from.
+const LINEAR_SIZE: u32 = 256;
+const RADIAL_SIZE: u32 = 256;
+
+// Radial textures cover twice the radius so clamping fills the rest with the last stop.
+const RADIAL_EXTENT: f64 = 2.0;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum GradientKind {
+	Linear(Point, Point),
+	Radial(Point, f64)
+}
+
+/// A fill that smoothly changes between colors.
+/// Set as the current fill with [`Graphics.gradient`](struct.Graphics.html#method.gradient).
+///
+/// The points are in the same space as the shapes,
+/// so the gradient moves with the current transform.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Gradient {
+	kind: GradientKind,
+	stops: Vec<(f64, Color)>
+}
+
+impl Gradient {
+	/// Creates a gradient that changes along the line from the start to the end point.
+	pub fn linear(start: Point, end: Point) -> Gradient {
+		Gradient {
+			kind: GradientKind::Linear(start, end),
+			stops: Vec::new()
+		}
+	}
+
+	/// Creates a gradient that changes outwards from the center to the radius.
+	pub fn radial(center: Point, radius: f64) -> Gradient {
+		Gradient {
+			kind: GradientKind::Radial(center, radius),
+			stops: Vec::new()
+		}
+	}
+
+	/// Adds a color at the offset, between 0 at the start and 1 at the end.
+	/// Before the first stop and after the last stop the color stays the same.
+	pub fn add_stop(&mut self, offset: f64, color: Color) -> &mut Gradient {
+		let offset = offset.max(0.0).min(1.0);
+		let index = self.stops.iter().position(|&(stop, _)| stop > offset).unwrap_or(self.stops.len());
+		self.stops.insert(index, (offset, color));
+		self
+	}
+
+	/// Gets the color at the offset.
+	/// Colors are interpolated with premultiplied alpha,
+	/// so fading to a transparent stop doesn't darken the color.
+	pub fn get_color(&self, offset: f64) -> Color {
+		let next = match self.stops.iter().position(|&(stop, _)| stop > offset) {
+			Some(next) => next,
+			None => return self.stops.last().map_or(Color::TRANSPARENT, |&(_, color)| color)
+		};
+		if next == 0 {
+			return self.stops[0].1;
+		}
+
+		let (start, from) = self.stops[next - 1];
+		let (end, to) = self.stops[next];
+		let color = from.premultiply().lerp(to.premultiply(), (offset - start) / (end - start));
+		if color.alpha > 0.0 {
+			Color::new(color.red / color.alpha, color.green / color.alpha, color.blue / color.alpha, color.alpha)
+		} else {
+			Color::TRANSPARENT
+		}
+	}
+
+	/// Gets the texture coordinate of a point in the texture from [`to_image`](#method.to_image).
+	pub(crate) fn texcoord(&self, point: Point) -> Point {
+		match self.kind {
+			GradientKind::Linear(start, end) => {
+				let axis = end - start;
+				let length = axis.length_squared();
+				Point {
+					x: if length > 0.0 {(point - start).dot(axis) / length} else {0.0},
+					y: 0.5
+				}
+			},
+			GradientKind::Radial(center, radius) => {
+				let extent = radius * RADIAL_EXTENT * 2.0;
+				if extent > 0.0 {
+					(point - center) / extent + Point {x: 0.5, y: 0.5}
+				} else {
+					Point {x: 0.5, y: 0.5}
+				}
+			}
+		}
+	}
+
+	pub(crate) fn to_image(&self) -> Image {
+		let mut data = Vec::new();
+		let (width, height) = match self.kind {
+			GradientKind::Linear(..) => {
+				for x in 0..LINEAR_SIZE {
+					let offset = (x as f64 + 0.5) / LINEAR_SIZE as f64;
+					data.extend_from_slice(&self.get_color(offset).to_rgba8());
+				}
+				(LINEAR_SIZE, 1)
+			},
+			GradientKind::Radial(..) => {
+				for y in 0..RADIAL_SIZE {
+					for x in 0..RADIAL_SIZE {
+						let point = Point {
+							x: (x as f64 + 0.5) / RADIAL_SIZE as f64 - 0.5,
+							y: (y as f64 + 0.5) / RADIAL_SIZE as f64 - 0.5
+						};
+						let offset = point.length() * RADIAL_EXTENT * 2.0;
+						data.extend_from_slice(&self.get_color(offset).to_rgba8());
+					}
+				}
+				(RADIAL_SIZE, RADIAL_SIZE)
+			}
+		};
+		Image::from_rgba(data, width, height).unwrap()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_color(a: Color, b: Color) {
+		assert!((a.red - b.red).abs() < 1e-9 && (a.green - b.green).abs() < 1e-9
+			&& (a.blue - b.blue).abs() < 1e-9 && (a.alpha - b.alpha).abs() < 1e-9, "{:?} is not {:?}", a, b);
+	}
+
+	fn assert_point(a: Point, b: Point) {
+		assert!((a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9, "{:?} is not {:?}", a, b);
+	}
+
+	fn gradient() -> Gradient {
+		Gradient::linear(Point {x: 0.0, y: 0.0}, Point {x: 1.0, y: 0.0})
+	}
+
+	#[test]
+	fn colors_are_interpolated_between_stops() {
+		let mut gradient = gradient();
+		assert_eq!(gradient.get_color(0.5), Color::TRANSPARENT);
+
+		gradient.add_stop(0.25, Color::RED).add_stop(0.75, Color::BLUE);
+		assert_color(gradient.get_color(0.25), Color::RED);
+		assert_color(gradient.get_color(0.5), Color::new(0.5, 0.0, 0.5, 1.0));
+		assert_color(gradient.get_color(0.75), Color::BLUE);
+
+		// The ends keep the color of the nearest stop.
+		assert_color(gradient.get_color(0.0), Color::RED);
+		assert_color(gradient.get_color(-5.0), Color::RED);
+		assert_color(gradient.get_color(1.0), Color::BLUE);
+		assert_color(gradient.get_color(5.0), Color::BLUE);
+	}
+
+	#[test]
+	fn stops_are_sorted_by_offset() {
+		let mut gradient = gradient();
+		gradient.add_stop(1.0, Color::BLUE).add_stop(0.0, Color::RED).add_stop(0.5, Color::GREEN);
+		assert_color(gradient.get_color(0.25), Color::new(0.5, 0.5, 0.0, 1.0));
+		assert_color(gradient.get_color(0.75), Color::new(0.0, 0.5, 0.5, 1.0));
+
+		// Offsets outside of 0 and 1 are clamped.
+		let mut gradient = self::gradient();
+		gradient.add_stop(2.0, Color::BLUE).add_stop(-1.0, Color::RED);
+		assert_color(gradient.get_color(0.5), Color::new(0.5, 0.0, 0.5, 1.0));
+	}
+
+	#[test]
+	fn duplicate_stops_make_a_hard_edge() {
+		let mut gradient = gradient();
+		gradient.add_stop(0.0, Color::BLACK).add_stop(1.0, Color::WHITE);
+		gradient.add_stop(0.5, Color::RED).add_stop(0.5, Color::BLUE);
+		assert_color(gradient.get_color(0.25), Color::new(0.5, 0.0, 0.0, 1.0));
+		assert!(gradient.get_color(0.5 - 1e-9).red > 0.99);
+		assert_color(gradient.get_color(0.5), Color::BLUE);
+		assert_color(gradient.get_color(0.75), Color::new(0.5, 0.5, 1.0, 1.0));
+	}
+
+	#[test]
+	fn transparent_stops_do_not_darken() {
+		let mut gradient = gradient();
+		gradient.add_stop(0.0, Color::RED).add_stop(1.0, Color::TRANSPARENT);
+		assert_color(gradient.get_color(0.5), Color::new(1.0, 0.0, 0.0, 0.5));
+		assert_color(gradient.get_color(1.0), Color::TRANSPARENT);
+
+		let mut gradient = self::gradient();
+		gradient.add_stop(0.0, Color::RED.with_alpha(0.25)).add_stop(1.0, Color::BLUE.with_alpha(0.75));
+		assert_color(gradient.get_color(0.5), Color::new(0.25, 0.0, 0.75, 0.5));
+	}
+
+	#[test]
+	fn linear_texcoords_follow_the_axis() {
+		let gradient = Gradient::linear(Point {x: 10.0, y: 0.0}, Point {x: 20.0, y: 0.0});
+		assert_point(gradient.texcoord(Point {x: 10.0, y: 0.0}), Point {x: 0.0, y: 0.5});
+		assert_point(gradient.texcoord(Point {x: 15.0, y: 7.0}), Point {x: 0.5, y: 0.5});
+		assert_point(gradient.texcoord(Point {x: 25.0, y: -3.0}), Point {x: 1.5, y: 0.5});
+
+		let gradient = Gradient::linear(Point {x: 0.0, y: 0.0}, Point {x: 3.0, y: 4.0});
+		assert_point(gradient.texcoord(Point {x: 3.0, y: 4.0}), Point {x: 1.0, y: 0.5});
+		assert_point(gradient.texcoord(Point {x: 4.0, y: -3.0}), Point {x: 0.0, y: 0.5});
+
+		let point = Point {x: 5.0, y: 5.0};
+		assert_point(Gradient::linear(point, point).texcoord(point), Point {x: 0.0, y: 0.5});
+		assert_eq!(gradient.to_image().get_size(), ::Size {width: LINEAR_SIZE as f64, height: 1.0});
+	}
+
+	#[test]
+	fn radial_texcoords_match_the_image() {
+		let gradient = Gradient::radial(Point {x: 10.0, y: 10.0}, 10.0);
+		assert_point(gradient.texcoord(Point {x: 10.0, y: 10.0}), Point {x: 0.5, y: 0.5});
+		for &(point, offset) in &[(Point {x: 20.0, y: 10.0}, 1.0), (Point {x: 10.0, y: 5.0}, 0.5), (Point {x: 40.0, y: 50.0}, 5.0)] {
+			// The image is drawn with the offset at each texcoord from the center of the texture.
+			let texcoord = gradient.texcoord(point) - Point {x: 0.5, y: 0.5};
+			assert!((texcoord.length() * RADIAL_EXTENT * 2.0 - offset).abs() < 1e-9);
+		}
+
+		let point = Point {x: 1.0, y: 2.0};
+		assert_point(Gradient::radial(point, 0.0).texcoord(Point {x: 5.0, y: 5.0}), Point {x: 0.5, y: 0.5});
+		assert_eq!(gradient.to_image().get_size(), ::Size {width: RADIAL_SIZE as f64, height: RADIAL_SIZE as f64});
+	}
+}
\ No newline at end of file
@@ -0,0 +1,158 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate image;
+use self::image::{DynamicImage, ImageResult};
+
+extern crate backtrace;
+use self::backtrace::Backtrace;
+
+use ::Size;
+use super::GraphicsError;
+
+use std::path::Path;
+
+/// An image stored in memory as 8-bit RGBA pixels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+	data: Vec<u8>,
+	width: u32,
+	height: u32
+}
+
+impl Image {
+	/// Creates an image from RGBA pixels, ordered left to right and top to bottom.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidImage`](enum.GraphicsError.html#variant.InvalidImage)
+	/// if the length of the data does not match the size.
+	pub fn from_rgba(data: Vec<u8>, width: u32, height: u32) -> Result<Image, GraphicsError> {
+		let length = (width as usize).checked_mul(height as usize)
+			.and_then(|pixels| pixels.checked_mul(4));
+		match length {
+			Some(length) if length == data.len() => (),
+			Some(length) => return Err(GraphicsError::InvalidImage(
+				format!("Expected {} bytes of RGBA data, found {}", length, data.len()),
+				Backtrace::new()
+			)),
+			None => return Err(GraphicsError::InvalidImage(
+				format!("A {}x{} image is too large", width, height),
+				Backtrace::new()
+			))
+		}
+		Ok(Image {data, width, height})
+	}
+
+	/// Loads an image from a file.
+	/// The format is detected from the file extension.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidImage`](enum.GraphicsError.html#variant.InvalidImage)
+	/// if the file could not be read or decoded.
+	pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Image, GraphicsError> {
+		Image::decode(image::open(path))
+	}
+
+	/// Loads an image from an encoded file in memory.
+	/// The format is detected from the data.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidImage`](enum.GraphicsError.html#variant.InvalidImage)
+	/// if the data could not be decoded.
+	pub fn from_memory(data: &[u8]) -> Result<Image, GraphicsError> {
+		Image::decode(image::load_from_memory(data))
+	}
+
+	fn decode(result: ImageResult<DynamicImage>) -> Result<Image, GraphicsError> {
+		let image = result
+			.map_err(|error| GraphicsError::InvalidImage(
+				ToString::to_string(&error),
+				Backtrace::new()
+			))?
+			.to_rgba();
+		let (width, height) = image.dimensions();
+		Ok(Image {
+			data: image.into_raw(),
+			width,
+			height
+		})
+	}
+
+	/// Gets the size of the image in pixels.
+	pub fn get_size(&self) -> Size {
+		Size {
+			width: self.width as f64,
+			height: self.height as f64
+		}
+	}
+
+	pub(crate) fn get_width(&self) -> u32 {
+		self.width
+	}
+
+	pub(crate) fn get_height(&self) -> u32 {
+		self.height
+	}
+
+	/// Gets the RGBA pixels of the image.
+	pub fn get_data(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// Copies an image into this one with its top-left at the pixel,
+	/// cutting off anything outside.
+	pub(crate) fn blit(&mut self, image: &Image, x: u32, y: u32) {
+		if x >= self.width || y >= self.height {
+			return;
+		}
+		let width = image.width.min(self.width - x) as usize * 4;
+		let height = image.height.min(self.height - y) as usize;
+		for row in 0..height {
+			let source = row * image.width as usize * 4;
+			let target = ((y as usize + row) * self.width as usize + x as usize) * 4;
+			self.data[target..target + width].copy_from_slice(&image.data[source..source + width]);
+		}
+	}
+
+	/// Copies part of the image into a new image.
+	pub(crate) fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Image {
+		let mut image = Image {
+			data: vec![0; width as usize * height as usize * 4],
+			width,
+			height
+		};
+		for row in 0..height.min(self.height.saturating_sub(y)) as usize {
+			let columns = width.min(self.width.saturating_sub(x)) as usize * 4;
+			let source = ((y as usize + row) * self.width as usize + x as usize) * 4;
+			let target = row * width as usize * 4;
+			image.data[target..target + columns].copy_from_slice(&self.data[source..source + columns]);
+		}
+		image
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn from_rgba_checks_the_length() {
+		assert!(Image::from_rgba(vec![0; 8], 2, 1).is_ok());
+		match Image::from_rgba(vec![0; 4], 2, 1) {
+			Err(GraphicsError::InvalidImage(message, _)) => assert_eq!(message, "Expected 8 bytes of RGBA data, found 4"),
+			_ => panic!("accepted the wrong length")
+		}
+		// The expected length doesn't fit in 32 bits.
+		assert!(Image::from_rgba(Vec::new(), 65536, 65536).is_err());
+		assert!(Image::from_rgba(Vec::new(), u32::MAX, u32::MAX).is_err());
+	}
+}
\ No newline at end of file
@@ -0,0 +1,375 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate unicode_segmentation;
+use self::unicode_segmentation::UnicodeSegmentation;
+
+use ::{Size, Point};
+use super::Font;
+
+// How many spaces a tab is as wide as.
+const TAB_SPACES: f64 = 4.0;
+
+/// How lines are positioned horizontally.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Align {
+	Left,
+	Center,
+	Right,
+
+	/// Stretches the spaces of wrapped lines so they fill the width.
+	/// The last line of each paragraph is aligned to the left.
+	Justify
+}
+
+impl Default for Align {
+	fn default() -> Align {
+		Align::Left
+	}
+}
+
+/// How text is laid out by a [`TextLayout`](struct.TextLayout.html).
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LayoutOptions {
+	/// The width that lines are wrapped to, between words where possible.
+	/// Lines are only broken at `\n` if this is `None`.
+	pub max_width: Option<f64>,
+
+	/// How lines are positioned within the maximum width,
+	/// or within the widest line if there is no maximum.
+	pub align: Align,
+
+	/// Multiplies the line height of the font.
+	pub line_spacing: f64
+}
+
+impl Default for LayoutOptions {
+	fn default() -> LayoutOptions {
+		LayoutOptions {
+			max_width: None,
+			align: Align::Left,
+			line_spacing: 1.0
+		}
+	}
+}
+
+/// Splits text into the byte ranges of user-perceived characters,
+/// following the Unicode extended grapheme cluster boundaries.
+pub(crate) fn graphemes(text: &str) -> Vec<(usize, usize)> {
+	text.grapheme_indices(true)
+		.map(|(start, cluster)| (start, start + cluster.len()))
+		.collect()
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Cluster {
+	start: usize,
+	end: usize,
+	x: f64,
+	advance: f64,
+	space: bool
+}
+
+#[derive(Clone, Debug)]
+struct Line {
+	clusters: Vec<Cluster>,
+	width: f64,
+	// Whether the line was broken to fit, rather than ending a paragraph.
+	wrapped: bool
+}
+
+impl Line {
+	fn new() -> Line {
+		Line {
+			clusters: Vec::new(),
+			width: 0.0,
+			wrapped: false
+		}
+	}
+}
+
+/// Text that has been broken into lines and positioned, ready to be drawn with
+/// [`Graphics.draw_layout`](struct.Graphics.html#method.draw_layout).
+///
+/// Laying out text measures every character, so a layout can be kept
+/// and drawn every frame until the text changes.
+#[derive(Clone)]
+pub struct TextLayout {
+	font: Font,
+	text: String,
+	glyphs: Vec<(char, Point)>,
+	size: Size,
+	lines: usize
+}
+
+impl TextLayout {
+	/// Lays out the text with the font and options.
+	pub fn new(font: &Font, text: &str, options: LayoutOptions) -> TextLayout {
+		let mut lines = Vec::new();
+		for paragraph in text.split('\n') {
+			let start = paragraph.as_ptr() as usize - text.as_ptr() as usize;
+			layout_paragraph(font, text, start, start + paragraph.len(), options.max_width, &mut lines);
+		}
+
+		let widest = lines.iter().fold(0.0, |widest: f64, line| widest.max(line.width));
+		let width = options.max_width.unwrap_or(widest);
+		let line_height = font.get_line_height() * options.line_spacing;
+
+		let mut glyphs = Vec::with_capacity(text.len());
+		for (index, line) in lines.iter().enumerate() {
+			let spaces = line.clusters.iter().filter(|cluster| cluster.space).count();
+			let (offset, stretch) = match options.align {
+				Align::Left => (0.0, 0.0),
+				Align::Center => ((width - line.width) / 2.0, 0.0),
+				Align::Right => (width - line.width, 0.0),
+				Align::Justify if line.wrapped && spaces > 0 => (0.0, (width - line.width).max(0.0) / spaces as f64),
+				Align::Justify => (0.0, 0.0)
+			};
+
+			let y = index as f64 * line_height;
+			let mut stretched = 0.0;
+			for cluster in &line.clusters {
+				let mut x = offset + cluster.x + stretched;
+				for c in text[cluster.start..cluster.end].chars().filter(|c| !c.is_control()) {
+					glyphs.push((c, Point {x, y}));
+					x += font.get_advance(c);
+				}
+				if cluster.space {
+					stretched += stretch;
+				}
+			}
+		}
+
+		let height = match lines.len() {
+			0 => 0.0,
+			count => (count - 1) as f64 * line_height + font.get_line_height()
+		};
+		TextLayout {
+			font: font.clone(),
+			text: text.to_string(),
+			glyphs,
+			size: Size {
+				width: width.max(widest),
+				height
+			},
+			lines: lines.len()
+		}
+	}
+
+	/// Gets the font the text is laid out with.
+	pub fn get_font(&self) -> &Font {
+		&self.font
+	}
+
+	/// Gets the text that was laid out.
+	pub fn get_text(&self) -> &str {
+		&self.text
+	}
+
+	/// Gets the size of the area the lines are aligned in, which is as wide as
+	/// the maximum width, or the widest line if there is no maximum.
+	pub fn get_size(&self) -> Size {
+		self.size
+	}
+
+	/// Gets how many lines the text was broken into.
+	pub fn get_line_count(&self) -> usize {
+		self.lines
+	}
+
+	/// Gets each character with the position of its top-left, relative to the layout.
+	pub(crate) fn get_glyphs(&self) -> &[(char, Point)] {
+		&self.glyphs
+	}
+}
+
+/// Gets how far a cluster moves the pen, where tabs are a few spaces wide.
+fn cluster_advance(font: &Font, cluster: &str) -> f64 {
+	cluster.chars().map(|c| match c {
+		'\t' => font.get_advance(' ') * TAB_SPACES,
+		c if c.is_control() => 0.0,
+		c => font.get_advance(c)
+	}).sum()
+}
+
+/// Breaks a paragraph into lines, between words where possible.
+fn layout_paragraph(font: &Font, text: &str, start: usize, end: usize, max_width: Option<f64>, lines: &mut Vec<Line>) {
+	let mut line = Line::new();
+	let mut previous: Option<char> = None;
+	// Where the current word starts in the line, to move it to the next line.
+	let mut word_start = 0;
+
+	for (cluster_start, cluster_end) in graphemes(&text[start..end]) {
+		let (cluster_start, cluster_end) = (start + cluster_start, start + cluster_end);
+		let string = &text[cluster_start..cluster_end];
+		let first = string.chars().next().unwrap();
+		let space = string.chars().all(char::is_whitespace);
+
+		let kerning = match previous {
+			Some(previous) => font.get_kerning(previous, first),
+			None => 0.0
+		};
+		let mut cluster = Cluster {
+			start: cluster_start,
+			end: cluster_end,
+			x: line.width + kerning,
+			advance: cluster_advance(font, string),
+			space
+		};
+		previous = string.chars().last();
+
+		if space {
+			line.width = cluster.x + cluster.advance;
+			line.clusters.push(cluster);
+			word_start = line.clusters.len();
+			continue;
+		}
+
+		let overflows = max_width.map_or(false, |max_width| cluster.x + cluster.advance > max_width);
+		if overflows && !line.clusters.is_empty() {
+			// Moves the current word to a new line, or breaks it if it is the whole line.
+			let word = if word_start > 0 {
+				line.clusters.split_off(word_start)
+			} else {
+				Vec::new()
+			};
+			while line.clusters.last().map_or(false, |cluster| cluster.space) {
+				line.clusters.pop();
+			}
+			line.width = line.clusters.last().map_or(0.0, |cluster| cluster.x + cluster.advance);
+			line.wrapped = true;
+			lines.push(line);
+
+			line = Line::new();
+			let shift = word.first().map_or(0.0, |cluster| cluster.x);
+			for mut moved in word {
+				moved.x -= shift;
+				line.clusters.push(moved);
+			}
+			word_start = 0;
+			line.width = line.clusters.last().map_or(0.0, |cluster| cluster.x + cluster.advance);
+			cluster.x = line.width + if line.clusters.is_empty() {0.0} else {kerning};
+		}
+
+		line.width = cluster.x + cluster.advance;
+		line.clusters.push(cluster);
+	}
+	lines.push(line);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::{Graphics, Image, TextureFilter};
+
+	#[cfg(feature = "serde")]
+	extern crate serde_json;
+
+	// Letters are 10 pixels wide and spaces are 5, with "ab" kerned 2 pixels closer.
+	const FONT: &str = "info face=\"Test\" size=10\n\
+		common lineHeight=12 base=10 pages=1\n\
+		page id=0 file=\"a.png\"\n\
+		char id=97 x=0 y=0 width=1 height=1 xoffset=0 yoffset=0 xadvance=10 page=0\n\
+		char id=98 x=0 y=0 width=1 height=1 xoffset=0 yoffset=0 xadvance=10 page=0\n\
+		char id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=5 page=0\n\
+		kerning first=97 second=98 amount=-2\n";
+
+	fn font() -> Font {
+		let mut graphics = Graphics::software(1, 1);
+		let page = Image::from_rgba(vec![0; 4], 1, 1).unwrap();
+		graphics.create_font(FONT.as_bytes(), &[page], TextureFilter::Nearest).unwrap()
+	}
+
+	fn layout(text: &str, max_width: Option<f64>, align: Align) -> TextLayout {
+		TextLayout::new(&font(), text, LayoutOptions {max_width, align, ..LayoutOptions::default()})
+	}
+
+	fn positions(layout: &TextLayout) -> Vec<(f64, f64)> {
+		layout.get_glyphs().iter().map(|&(_, point)| (point.x, point.y)).collect()
+	}
+
+	#[test]
+	fn graphemes_follow_unicode() {
+		assert_eq!(graphemes("e\u{301}x"), [(0, 3), (3, 4)]);
+		assert_eq!(graphemes("\r\n"), [(0, 2)]);
+		// Two flags, and a family joined with zero width joiners.
+		assert_eq!(graphemes("\u{1F1F3}\u{1F1FF}\u{1F1E6}\u{1F1FA}"), [(0, 8), (8, 16)]);
+		assert_eq!(graphemes("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}").len(), 1);
+		assert!(graphemes("").is_empty());
+	}
+
+	#[test]
+	fn measure_uses_advances_and_kerning() {
+		let font = font();
+		assert_eq!(font.measure("ab"), Size {width: 18.0, height: 12.0});
+		assert_eq!(font.measure("ab\na"), Size {width: 18.0, height: 24.0});
+		assert_eq!(font.measure("a\tb"), Size {width: 40.0, height: 12.0});
+		assert_eq!(font.measure(""), Size {width: 0.0, height: 12.0});
+	}
+
+	#[test]
+	fn lines_wrap_between_words() {
+		let layout = layout("aa aa aa", Some(25.0), Align::Left);
+		assert_eq!(layout.get_line_count(), 3);
+		assert_eq!(layout.get_size(), Size {width: 25.0, height: 36.0});
+		assert_eq!(positions(&layout), [
+			(0.0, 0.0), (10.0, 0.0),
+			(0.0, 12.0), (10.0, 12.0),
+			(0.0, 24.0), (10.0, 24.0)
+		]);
+	}
+
+	#[test]
+	fn long_words_are_broken() {
+		let layout = layout("aaaaa", Some(25.0), Align::Left);
+		assert_eq!(layout.get_line_count(), 3);
+		assert_eq!(positions(&layout), [
+			(0.0, 0.0), (10.0, 0.0),
+			(0.0, 12.0), (10.0, 12.0),
+			(0.0, 24.0)
+		]);
+	}
+
+	#[test]
+	fn justify_stretches_wrapped_lines() {
+		let justify = layout("a a aa", Some(28.0), Align::Justify);
+		assert_eq!(justify.get_line_count(), 2);
+		// The last line of the paragraph stays on the left.
+		assert_eq!(positions(&justify), [
+			(0.0, 0.0), (10.0, 0.0), (18.0, 0.0),
+			(0.0, 12.0), (10.0, 12.0)
+		]);
+
+		let right = layout("a a aa", Some(28.0), Align::Right);
+		assert_eq!(positions(&right)[3..], [(8.0, 12.0), (18.0, 12.0)]);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn options_serde_round_trips() {
+		let options = LayoutOptions {
+			max_width: Some(120.0),
+			align: Align::Justify,
+			line_spacing: 1.5
+		};
+		let json = serde_json::to_string(&options).unwrap();
+		assert_eq!(serde_json::from_str::<LayoutOptions>(&json).unwrap(), options);
+
+		let options: LayoutOptions = serde_json::from_str(r#"{"align": "Center"}"#).unwrap();
+		assert_eq!(options, LayoutOptions {align: Align::Center, ..LayoutOptions::default()});
+	}
+}
\ No newline at end of file
@@ -14,28 +14,81 @@
 
 //! Manages all the drawing and graphics.
 
-extern crate glutin;
-use self::glutin::GlContext;
-
 extern crate backtrace;
 use self::backtrace::Backtrace;
 
 mod gl;
-use self::gl::{Gl, CheckError, types::*};
+mod debug;
+mod program;
+
+mod image;
+pub use self::image::Image;
+
+mod texture;
+pub use self::texture::Texture;
+
+mod backend;
+pub use self::backend::{RenderBackend, TextureFilter, DrawState, MaskMode, Vertex, Point32, Color8};
+
+mod blend;
+pub use self::blend::BlendMode;
+
+mod opengl;
+use self::opengl::GlBackend;
+
+mod software;
+pub use self::software::SoftwareBackend;
+use self::software::SoftwareWindowBackend;
+
+mod color;
+pub use self::color::Color;
+
+mod angle;
+pub use self::angle::Angle;
+
+mod shape;
+pub use self::shape::{Stroke, LineJoin, LineCap};
+use self::shape::Mesh;
+
+mod gradient;
+pub use self::gradient::Gradient;
+
+mod path;
+pub use self::path::{Path, FillRule};
+
+mod truetype;
+mod glyphs;
+
+mod atlas;
+pub use self::atlas::{Atlas, AtlasBuilder, Sprite};
+
+mod font;
+pub use self::font::Font;
+
+mod layout;
+pub use self::layout::{TextLayout, LayoutOptions, Align};
+
+mod camera;
+pub use self::camera::{Camera, Scaling};
 
 mod state;
-pub use self::state::{Color, Angle};
 use self::state::State;
 
-use ::{Size, Point};
-use ::core::{RcWindow, WindowError};
+use ::{Size, Point, Rect};
+use ::core::{RcWindow, WindowError, Config, Renderer};
+use ::input::Input;
 
+use std::fmt::{Display, Formatter, Error};
+use std::error;
+use std::fs;
+use std::path::{Path as FilePath, Component};
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::mem;
-use std::ptr;
 
-const BUFFER_SIZE: usize = 4;
+const BUFFER_SIZE: usize = 4096;
+
+// The furthest a curve can be from the true shape, in pixels.
+const CURVE_TOLERANCE: f64 = 0.25;
 
 /// Possible errors that can occur from push/pop operations.
 #[derive(Clone, Debug)]
@@ -46,89 +99,202 @@ pub enum StackError {
 	StackUnderflow(Backtrace)
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-struct Point32 {
-	x: f32,
-	y: f32
+impl Display for StackError {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+		match self {
+			&StackError::StackUnderflow(..) => write!(f, "Stack underflow")
+		}
+	}
+}
+
+impl error::Error for StackError {}
+
+/// Possible errors that can occur from graphics related actions.
+#[derive(Clone, Debug)]
+pub enum GraphicsError {
+	/// OpenGL reported an error.
+	/// Contains the name of the error and the call that caused it.
+	/// This is only returned if
+	/// [`Config.gl_errors`](../core/struct.Config.html#structfield.gl_errors) is
+	/// [`GlErrors::Report`](../core/enum.GlErrors.html#variant.Report).
+	GlError(&'static str, &'static str, Backtrace),
+
+	/// An image could not be loaded or has invalid data.
+	InvalidImage(String, Backtrace),
+
+	/// Path data could not be parsed.
+	InvalidPath(String, Backtrace),
+
+	/// A font could not be loaded or has invalid data.
+	InvalidFont(String, Backtrace),
+
+	/// A size was zero, negative or not finite where it has to be positive.
+	InvalidSize(String, Backtrace),
+
+	/// A texture created by other graphics was drawn, which draws nothing.
+	/// Returned by the next [`Window.update`](../core/struct.Window.html#method.update).
+	ForeignTexture(Backtrace)
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-struct Color8 {
-	red:   u8,
-	green: u8,
-	blue:  u8,
-	alpha: u8
+impl Display for GraphicsError {
+	fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+		match self {
+			&GraphicsError::GlError(error, call, ..) => write!(f, "{} in {}", error, call),
+			&GraphicsError::InvalidImage(ref error, ..) => write!(f, "{}", error),
+			&GraphicsError::InvalidPath(ref error, ..) => write!(f, "{}", error),
+			&GraphicsError::InvalidFont(ref error, ..) => write!(f, "{}", error),
+			&GraphicsError::InvalidSize(ref error, ..) => write!(f, "{}", error),
+			&GraphicsError::ForeignTexture(..) => write!(f, "Texture was created by other graphics")
+		}
+	}
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-struct Vertex {
-	point: Point32,
-	texcoord: Point32,
-	color: Color8
+impl error::Error for GraphicsError {}
+
+impl GraphicsError {
+	/// Gets the backtrace captured when the error occurred.
+	pub fn backtrace(&self) -> &Backtrace {
+		match self {
+			&GraphicsError::GlError(_, _, ref backtrace) => backtrace,
+			&GraphicsError::InvalidImage(_, ref backtrace) => backtrace,
+			&GraphicsError::InvalidPath(_, ref backtrace) => backtrace,
+			&GraphicsError::InvalidFont(_, ref backtrace) => backtrace,
+			&GraphicsError::InvalidSize(_, ref backtrace) => backtrace,
+			&GraphicsError::ForeignTexture(ref backtrace) => backtrace
+		}
+	}
 }
 
-struct GraphicsImpl {
-	window: RcWindow,
-	gl: Gl,
+impl StackError {
+	/// Gets the backtrace captured when the error occurred.
+	pub fn backtrace(&self) -> &Backtrace {
+		match self {
+			&StackError::StackUnderflow(ref backtrace) => backtrace
+		}
+	}
+}
+
+pub(crate) struct GraphicsImpl {
+	backend: Box<dyn RenderBackend>,
 	state: Vec<State>,
-	vertex_array: GLuint,
-	elements: GLuint,
-	buffer: GLuint,
-	buffer_data: Vec<Vertex>
+	buffer_data: Vec<Vertex>,
+	elements_data: Vec<u16>,
+	white: u32,
+	draw_state: DrawState,
+	cursor: Option<(Rc<Image>, u32)>,
+	// Gradient textures with whether or not they were used this frame.
+	gradients: Vec<(Rc<Gradient>, u32, bool)>,
+	virtual_size: Option<(Size, Scaling)>,
+	letterbox: Color,
+	// The area of the target the virtual size is scaled to, if there is one.
+	viewport: Option<Rect>,
+	// The first misuse since the last update, which isn't reported by the backend.
+	error: Option<GraphicsError>
 }
 
 impl GraphicsImpl {
-	fn new(window: RcWindow) -> GraphicsImpl {
-		unsafe {
-			let gl = {
-				let context = &window.borrow().window;
-				Gl::load_with(|name| context.get_proc_address(name) as *const _)
-			};
-
-			let mut vertex_array = 0;
-			gl.GenVertexArrays(1, &mut vertex_array);
-			gl.BindVertexArray(vertex_array);
-
-			let mut elements = 0;
-			gl.GenBuffers(1, &mut elements);
-			gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, elements);
-
-			let elements_data = [0u8, 1u8, 2u8, 2u8, 1u8, 3u8];
-			gl.BufferData(
-				gl::ELEMENT_ARRAY_BUFFER,
-				elements_data.len() as GLsizeiptr,
-				elements_data.as_ptr() as *const _,
-				gl::STATIC_DRAW
-			);
-
-			let mut buffer = 0;
-			gl.GenBuffers(1, &mut buffer);
-			gl.BindBuffer(gl::ARRAY_BUFFER, buffer);
-
-			gl.EnableVertexAttribArray(0);
-			gl.EnableVertexAttribArray(1);
-			gl.EnableVertexAttribArray(2);
-
-			let stride = mem::size_of::<Vertex>() as GLsizei;
-			gl.VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, offset_of!(Vertex, point) as *const _);
-			gl.VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, stride, offset_of!(Vertex, texcoord) as *const _);
-			gl.VertexAttribPointer(2, 4, gl::UNSIGNED_BYTE, gl::TRUE, stride, offset_of!(Vertex, color) as *const _);
-
-			gl.check_error();
-			GraphicsImpl {
-				window,
-				gl,
-				state: vec![State::default()],
-				vertex_array,
-				elements,
-				buffer,
-				buffer_data: Vec::with_capacity(BUFFER_SIZE)
-			}
+	fn new(mut backend: Box<dyn RenderBackend>) -> GraphicsImpl {
+		let white = Image::from_rgba(vec![255; 4], 1, 1).unwrap();
+		let white = backend.create_texture(&white);
+		GraphicsImpl {
+			backend,
+			state: vec![State::default()],
+			buffer_data: Vec::with_capacity(BUFFER_SIZE),
+			elements_data: Vec::with_capacity(BUFFER_SIZE / 4 * 6),
+			white,
+			draw_state: DrawState {
+				texture: white,
+				blend: BlendMode::Alpha,
+				clip: None,
+				mask: MaskMode::Disabled
+			},
+			cursor: None,
+			gradients: Vec::new(),
+			virtual_size: None,
+			letterbox: Color::BLACK,
+			viewport: None,
+			error: None
+		}
+	}
+
+	fn call(&mut self, call: &'static str) {
+		self.backend.set_call(call);
+	}
+
+	/// Keeps the error until the next update, unless there already is one.
+	fn report(&mut self, error: GraphicsError) {
+		if self.error.is_none() {
+			self.error = Some(error);
 		}
 	}
 
-	fn vertex(&mut self, point: Point, texcoord: Point) {
+	fn take_error(&mut self) -> Option<GraphicsError> {
+		self.error.take().or_else(|| self.backend.take_error())
+	}
+
+	/// Uses the texture and the blend mode, clip and mask of the current state
+	/// for the next triangles, flushing the batch if any of them changed.
+	fn bind(&mut self, texture: u32) {
+		let state = self.state.last().unwrap();
+		let clip = match self.viewport {
+			Some(viewport) => {
+				let round = |point: Point| Point {x: point.x.round(), y: point.y.round()};
+				let clip = state.get_clip().map_or(viewport, |clip| Rect::from_points(
+					round(self.to_target(clip.top_left())),
+					round(self.to_target(clip.bottom_right()))
+				));
+				Some(clip.intersection(&viewport).unwrap_or(Rect::new(0.0, 0.0, 0.0, 0.0)))
+			},
+			None => state.get_clip()
+		};
+		let draw_state = DrawState {
+			texture,
+			blend: state.get_blend(),
+			clip,
+			mask: state.get_mask()
+		};
+		if self.draw_state != draw_state {
+			self.flush();
+			self.draw_state = draw_state;
+		}
+	}
+
+	fn delete_texture(&mut self, texture: u32) {
+		if self.draw_state.texture == texture {
+			let white = self.white;
+			self.bind(white);
+		}
+		self.backend.delete_texture(texture);
+	}
+
+	/// Converts a point from the virtual size to the target.
+	fn to_target(&self, point: Point) -> Point {
+		match (self.virtual_size, self.viewport) {
+			(Some((size, _)), Some(viewport)) => Point {
+				x: viewport.origin.x + point.x * viewport.size.width / size.width,
+				y: viewport.origin.y + point.y * viewport.size.height / size.height
+			},
+			_ => point
+		}
+	}
+
+	/// Gets how much the virtual size is scaled by.
+	fn get_virtual_scale(&self) -> f64 {
+		match (self.virtual_size, self.viewport) {
+			(Some((size, _)), Some(viewport)) =>
+				(viewport.size.width / size.width).min(viewport.size.height / size.height),
+			_ => 1.0
+		}
+	}
+
+	fn update_viewport(&mut self) {
+		let target = self.backend.get_size();
+		self.viewport = self.virtual_size.map(|(size, scaling)| scaling.get_viewport(size, target));
+	}
+
+	fn vertex(&mut self, point: Point, texcoord: Point) -> u16 {
 		let point = self.state.last().unwrap().transform(point);
+		let point = self.to_target(point);
 		let color = self.state.last().unwrap().get_color();
 		self.buffer_data.push(Vertex {
 			point: Point32 {
@@ -139,44 +305,211 @@ impl GraphicsImpl {
 				x: texcoord.x as f32,
 				y: texcoord.y as f32
 			},
-			color: Color8 {
-				red:   (color.red   * 255.0) as u8,
-				green: (color.green * 255.0) as u8,
-				blue:  (color.blue  * 255.0) as u8,
-				alpha: (color.alpha * 255.0) as u8
-			}
+			color: Color8::from(color)
 		});
+		(self.buffer_data.len() - 1) as u16
+	}
 
-		if self.buffer_data.len() == BUFFER_SIZE {
+	fn reserve(&mut self, vertices: usize) {
+		if self.buffer_data.len() + vertices > BUFFER_SIZE {
 			self.flush();
 		}
 	}
 
-	fn flush(&mut self) {
-		unsafe {
-			if self.buffer_data.len() > 0 {
-				self.gl.BufferData(
-					gl::ARRAY_BUFFER,
-					(self.buffer_data.len() * mem::size_of::<Vertex>()) as GLsizeiptr,
-					self.buffer_data.as_ptr() as *const _,
-					gl::STREAM_DRAW
-				);
-				// TODO: fix element buffer size
-				self.gl.DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_BYTE, ptr::null());
-				self.gl.check_error();
-				self.buffer_data.clear();
+	/// Adds a quad with the points ordered top-left, top-right,
+	/// bottom-left and bottom-right.
+	fn quad(&mut self, points: [Point; 4], texcoords: [Point; 4]) {
+		self.reserve(4);
+		let a = self.vertex(points[0], texcoords[0]);
+		let b = self.vertex(points[1], texcoords[1]);
+		let c = self.vertex(points[2], texcoords[2]);
+		let d = self.vertex(points[3], texcoords[3]);
+		self.elements_data.extend_from_slice(&[a, b, c, c, b, d]);
+	}
+
+	/// Gets the texture of a gradient, creating it if it is not cached.
+	fn gradient_texture(&mut self, gradient: &Rc<Gradient>) -> u32 {
+		let cached = self.gradients.iter_mut()
+			.find(|cached| Rc::ptr_eq(&cached.0, gradient));
+		if let Some(cached) = cached {
+			cached.2 = true;
+			return cached.1;
+		}
+
+		let texture = self.backend.create_texture_with_filter(&gradient.to_image(), TextureFilter::Linear);
+		self.gradients.push((Rc::clone(gradient), texture, true));
+		texture
+	}
+
+	/// Deletes the gradient textures that were not used since the last call.
+	fn clean_gradients(&mut self) {
+		let (used, unused) = self.gradients.drain(..).partition(|cached| cached.2);
+		self.gradients = used;
+		for (_, texture, _) in unused {
+			self.delete_texture(texture);
+		}
+		for cached in &mut self.gradients {
+			cached.2 = false;
+		}
+	}
+
+	/// Adds the triangles of a mesh filled with the current gradient or color.
+	fn mesh(&mut self, mesh: &Mesh) {
+		let gradient = self.state.last().unwrap().get_gradient().cloned();
+		let texture = match gradient {
+			Some(ref gradient) => self.gradient_texture(gradient),
+			None => self.white
+		};
+		self.bind(texture);
+
+		let texcoord = |point: Point| match gradient {
+			Some(ref gradient) => gradient.texcoord(point),
+			None => Point {x: 0.5, y: 0.5}
+		};
+		if mesh.points.len() <= BUFFER_SIZE {
+			self.reserve(mesh.points.len());
+			let base = self.buffer_data.len();
+			for &point in &mesh.points {
+				self.vertex(point, texcoord(point));
+			}
+			self.elements_data.extend(mesh.indices.iter().map(|&index| (base + index) as u16));
+		} else {
+			// Too big to fit in one batch, so the triangles are split up.
+			for triangle in mesh.indices.chunks(3) {
+				self.reserve(3);
+				for &index in triangle {
+					let point = mesh.points[index];
+					let element = self.vertex(point, texcoord(point));
+					self.elements_data.push(element);
+				}
+			}
+		}
+	}
+
+	fn tolerance(&self) -> f64 {
+		let scale = self.state.last().unwrap().get_scale() * self.get_virtual_scale();
+		if scale > 0.0 {
+			CURVE_TOLERANCE / scale
+		} else {
+			CURVE_TOLERANCE
+		}
+	}
+
+	fn fill_convex(&mut self, points: &[Point]) {
+		let mut mesh = Mesh::new();
+		shape::fill_convex(&mut mesh, points);
+		self.mesh(&mesh);
+	}
+
+	fn stroke(&mut self, points: &[Point], closed: bool) {
+		let mut mesh = Mesh::new();
+		let stroke = self.state.last().unwrap().get_stroke();
+		let tolerance = self.tolerance();
+		shape::stroke(&mut mesh, &stroke, points, closed, tolerance);
+		self.mesh(&mesh);
+	}
+
+	fn rect(&mut self, rect: Rect, texcoords: Rect) {
+		self.quad([
+			rect.top_left(),
+			rect.top_right(),
+			rect.bottom_left(),
+			rect.bottom_right()
+		], [
+			texcoords.top_left(),
+			texcoords.top_right(),
+			texcoords.bottom_left(),
+			texcoords.bottom_right()
+		]);
+	}
+
+	/// Adds the characters of a layout with its top-left at the point.
+	/// The text must have been prepared with the font.
+	fn draw_layout(&mut self, layout: &TextLayout, point: Point) {
+		let font = layout.get_font();
+		for &(c, position) in layout.get_glyphs() {
+			if let Some(quad) = font.get_quad(c) {
+				self.bind(quad.texture);
+				self.rect(quad.rect.translate(point + position), Rect {
+					origin: Point {
+						x: quad.region.origin.x / quad.texture_size.width,
+						y: quad.region.origin.y / quad.texture_size.height
+					},
+					size: quad.region.size / quad.texture_size
+				});
+			}
+		}
+	}
+
+	fn update_texture(&mut self, texture: u32, x: u32, y: u32, image: &Image) {
+		// Batched triangles would otherwise be drawn with the new contents.
+		if self.draw_state.texture == texture {
+			self.flush();
+		}
+		self.backend.update_texture(texture, x, y, image);
+	}
+
+	/// Fills the area around the viewport with the letterbox color.
+	fn draw_letterbox(&mut self, viewport: Rect) {
+		let target = Rect::from(self.backend.get_size());
+		let bars = [
+			Rect::from_points(target.top_left(), Point {x: target.right(), y: viewport.top()}),
+			Rect::from_points(Point {x: target.left(), y: viewport.bottom()}, target.bottom_right()),
+			Rect::from_points(Point {x: target.left(), y: viewport.top()}, viewport.bottom_left()),
+			Rect::from_points(viewport.top_right(), Point {x: target.right(), y: viewport.bottom()})
+		];
+
+		let mut state = State::default();
+		state.color(self.letterbox);
+		self.state.push(state);
+		let white = self.white;
+		self.bind(white);
+		for bar in bars.iter().filter(|bar| bar.size.width > 0.0 && bar.size.height > 0.0) {
+			self.rect(*bar, Rect::new(0.0, 0.0, 1.0, 1.0));
+		}
+		self.state.pop();
+	}
+
+	fn draw_cursor(&mut self, image: &Rc<Image>, hotspot: Point, point: Point) {
+		let texture = match self.cursor {
+			Some((ref cursor, texture)) if Rc::ptr_eq(cursor, image) => texture,
+			_ => {
+				if let Some((_, texture)) = self.cursor.take() {
+					self.delete_texture(texture);
+				}
+				let texture = self.backend.create_texture(image);
+				self.cursor = Some((Rc::clone(image), texture));
+				texture
 			}
+		};
+
+		self.state.push(State::default());
+		self.bind(texture);
+		self.rect(Rect {
+			origin: point - hotspot,
+			size: image.get_size()
+		}, Rect::new(0.0, 0.0, 1.0, 1.0));
+		self.state.pop();
+	}
+
+	fn flush(&mut self) {
+		if self.elements_data.len() > 0 {
+			self.backend.draw(&self.draw_state, &self.buffer_data, &self.elements_data);
 		}
+		self.buffer_data.clear();
+		self.elements_data.clear();
 	}
 }
 
 impl Drop for GraphicsImpl {
 	fn drop(&mut self) {
-		unsafe {
-			self.gl.DeleteBuffers(1, &self.buffer);
-			self.gl.DeleteBuffers(1, &self.elements);
-			self.gl.DeleteVertexArrays(1, &self.vertex_array);
+		if let Some((_, texture)) = self.cursor.take() {
+			self.backend.delete_texture(texture);
+		}
+		for (_, texture, _) in self.gradients.drain(..) {
+			self.backend.delete_texture(texture);
 		}
+		self.backend.delete_texture(self.white);
 	}
 }
 
@@ -188,32 +521,227 @@ pub struct Graphics {
 }
 
 impl Graphics {
-	pub(crate) fn new(window: RcWindow) -> Graphics {
+	pub(crate) fn new(window: RcWindow, config: &Config) -> Graphics {
+		let backend = GlBackend::new(window, config);
+		match config.renderer {
+			Renderer::Gl => Graphics::with_backend(Box::new(backend)),
+			Renderer::Software => Graphics::with_backend(Box::new(SoftwareWindowBackend::new(backend)))
+		}
+	}
+
+	/// Creates graphics that draw with a custom backend instead of a window.
+	pub fn with_backend(backend: Box<dyn RenderBackend>) -> Graphics {
 		Graphics {
-			rc: Rc::new(RefCell::new(GraphicsImpl::new(window)))
+			rc: Rc::new(RefCell::new(GraphicsImpl::new(backend)))
 		}
 	}
 
-//	fn clone_rc(&self) -> RcGraphics {
-//		Rc::clone(&self.rc)
-//	}
+	/// Creates graphics that draw with a
+	/// [`SoftwareBackend`](struct.SoftwareBackend.html) of the provided size in pixels.
+	/// Use [`read_pixels`](#method.read_pixels) to get the result.
+	pub fn software(width: u32, height: u32) -> Graphics {
+		Graphics::with_backend(Box::new(SoftwareBackend::new(width, height)))
+	}
+
+	fn clone_rc(&self) -> RcGraphics {
+		Rc::clone(&self.rc)
+	}
 
-	pub(crate) fn update(&self) -> Result<(), WindowError> {
+	pub(crate) fn update(&self, input: &Input) -> Result<(), WindowError> {
 		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Window::update");
+
+		// The letterbox and cursor are drawn in target coordinates.
+		let viewport = graphics.viewport.take();
+		if let Some(viewport) = viewport {
+			graphics.draw_letterbox(viewport);
+		}
+		if let Some((ref image, hotspot)) = input.cursor_image {
+			if !input.cursor_hidden {
+				graphics.draw_cursor(image, hotspot, input.get_cursor_point());
+			}
+		}
+
 		graphics.flush();
-		graphics.window.borrow().window.swap_buffers()
-			.map_err(|error| WindowError::InternalError(
-				ToString::to_string(&error),
+		graphics.clean_gradients();
+		graphics.backend.present()?;
+		graphics.update_viewport();
+		match graphics.take_error() {
+			Some(error) => Err(WindowError::GraphicsError(error)),
+			None => Ok(())
+		}
+	}
+
+	/// Gets the size of the target that is drawn to.
+	pub fn get_size(&self) -> Size {
+		self.rc.borrow().backend.get_size()
+	}
+
+	/// Draws at a virtual size that is scaled to fit the target,
+	/// such as a low resolution for pixel art.
+	/// Everything is drawn in virtual coordinates and clipped to the
+	/// [`viewport`](#method.get_viewport), and the rest of the target is
+	/// filled with the [letterbox color](#method.set_letterbox_color)
+	/// at the end of each frame.
+	///
+	/// Use [`Scaling::Integer`](enum.Scaling.html#variant.Integer) with textures using
+	/// [`TextureFilter::Nearest`](enum.TextureFilter.html#variant.Nearest)
+	/// to keep pixels crisp.
+	///
+	/// The geometry is scaled up rather than drawn to a texture of the virtual size,
+	/// so shapes and sprites that are rotated, scaled or at fractional positions
+	/// are drawn at the resolution of the target and don't line up with virtual pixels.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidSize`](enum.GraphicsError.html#variant.InvalidSize)
+	/// if the width or height is not positive and finite.
+	pub fn set_virtual_size(&mut self, size: Size, scaling: Scaling) -> Result<(), GraphicsError> {
+		let valid = |length: f64| length > 0.0 && length.is_finite();
+		if !valid(size.width) || !valid(size.height) {
+			return Err(GraphicsError::InvalidSize(
+				format!("The virtual size {}x{} is not positive", size.width, size.height),
 				Backtrace::new()
-			))?;
+			));
+		}
+
+		let mut graphics = self.rc.borrow_mut();
+		graphics.virtual_size = Some((size, scaling));
+		graphics.update_viewport();
 		Ok(())
 	}
 
+	/// Stops using a virtual size, so everything is drawn in target pixels again.
+	pub fn clear_virtual_size(&mut self) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.virtual_size = None;
+		graphics.viewport = None;
+	}
+
+	/// Gets the size that is drawn in, which is the virtual size if there is one
+	/// or the size of the target otherwise.
+	pub fn get_virtual_size(&self) -> Size {
+		let graphics = self.rc.borrow();
+		graphics.virtual_size.map_or_else(|| graphics.backend.get_size(), |(size, _)| size)
+	}
+
+	/// Gets the area of the target that the virtual size is scaled to.
+	pub fn get_viewport(&self) -> Rect {
+		let graphics = self.rc.borrow();
+		graphics.viewport.unwrap_or_else(|| Rect::from(graphics.backend.get_size()))
+	}
+
+	/// Converts a point on the target, such as the cursor, to virtual coordinates.
+	pub fn to_virtual(&self, point: Point) -> Point {
+		let graphics = self.rc.borrow();
+		match (graphics.virtual_size, graphics.viewport) {
+			(Some((size, _)), Some(viewport)) => Point {
+				x: (point.x - viewport.origin.x) * size.width / viewport.size.width,
+				y: (point.y - viewport.origin.y) * size.height / viewport.size.height
+			},
+			_ => point
+		}
+	}
+
+	/// Sets the color that fills the target outside of the virtual size.
+	/// Defaults to black.
+	pub fn set_letterbox_color(&mut self, color: Color) {
+		self.rc.borrow_mut().letterbox = color;
+	}
+
+	/// Uploads an image so it can be drawn.
+	/// The texture uses [`TextureFilter::Nearest`](enum.TextureFilter.html#variant.Nearest).
+	pub fn create_texture(&mut self, image: &Image) -> Texture {
+		self.create_texture_with_filter(image, TextureFilter::Nearest)
+	}
+
+	/// Uploads an image so it can be drawn, using the filter when scaled.
+	pub fn create_texture_with_filter(&mut self, image: &Image, filter: TextureFilter) -> Texture {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::create_texture");
+		let id = graphics.backend.create_texture_with_filter(image, filter);
+		Texture::new(Rc::downgrade(&self.clone_rc()), id, image.get_size())
+	}
+
+	/// Packs the images of the builder into as few textures as possible.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidImage`](enum.GraphicsError.html#variant.InvalidImage)
+	/// if an image is larger than the maximum size of the textures.
+	pub fn create_atlas(&mut self, builder: &AtlasBuilder) -> Result<Atlas, GraphicsError> {
+		builder.build(self)
+	}
+
+	/// Loads an AngelCode BMFont file in the text or binary format,
+	/// along with its pages which are found relative to the file.
+	/// The pages use the filter when scaled.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidFont`](enum.GraphicsError.html#variant.InvalidFont)
+	/// if the file could not be read or parsed, or a page is outside of the directory of the file,
+	/// or [`GraphicsError::InvalidImage`](enum.GraphicsError.html#variant.InvalidImage)
+	/// if a page could not be loaded.
+	pub fn load_font<P: AsRef<FilePath>>(&mut self, path: P, filter: TextureFilter) -> Result<Font, GraphicsError> {
+		let path = path.as_ref();
+		let data = fs::read(path)
+			.map_err(|error| GraphicsError::InvalidFont(
+				format!("{}: {}", path.display(), error),
+				Backtrace::new()
+			))?;
+		let font = font::parse(&data)?;
+		let directory = path.parent().unwrap_or(FilePath::new(""));
+		let outside = font.pages.iter().find(|page| !FilePath::new(page).components().all(|component| match component {
+			Component::Normal(_) | Component::CurDir => true,
+			_ => false
+		}));
+		if let Some(page) = outside {
+			return Err(GraphicsError::InvalidFont(
+				format!("{}: The page {} is outside of the directory of the font", path.display(), page),
+				Backtrace::new()
+			));
+		}
+		let pages = font.pages.iter()
+			.map(|page| Image::from_file(directory.join(page)))
+			.collect::<Result<Vec<_>, _>>()?;
+		self.create_font_from_data(font, &pages, filter)
+	}
+
+	/// Creates a font from an AngelCode BMFont file in memory
+	/// and its pages, in the same order as the file lists them.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidFont`](enum.GraphicsError.html#variant.InvalidFont)
+	/// if the data could not be parsed or does not match the pages.
+	pub fn create_font(&mut self, data: &[u8], pages: &[Image], filter: TextureFilter) -> Result<Font, GraphicsError> {
+		let font = font::parse(data)?;
+		self.create_font_from_data(font, pages, filter)
+	}
+
+	fn create_font_from_data(&mut self, font: font::FontData, pages: &[Image], filter: TextureFilter) -> Result<Font, GraphicsError> {
+		let pages = pages.iter()
+			.map(|page| self.create_texture_with_filter(page, filter))
+			.collect();
+		Font::new(font, pages)
+	}
+
+	pub(crate) fn owns_texture(&self, texture: &Texture) -> bool {
+		texture.is_from(&self.rc)
+	}
+
+	pub(crate) fn update_texture(&mut self, texture: &Texture, x: u32, y: u32, image: &Image) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::update_texture");
+		graphics.update_texture(texture.get_id(), x, y, image);
+	}
+
+	/// Reads the pixels that have been drawn, from the top-left.
+	pub fn read_pixels(&mut self) -> Image {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::read_pixels");
+		graphics.flush();
+		graphics.backend.read_pixels()
+	}
+
 	/// Pushes the current rendering state.
 	/// Call [`pop`](#method.pop) to undo any changes since the last push.
 	pub fn push(&mut self) {
 		let mut graphics = self.rc.borrow_mut();
-		let state = *graphics.state.last().unwrap();
+		let state = graphics.state.last().unwrap().clone();
 		graphics.state.push(state);
 	}
 
@@ -242,6 +770,91 @@ impl Graphics {
 		self.rc.borrow_mut().state.last_mut().unwrap().tint(color);
 	}
 
+	/// Fills rectangles, shapes and paths with the gradient instead of a flat color.
+	/// The gradient is multiplied with the current color.
+	///
+	/// The texture of the gradient is kept for as long as the same `Rc`
+	/// is drawn with every frame, so keep it instead of creating one each frame.
+	pub fn gradient(&mut self, gradient: &Rc<Gradient>) {
+		let gradient = Some(Rc::clone(gradient));
+		self.rc.borrow_mut().state.last_mut().unwrap().gradient(gradient);
+	}
+
+	/// Fills with the current color again after [`gradient`](#method.gradient).
+	pub fn clear_gradient(&mut self) {
+		self.rc.borrow_mut().state.last_mut().unwrap().gradient(None);
+	}
+
+	/// Sets how the next drawn colors are combined with the colors already drawn.
+	pub fn blend(&mut self, blend: BlendMode) {
+		self.rc.borrow_mut().state.last_mut().unwrap().blend(blend);
+	}
+
+	/// Clips to the viewport of the camera and transforms
+	/// so the camera position is drawn at the center of the viewport.
+	/// The viewport uses the current transform, so call [`push`](#method.push) first
+	/// and [`pop`](#method.pop) when done with the camera.
+	pub fn camera(&mut self, camera: &Camera) {
+		let mut graphics = self.rc.borrow_mut();
+		let state = graphics.state.last_mut().unwrap();
+		state.clip(camera.viewport);
+		state.translate(camera.viewport.center());
+		state.rotate(-camera.rotation);
+		state.scale(Size {
+			width: camera.zoom,
+			height: camera.zoom
+		});
+		state.translate(-camera.position);
+	}
+
+	/// Limits drawing to inside the rectangle, intersected with the current clip.
+	/// The rectangle uses the current transform, but the clip is always
+	/// the axis-aligned bounds in whole pixels.
+	/// Call [`pop`](#method.pop) to restore the previous clip.
+	pub fn clip(&mut self, rect: Rect) {
+		self.rc.borrow_mut().state.last_mut().unwrap().clip(rect);
+	}
+
+	/// Removes the clip so the whole target can be drawn to.
+	pub fn reset_clip(&mut self) {
+		self.rc.borrow_mut().state.last_mut().unwrap().reset_clip();
+	}
+
+	/// Clears the mask and starts adding to it.
+	/// Everything drawn until [`end_mask`](#method.end_mask) defines the visible area
+	/// instead of being drawn.
+	pub fn begin_mask(&mut self) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::begin_mask");
+		graphics.flush();
+		graphics.backend.clear_mask();
+		graphics.state.last_mut().unwrap().mask(MaskMode::Write);
+	}
+
+	/// Stops adding to the mask and only draws inside it.
+	pub fn end_mask(&mut self) {
+		self.mask(MaskMode::Inside);
+	}
+
+	/// Sets how drawing interacts with the mask.
+	/// Use [`MaskMode::Outside`](enum.MaskMode.html#variant.Outside) to cut
+	/// the mask out instead, or [`MaskMode::Disabled`](enum.MaskMode.html#variant.Disabled)
+	/// to stop masking. The mask is kept until the next
+	/// [`begin_mask`](#method.begin_mask).
+	pub fn mask(&mut self, mask: MaskMode) {
+		self.rc.borrow_mut().state.last_mut().unwrap().mask(mask);
+	}
+
+	/// Sets how lines and outlines are stroked.
+	pub fn stroke(&mut self, stroke: Stroke) {
+		self.rc.borrow_mut().state.last_mut().unwrap().stroke(stroke);
+	}
+
+	/// Sets the width of lines and outlines.
+	pub fn line_width(&mut self, width: f64) {
+		self.rc.borrow_mut().state.last_mut().unwrap().line_width(width);
+	}
+
 	/// Resets the transform to identity.
 	pub fn identity(&mut self) {
 		self.rc.borrow_mut().state.last_mut().unwrap().identity();
@@ -267,19 +880,266 @@ impl Graphics {
 		self.rc.borrow_mut().state.last_mut().unwrap().transform(point)
 	}
 
+	/// Draws a rectangle with the current color and transform.
+	pub fn rect(&mut self, rect: Rect) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::rect");
+		graphics.fill_convex(&[rect.top_left(), rect.top_right(), rect.bottom_right(), rect.bottom_left()]);
+	}
+
+	/// Draws a line between two points with the current stroke.
+	pub fn line(&mut self, a: Point, b: Point) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::line");
+		graphics.stroke(&[a, b], false);
+	}
+
+	/// Draws a line through all the points with the current stroke.
+	pub fn polyline(&mut self, points: &[Point]) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::polyline");
+		graphics.stroke(points, false);
+	}
+
+	/// Fills a polygon, which can be concave but should not intersect itself.
+	pub fn polygon(&mut self, points: &[Point]) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::polygon");
+		let mut mesh = Mesh::new();
+		shape::fill(&mut mesh, points);
+		graphics.mesh(&mesh);
+	}
+
+	/// Draws the outline of a polygon with the current stroke.
+	pub fn stroke_polygon(&mut self, points: &[Point]) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::stroke_polygon");
+		graphics.stroke(points, true);
+	}
+
+	/// Draws the outline of a rectangle with the current stroke.
+	pub fn stroke_rect(&mut self, rect: Rect) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::stroke_rect");
+		graphics.stroke(&[rect.top_left(), rect.top_right(), rect.bottom_right(), rect.bottom_left()], true);
+	}
+
+	/// Fills a circle.
+	pub fn circle(&mut self, center: Point, radius: f64) {
+		self.ellipse(center, Size {width: radius, height: radius});
+	}
+
+	/// Draws the outline of a circle with the current stroke.
+	pub fn stroke_circle(&mut self, center: Point, radius: f64) {
+		self.stroke_ellipse(center, Size {width: radius, height: radius});
+	}
+
+	/// Fills an ellipse with the horizontal and vertical radius.
+	pub fn ellipse(&mut self, center: Point, radius: Size) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::ellipse");
+		let points = shape::ellipse(center, radius, graphics.tolerance());
+		graphics.fill_convex(&points);
+	}
+
+	/// Draws the outline of an ellipse with the current stroke.
+	pub fn stroke_ellipse(&mut self, center: Point, radius: Size) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::stroke_ellipse");
+		let points = shape::ellipse(center, radius, graphics.tolerance());
+		graphics.stroke(&points, true);
+	}
+
+	/// Fills the area between an arc of a circle and the line joining its ends.
+	/// The arc starts at the angle and sweeps clockwise,
+	/// with an angle of 0 pointing along the positive x-axis.
+	pub fn arc(&mut self, center: Point, radius: f64, start: Angle, sweep: Angle) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::arc");
+		let radius = Size {width: radius, height: radius};
+		let points = shape::arc(center, radius, start.as_radians(), sweep.as_radians(), graphics.tolerance());
+		graphics.fill_convex(&points);
+	}
+
+	/// Draws an arc of a circle with the current stroke.
+	/// See [`arc`](#method.arc) for the angles.
+	pub fn stroke_arc(&mut self, center: Point, radius: f64, start: Angle, sweep: Angle) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::stroke_arc");
+		let radius = Size {width: radius, height: radius};
+		let points = shape::arc(center, radius, start.as_radians(), sweep.as_radians(), graphics.tolerance());
+		graphics.stroke(&points, false);
+	}
+
+	/// Fills a slice of a circle.
+	/// See [`arc`](#method.arc) for the angles.
+	pub fn pie(&mut self, center: Point, radius: f64, start: Angle, sweep: Angle) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::pie");
+		let mut mesh = Mesh::new();
+		let radius = Size {width: radius, height: radius};
+		let tolerance = graphics.tolerance();
+		mesh.arc_fan(center, radius, start.as_radians(), sweep.as_radians(), tolerance);
+		graphics.mesh(&mesh);
+	}
+
+	/// Draws the outline of a slice of a circle with the current stroke.
+	/// See [`arc`](#method.arc) for the angles.
+	pub fn stroke_pie(&mut self, center: Point, radius: f64, start: Angle, sweep: Angle) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::stroke_pie");
+		let radius = Size {width: radius, height: radius};
+		let mut points = vec![center];
+		points.extend(shape::arc(center, radius, start.as_radians(), sweep.as_radians(), graphics.tolerance()));
+		graphics.stroke(&points, true);
+	}
+
+	/// Fills a rectangle with corners rounded by the radius.
+	pub fn rounded_rect(&mut self, rect: Rect, radius: f64) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::rounded_rect");
+		let points = shape::rounded_rect(rect, radius, graphics.tolerance());
+		graphics.fill_convex(&points);
+	}
+
+	/// Draws the outline of a rectangle with corners rounded by the radius
+	/// with the current stroke.
+	pub fn stroke_rounded_rect(&mut self, rect: Rect, radius: f64) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::stroke_rounded_rect");
+		let points = shape::rounded_rect(rect, radius, graphics.tolerance());
+		graphics.stroke(&points, true);
+	}
+
+	/// Fills a path using the fill rule.
+	pub fn fill_path(&mut self, path: &Path, rule: FillRule) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::fill_path");
+		let mut mesh = Mesh::new();
+		path.fill(&mut mesh, rule, graphics.tolerance());
+		graphics.mesh(&mesh);
+	}
+
+	/// Draws a path with the current stroke.
+	pub fn stroke_path(&mut self, path: &Path) {
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::stroke_path");
+		let mut mesh = Mesh::new();
+		let stroke = graphics.state.last().unwrap().get_stroke();
+		path.stroke(&mut mesh, &stroke, graphics.tolerance());
+		graphics.mesh(&mesh);
+	}
+
+	/// Draws a texture at its size with the top-left at the point.
+	/// The texture is multiplied with the current color and
+	/// uses the current transform.
+	pub fn draw(&mut self, texture: &Texture, point: Point) {
+		self.draw_region(texture, Rect::from(texture.get_size()), Rect {
+			origin: point,
+			size: texture.get_size()
+		});
+	}
+
+	/// Draws the source region of a texture, in pixels,
+	/// stretched to cover the destination rectangle.
+	///
+	/// Textures can only be drawn by the graphics that created them.
+	/// Other textures are not drawn, and
+	/// [`GraphicsError::ForeignTexture`](enum.GraphicsError.html#variant.ForeignTexture)
+	/// is returned by the next [`Window.update`](../core/struct.Window.html#method.update).
+	pub fn draw_region(&mut self, texture: &Texture, source: Rect, dest: Rect) {
+		let owned = self.owns_texture(texture);
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::draw_region");
+		if !owned {
+			graphics.report(GraphicsError::ForeignTexture(Backtrace::new()));
+			return;
+		}
+		graphics.bind(texture.get_id());
+		let size = texture.get_size();
+		graphics.rect(dest, Rect {
+			origin: Point {
+				x: source.origin.x / size.width,
+				y: source.origin.y / size.height
+			},
+			size: source.size / size
+		});
+	}
+
+	/// Draws a sprite at its size with the top-left at the point.
+	/// The sprite is multiplied with the current color and
+	/// uses the current transform.
+	pub fn draw_sprite(&mut self, sprite: &Sprite, point: Point) {
+		self.draw_region(sprite.get_texture(), sprite.get_region(), Rect {
+			origin: point,
+			size: sprite.get_size()
+		});
+	}
+
+	/// Draws the source region of a sprite, in pixels relative to the sprite,
+	/// stretched to cover the destination rectangle.
+	pub fn draw_sprite_region(&mut self, sprite: &Sprite, source: Rect, dest: Rect) {
+		self.draw_region(sprite.get_texture(), source.translate(sprite.get_region().origin), dest);
+	}
+
+	/// Draws text with the top-left of the first line at the point.
+	/// Each `\n` starts a new line, and characters the font does not have
+	/// are drawn with its fallback character if it has one.
+	/// TrueType fonts rasterize characters the first time they are drawn at a size.
+	/// The text is multiplied with the current color and
+	/// uses the current transform.
+	pub fn print(&mut self, font: &Font, text: &str, point: Point) {
+		let layout = TextLayout::new(font, text, LayoutOptions::default());
+		font.prepare(self, text);
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::print");
+		graphics.draw_layout(&layout, point);
+	}
+
+	/// Draws text that has been laid out, with the top-left of the layout at the point.
+	/// The text is multiplied with the current color and
+	/// uses the current transform.
+	pub fn draw_layout(&mut self, layout: &TextLayout, point: Point) {
+		layout.get_font().prepare(self, layout.get_text());
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::draw_layout");
+		graphics.draw_layout(layout, point);
+	}
+
 	/// Clears the screen with the current color.
+	/// If [`Config.transparent`](../core/struct.Config.html#structfield.transparent)
+	/// is enabled, the alpha of the color sets the transparency of the window.
 	pub fn clear(&self) {
-		unsafe {
-			let graphics = self.rc.borrow();
-			let color = graphics.state.last().unwrap().get_color();
-			graphics.gl.ClearColor(
-				color.red   as f32,
-				color.green as f32,
-				color.blue  as f32,
-				color.alpha as f32
-			);
-			graphics.gl.Clear(gl::COLOR_BUFFER_BIT);
-			graphics.gl.check_error();
+		let mut graphics = self.rc.borrow_mut();
+		graphics.call("Graphics::clear");
+		graphics.flush();
+		let color = graphics.state.last().unwrap().get_color();
+		graphics.backend.clear(color);
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn textures_from_other_graphics_are_not_drawn() {
+		let image = Image::from_rgba(vec![255; 8], 2, 1).unwrap();
+		let mut graphics = Graphics::software(2, 1);
+		let mut other = Graphics::software(2, 1);
+		let foreign = other.create_texture(&image);
+
+		graphics.draw(&foreign, Point {x: 0.0, y: 0.0});
+		assert!(graphics.read_pixels().get_data().iter().all(|&value| value == 0));
+		match graphics.rc.borrow_mut().take_error() {
+			Some(GraphicsError::ForeignTexture(..)) => (),
+			error => panic!("expected a foreign texture error, found {:?}", error)
 		}
+
+		let texture = graphics.create_texture(&image);
+		graphics.draw(&texture, Point {x: 0.0, y: 0.0});
+		assert!(graphics.read_pixels().get_data().iter().all(|&value| value == 255));
+		assert!(graphics.rc.borrow_mut().take_error().is_none());
 	}
 }
\ No newline at end of file
@@ -0,0 +1,446 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate glutin;
+use self::glutin::GlContext;
+
+extern crate backtrace;
+use self::backtrace::Backtrace;
+
+use super::gl::{self, Gl, types::*};
+use super::debug::Debug;
+use super::program::Program;
+use super::backend::{RenderBackend, DrawState, MaskMode, Vertex, TextureFilter};
+use super::{Color, Image, GraphicsError, BlendMode};
+
+use ::{Size, Rect};
+use ::core::{self, RcWindow, WindowError, Config, GlErrors, GlVersion};
+
+use std::ffi::CStr;
+use std::mem;
+use std::ptr;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GlApi {
+	Gl,
+	Gles2,
+	Gles3
+}
+
+/// Draws with OpenGL to the window.
+pub struct GlBackend {
+	window: RcWindow,
+	gl: Gl,
+	api: GlApi,
+	debug: Box<Debug>,
+	errors: GlErrors,
+	error: Option<GraphicsError>,
+	transparent: bool,
+	program: Program,
+	vertex_array: GLuint,
+	elements: GLuint,
+	buffer: GLuint,
+	blend: Option<BlendMode>,
+	clip: Option<Option<Rect>>,
+	mask: Option<MaskMode>,
+	size: Size,
+	scale: f64
+}
+
+impl GlBackend {
+	pub fn new(window: RcWindow, config: &Config) -> GlBackend {
+		unsafe {
+			let gl = {
+				let context = &window.borrow().window;
+				Gl::load_with(|name| context.get_proc_address(name) as *const _)
+			};
+
+			let debug = Debug::new();
+			if config.gl_debug && !debug.install(&gl) {
+				core::log("KHR_debug is not available, GL debug messages are disabled");
+			}
+
+			let renderer = gl.GetString(gl::RENDERER);
+			if !renderer.is_null() {
+				core::set_renderer(CStr::from_ptr(renderer as *const _).to_string_lossy().into_owned());
+			}
+
+			let api = match config.gl_version {
+				GlVersion::Gl(..) => GlApi::Gl,
+				GlVersion::Gles(2, _) => GlApi::Gles2,
+				GlVersion::Gles(..) => GlApi::Gles3
+			};
+
+			let program = Program::new(&gl, api);
+			gl.UseProgram(program.program);
+			gl.Uniform1i(program.image, 0);
+			gl.ActiveTexture(gl::TEXTURE0);
+
+			// OpenGL ES 2.0 has no vertex arrays but doesn't require one either.
+			let mut vertex_array = 0;
+			if api != GlApi::Gles2 {
+				gl.GenVertexArrays(1, &mut vertex_array);
+				gl.BindVertexArray(vertex_array);
+			}
+
+			let mut elements = 0;
+			gl.GenBuffers(1, &mut elements);
+			gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, elements);
+
+			let mut buffer = 0;
+			gl.GenBuffers(1, &mut buffer);
+			gl.BindBuffer(gl::ARRAY_BUFFER, buffer);
+
+			gl.EnableVertexAttribArray(0);
+			gl.EnableVertexAttribArray(1);
+			gl.EnableVertexAttribArray(2);
+
+			let stride = mem::size_of::<Vertex>() as GLsizei;
+			gl.VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, offset_of!(Vertex, point) as *const _);
+			gl.VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, stride, offset_of!(Vertex, texcoord) as *const _);
+			gl.VertexAttribPointer(2, 4, gl::UNSIGNED_BYTE, gl::TRUE, stride, offset_of!(Vertex, color) as *const _);
+
+			let mut backend = GlBackend {
+				window,
+				gl,
+				api,
+				debug,
+				errors: config.gl_errors,
+				error: None,
+				transparent: config.transparent,
+				program,
+				vertex_array,
+				elements,
+				buffer,
+				blend: None,
+				clip: None,
+				mask: None,
+				size: Size::default(),
+				scale: 1.0
+			};
+			backend.resize();
+			backend.check_error();
+			backend
+		}
+	}
+
+	fn check_error(&mut self) {
+		if self.errors == GlErrors::Ignore {
+			return;
+		}
+
+		let error = unsafe { self.gl.GetError() };
+		if error != gl::NO_ERROR {
+			let error = GraphicsError::GlError(
+				gl::error_name(error),
+				self.debug.get_call(),
+				Backtrace::new()
+			);
+			match self.errors {
+				GlErrors::Panic => panic!("{}", error),
+				_ => if self.error.is_none() {
+					self.error = Some(error);
+				}
+			}
+		}
+	}
+
+	fn set_blend(&mut self, blend: BlendMode) {
+		if self.blend == Some(blend) {
+			return;
+		}
+		self.blend = Some(blend);
+
+		// The color and alpha factors, and whether or not the shader premultiplies.
+		let (source, target, source_alpha, target_alpha, premultiply) = match blend {
+			BlendMode::Alpha => (gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE_MINUS_SRC_ALPHA, false),
+			BlendMode::Premultiplied => (gl::ONE, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE_MINUS_SRC_ALPHA, false),
+			BlendMode::Additive => (gl::SRC_ALPHA, gl::ONE, gl::ZERO, gl::ONE, false),
+			BlendMode::Multiply => (gl::DST_COLOR, gl::ONE_MINUS_SRC_ALPHA, gl::ZERO, gl::ONE, true),
+			BlendMode::Screen => (gl::ONE, gl::ONE_MINUS_SRC_COLOR, gl::ZERO, gl::ONE, true),
+			BlendMode::Replace => (gl::ONE, gl::ZERO, gl::ONE, gl::ZERO, false)
+		};
+		unsafe {
+			// Replacing doesn't need blending at all.
+			if blend == BlendMode::Replace {
+				self.gl.Disable(gl::BLEND);
+			} else {
+				self.gl.Enable(gl::BLEND);
+				self.gl.BlendFuncSeparate(source, target, source_alpha, target_alpha);
+			}
+			self.gl.Uniform1f(self.program.premultiply, if premultiply {1.0} else {0.0});
+		}
+	}
+
+	fn set_clip(&mut self, clip: Option<Rect>) {
+		if self.clip == Some(clip) {
+			return;
+		}
+		self.clip = Some(clip);
+
+		unsafe {
+			match clip {
+				Some(clip) => {
+					// OpenGL uses physical pixels from the bottom-left.
+					let height = self.size.height * self.scale;
+					self.gl.Enable(gl::SCISSOR_TEST);
+					self.gl.Scissor(
+						(clip.left() * self.scale).round() as GLint,
+						(height - clip.bottom() * self.scale).round() as GLint,
+						(clip.size.width * self.scale).round() as GLsizei,
+						(clip.size.height * self.scale).round() as GLsizei
+					);
+				},
+				None => self.gl.Disable(gl::SCISSOR_TEST)
+			}
+		}
+	}
+
+	fn set_mask(&mut self, mask: MaskMode) {
+		if self.mask == Some(mask) {
+			return;
+		}
+		self.mask = Some(mask);
+
+		unsafe {
+			if mask == MaskMode::Disabled {
+				self.gl.Disable(gl::STENCIL_TEST);
+			} else {
+				self.gl.Enable(gl::STENCIL_TEST);
+			}
+
+			let write = mask == MaskMode::Write;
+			let color = if write {gl::FALSE} else {gl::TRUE};
+			self.gl.ColorMask(color, color, color, color);
+			self.gl.StencilOp(gl::KEEP, gl::KEEP, if write {gl::REPLACE} else {gl::KEEP});
+			self.gl.StencilFunc(match mask {
+				MaskMode::Inside => gl::EQUAL,
+				MaskMode::Outside => gl::NOTEQUAL,
+				_ => gl::ALWAYS
+			}, 1, 0xFF);
+		}
+	}
+
+	// Clearing is affected by the scissor and color mask, so they are disabled first.
+	fn prepare_clear(&mut self) {
+		self.set_clip(None);
+		self.set_mask(MaskMode::Disabled);
+	}
+
+	fn resize(&mut self) {
+		let (size, scale) = {
+			let window = &self.window.borrow().window;
+			(window.get_inner_size(), window.get_hidpi_factor())
+		};
+
+		if let Some(size) = size {
+			self.size = Size {
+				width: size.width,
+				height: size.height
+			};
+			self.scale = scale;
+			unsafe {
+				self.gl.Viewport(0, 0, (size.width * scale) as GLsizei, (size.height * scale) as GLsizei);
+				let projection = [
+					(2.0 / size.width) as f32, 0.0, 0.0,
+					0.0, (-2.0 / size.height) as f32, 0.0,
+					-1.0, 1.0, 1.0
+				];
+				self.gl.UniformMatrix3fv(self.program.projection, 1, gl::FALSE, projection.as_ptr());
+			}
+			// The scissor depends on the size.
+			self.clip = None;
+		}
+	}
+}
+
+impl RenderBackend for GlBackend {
+	fn get_size(&self) -> Size {
+		self.size
+	}
+
+	fn create_texture(&mut self, image: &Image) -> u32 {
+		self.create_texture_with_filter(image, TextureFilter::Nearest)
+	}
+
+	fn create_texture_with_filter(&mut self, image: &Image, filter: TextureFilter) -> u32 {
+		let filter = match filter {
+			TextureFilter::Nearest => gl::NEAREST,
+			TextureFilter::Linear => gl::LINEAR
+		};
+
+		unsafe {
+			let mut texture = 0;
+			self.gl.GenTextures(1, &mut texture);
+			self.gl.BindTexture(gl::TEXTURE_2D, texture);
+			self.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as GLint);
+			self.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as GLint);
+			self.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+			self.gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+			self.gl.TexImage2D(
+				gl::TEXTURE_2D,
+				0,
+				if self.api == GlApi::Gles2 {gl::RGBA} else {gl::RGBA8} as GLint,
+				image.get_width() as GLsizei,
+				image.get_height() as GLsizei,
+				0,
+				gl::RGBA,
+				gl::UNSIGNED_BYTE,
+				image.get_data().as_ptr() as *const _
+			);
+			self.check_error();
+			texture
+		}
+	}
+
+	fn update_texture(&mut self, texture: u32, x: u32, y: u32, image: &Image) {
+		unsafe {
+			self.gl.BindTexture(gl::TEXTURE_2D, texture);
+			self.gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+			self.gl.TexSubImage2D(
+				gl::TEXTURE_2D,
+				0,
+				x as GLint,
+				y as GLint,
+				image.get_width() as GLsizei,
+				image.get_height() as GLsizei,
+				gl::RGBA,
+				gl::UNSIGNED_BYTE,
+				image.get_data().as_ptr() as *const _
+			);
+		}
+		self.check_error();
+	}
+
+	fn delete_texture(&mut self, texture: u32) {
+		unsafe {
+			self.gl.DeleteTextures(1, &texture);
+		}
+	}
+
+	fn clear(&mut self, mut color: Color) {
+		if self.transparent {
+			// Compositors expect premultiplied alpha.
+			color = color.premultiply();
+		}
+
+		self.prepare_clear();
+		unsafe {
+			self.gl.ClearColor(
+				color.red   as f32,
+				color.green as f32,
+				color.blue  as f32,
+				color.alpha as f32
+			);
+			self.gl.Clear(gl::COLOR_BUFFER_BIT);
+		}
+		self.check_error();
+	}
+
+	fn clear_mask(&mut self) {
+		self.prepare_clear();
+		unsafe {
+			self.gl.ClearStencil(0);
+			self.gl.Clear(gl::STENCIL_BUFFER_BIT);
+		}
+		self.check_error();
+	}
+
+	fn draw(&mut self, state: &DrawState, vertices: &[Vertex], elements: &[u16]) {
+		self.set_blend(state.blend);
+		self.set_clip(state.clip);
+		self.set_mask(state.mask);
+		unsafe {
+			self.gl.BindTexture(gl::TEXTURE_2D, state.texture);
+			self.gl.BufferData(
+				gl::ARRAY_BUFFER,
+				(vertices.len() * mem::size_of::<Vertex>()) as GLsizeiptr,
+				vertices.as_ptr() as *const _,
+				gl::STREAM_DRAW
+			);
+			self.gl.BufferData(
+				gl::ELEMENT_ARRAY_BUFFER,
+				(elements.len() * mem::size_of::<u16>()) as GLsizeiptr,
+				elements.as_ptr() as *const _,
+				gl::STREAM_DRAW
+			);
+			self.gl.DrawElements(
+				gl::TRIANGLES,
+				elements.len() as GLsizei,
+				gl::UNSIGNED_SHORT,
+				ptr::null()
+			);
+		}
+		self.check_error();
+	}
+
+	fn read_pixels(&mut self) -> Image {
+		let width = (self.size.width * self.scale) as usize;
+		let height = (self.size.height * self.scale) as usize;
+		let mut data = vec![0u8; width * height * 4];
+		unsafe {
+			self.gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+			self.gl.ReadPixels(
+				0,
+				0,
+				width as GLsizei,
+				height as GLsizei,
+				gl::RGBA,
+				gl::UNSIGNED_BYTE,
+				data.as_mut_ptr() as *mut _
+			);
+		}
+		self.check_error();
+
+		// OpenGL reads from the bottom-left.
+		let stride = width * 4;
+		let mut flipped = Vec::with_capacity(data.len());
+		for row in data.chunks(stride).rev() {
+			flipped.extend_from_slice(row);
+		}
+		Image::from_rgba(flipped, width as u32, height as u32).unwrap()
+	}
+
+	fn present(&mut self) -> Result<(), WindowError> {
+		self.window.borrow().window.swap_buffers()
+			.map_err(|error| WindowError::InternalError(
+				ToString::to_string(&error),
+				Backtrace::new()
+			))?;
+		self.resize();
+		Ok(())
+	}
+
+	fn take_error(&mut self) -> Option<GraphicsError> {
+		self.error.take()
+	}
+
+	fn set_call(&mut self, call: &'static str) {
+		self.debug.call(call);
+	}
+}
+
+impl Drop for GlBackend {
+	fn drop(&mut self) {
+		unsafe {
+			self.debug.uninstall(&self.gl);
+			self.gl.DeleteBuffers(1, &self.buffer);
+			self.gl.DeleteBuffers(1, &self.elements);
+			if self.api != GlApi::Gles2 {
+				self.gl.DeleteVertexArrays(1, &self.vertex_array);
+			}
+			self.program.delete(&self.gl);
+		}
+	}
+}
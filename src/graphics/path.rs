@@ -0,0 +1,633 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate backtrace;
+use self::backtrace::Backtrace;
+
+use ::{Point, Size};
+use super::{Angle, GraphicsError};
+use super::shape::{self, Mesh, Stroke};
+
+use std::f64::consts::PI;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// How the inside of a path is decided where it overlaps itself.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FillRule {
+	/// Areas are filled if the path winds around them a different amount
+	/// clockwise than anticlockwise.
+	NonZero,
+
+	/// Areas are filled if a line from them crosses the path an odd amount of times.
+	EvenOdd
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Command {
+	MoveTo(Point),
+	LineTo(Point),
+	QuadTo(Point, Point),
+	CubicTo(Point, Point, Point),
+	ArcTo(Size, Angle, bool, bool, Point),
+	Close
+}
+
+/// A shape made of lines and curves that can be filled or stroked.
+///
+/// Curves are split into lines when drawn, using the current transform
+/// so they stay smooth when scaled.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Path {
+	commands: Vec<Command>
+}
+
+impl Path {
+	/// Creates an empty path.
+	pub fn new() -> Path {
+		Path::default()
+	}
+
+	/// Starts a new sub-path at the point.
+	pub fn move_to(&mut self, point: Point) -> &mut Path {
+		self.commands.push(Command::MoveTo(point));
+		self
+	}
+
+	/// Adds a line from the current point.
+	pub fn line_to(&mut self, point: Point) -> &mut Path {
+		self.commands.push(Command::LineTo(point));
+		self
+	}
+
+	/// Adds a quadratic Bézier curve from the current point.
+	pub fn quad_to(&mut self, control: Point, point: Point) -> &mut Path {
+		self.commands.push(Command::QuadTo(control, point));
+		self
+	}
+
+	/// Adds a cubic Bézier curve from the current point.
+	pub fn cubic_to(&mut self, control1: Point, control2: Point, point: Point) -> &mut Path {
+		self.commands.push(Command::CubicTo(control1, control2, point));
+		self
+	}
+
+	/// Adds an elliptical arc from the current point, like the SVG `A` command.
+	/// The ellipse has the radius and is rotated by the angle.
+	/// Of the four arcs that fit, `large_arc` picks the one longer than half a turn
+	/// and `clockwise` picks the one that turns clockwise.
+	pub fn arc_to(&mut self, radius: Size, rotation: Angle, large_arc: bool, clockwise: bool, point: Point) -> &mut Path {
+		self.commands.push(Command::ArcTo(radius, rotation, large_arc, clockwise, point));
+		self
+	}
+
+	/// Closes the current sub-path with a line back to its start.
+	pub fn close(&mut self) -> &mut Path {
+		self.commands.push(Command::Close);
+		self
+	}
+
+	/// Parses the path data of an SVG `d` attribute.
+	/// All commands are supported, including relative and shorthand forms.
+	/// # Errors
+	/// Returns [`GraphicsError::InvalidPath`](enum.GraphicsError.html#variant.InvalidPath)
+	/// if the data is not valid or has a number too large to represent.
+	pub fn from_svg(data: &str) -> Result<Path, GraphicsError> {
+		Parser::new(data).parse()
+			.map_err(|index| GraphicsError::InvalidPath(
+				format!("Invalid SVG path data at character {}", index),
+				Backtrace::new()
+			))
+	}
+
+	/// Splits the path into lines.
+	/// Returns every sub-path with whether or not it was closed.
+	pub(crate) fn flatten(&self, tolerance: f64) -> Vec<(Vec<Point>, bool)> {
+		let mut result = Vec::new();
+		let mut points: Vec<Point> = Vec::new();
+		let mut start = Point::default();
+		let mut current = Point::default();
+
+		for command in &self.commands {
+			if points.is_empty() {
+				if let &Command::MoveTo(..) = command {} else {
+					points.push(current);
+				}
+			}
+
+			match *command {
+				Command::MoveTo(point) => {
+					if points.len() > 1 {
+						result.push((points, false));
+					}
+					points = vec![point];
+					start = point;
+					current = point;
+				},
+				Command::LineTo(point) => {
+					points.push(point);
+					current = point;
+				},
+				Command::QuadTo(control, point) => {
+					let deviation = (current - control * 2.0 + point).length();
+					let segments = curve_segments(deviation / 4.0, tolerance);
+					for i in 1..segments + 1 {
+						let t = i as f64 / segments as f64;
+						let u = 1.0 - t;
+						points.push(current * (u * u) + control * (2.0 * u * t) + point * (t * t));
+					}
+					current = point;
+				},
+				Command::CubicTo(control1, control2, point) => {
+					let deviation = (current - control1 * 2.0 + control2).length()
+						.max((control1 - control2 * 2.0 + point).length());
+					let segments = curve_segments(deviation * 0.75, tolerance);
+					for i in 1..segments + 1 {
+						let t = i as f64 / segments as f64;
+						let u = 1.0 - t;
+						points.push(
+							current * (u * u * u) +
+							control1 * (3.0 * u * u * t) +
+							control2 * (3.0 * u * t * t) +
+							point * (t * t * t)
+						);
+					}
+					current = point;
+				},
+				Command::ArcTo(radius, rotation, large_arc, clockwise, point) => {
+					arc(&mut points, current, radius, rotation, large_arc, clockwise, point, tolerance);
+					current = point;
+				},
+				Command::Close => {
+					if points.len() > 1 {
+						result.push((points, true));
+					}
+					points = Vec::new();
+					current = start;
+				}
+			}
+		}
+
+		if points.len() > 1 {
+			result.push((points, false));
+		}
+		result
+	}
+
+	pub(crate) fn fill(&self, mesh: &mut Mesh, rule: FillRule, tolerance: f64) {
+		let polygons: Vec<Vec<Point>> = self.flatten(tolerance)
+			.into_iter()
+			.map(|(points, _)| points)
+			.collect();
+		fill(mesh, &polygons, rule);
+	}
+
+	pub(crate) fn stroke(&self, mesh: &mut Mesh, stroke: &Stroke, tolerance: f64) {
+		for (points, closed) in self.flatten(tolerance) {
+			shape::stroke(mesh, stroke, &points, closed, tolerance);
+		}
+	}
+}
+
+// Wang's formula for the amount of lines needed to keep a curve within the tolerance,
+// given the largest second difference of its control points scaled by its degree.
+fn curve_segments(deviation: f64, tolerance: f64) -> usize {
+	((deviation / tolerance).sqrt().ceil() as usize).max(1).min(1024)
+}
+
+// Converts an SVG endpoint arc to a center arc and splits it into lines.
+// See the implementation notes of the SVG specification.
+fn arc(points: &mut Vec<Point>, from: Point, radius: Size, rotation: Angle,
+	large_arc: bool, clockwise: bool, to: Point, tolerance: f64) {
+	let mut rx = radius.width.abs();
+	let mut ry = radius.height.abs();
+	if from == to {
+		return;
+	}
+	if rx == 0.0 || ry == 0.0 {
+		points.push(to);
+		return;
+	}
+
+	let (sin, cos) = (rotation.sin(), rotation.cos());
+	let half = (from - to) / 2.0;
+	let p = Point {
+		x: cos * half.x + sin * half.y,
+		y: -sin * half.x + cos * half.y
+	};
+
+	// Scale up radii that are too small to reach the end point.
+	let lambda = (p.x * p.x) / (rx * rx) + (p.y * p.y) / (ry * ry);
+	if lambda > 1.0 {
+		rx *= lambda.sqrt();
+		ry *= lambda.sqrt();
+	}
+
+	let numerator = rx * rx * ry * ry - rx * rx * p.y * p.y - ry * ry * p.x * p.x;
+	let denominator = rx * rx * p.y * p.y + ry * ry * p.x * p.x;
+	let mut factor = (numerator / denominator).max(0.0).sqrt();
+	if large_arc == clockwise {
+		factor = -factor;
+	}
+	let c = Point {
+		x: factor * rx * p.y / ry,
+		y: -factor * ry * p.x / rx
+	};
+	let center = Point {
+		x: cos * c.x - sin * c.y + (from.x + to.x) / 2.0,
+		y: sin * c.x + cos * c.y + (from.y + to.y) / 2.0
+	};
+
+	let angle = |v: Point| v.y.atan2(v.x);
+	let start = angle(Point {x: (p.x - c.x) / rx, y: (p.y - c.y) / ry});
+	let end = angle(Point {x: (-p.x - c.x) / rx, y: (-p.y - c.y) / ry});
+	let mut sweep = end - start;
+	if clockwise && sweep < 0.0 {
+		sweep += 2.0 * PI;
+	} else if !clockwise && sweep > 0.0 {
+		sweep -= 2.0 * PI;
+	}
+
+	let segments = shape::arc_segments(rx.max(ry), sweep, tolerance);
+	for i in 1..segments {
+		let theta = start + sweep * i as f64 / segments as f64;
+		let (x, y) = (theta.cos() * rx, theta.sin() * ry);
+		points.push(Point {
+			x: cos * x - sin * y + center.x,
+			y: sin * x + cos * y + center.y
+		});
+	}
+	// The last point is exact so following commands line up.
+	points.push(to);
+}
+
+fn intersection_y(a: (Point, Point), b: (Point, Point)) -> Option<f64> {
+	let r = a.1 - a.0;
+	let s = b.1 - b.0;
+	let denominator = r.cross(s);
+	if denominator == 0.0 {
+		return None;
+	}
+	let t = (b.0 - a.0).cross(s) / denominator;
+	let u = (b.0 - a.0).cross(r) / denominator;
+	if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+		Some(a.0.y + r.y * t)
+	} else {
+		None
+	}
+}
+
+/// Fills polygons, which can overlap and intersect themselves, using the fill rule.
+/// Edges to or from points that aren't finite are left out.
+///
+/// The area is split into horizontal bands wherever an edge starts, ends or crosses
+/// another edge. No edges cross inside a band, so the filled spans of each band
+/// are trapezoids.
+pub fn fill(mesh: &mut Mesh, polygons: &[Vec<Point>], rule: FillRule) {
+	let mut edges = Vec::new();
+	for polygon in polygons {
+		for i in 0..polygon.len() {
+			let a = polygon[i];
+			let b = polygon[(i + 1) % polygon.len()];
+			let finite = a.x.is_finite() && a.y.is_finite() && b.x.is_finite() && b.y.is_finite();
+			if finite && a.y != b.y {
+				edges.push((a, b));
+			}
+		}
+	}
+
+	let mut bands: Vec<f64> = edges.iter().flat_map(|edge| vec![edge.0.y, edge.1.y]).collect();
+	for i in 0..edges.len() {
+		for j in i + 1..edges.len() {
+			if let Some(y) = intersection_y(edges[i], edges[j]) {
+				bands.push(y);
+			}
+		}
+	}
+	bands.retain(|y| y.is_finite());
+	bands.sort_by(f64::total_cmp);
+	bands.dedup();
+
+	let mut spans = Vec::new();
+	for band in bands.windows(2) {
+		let (top, bottom) = (band[0], band[1]);
+		let middle = (top + bottom) / 2.0;
+
+		spans.clear();
+		for &(a, b) in &edges {
+			if a.y.min(b.y) <= top && a.y.max(b.y) >= bottom {
+				let x = |y: f64| a.x + (b.x - a.x) * (y - a.y) / (b.y - a.y);
+				spans.push((x(middle), x(top), x(bottom), if b.y > a.y {1} else {-1}));
+			}
+		}
+		spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+		let mut winding = 0;
+		for i in 0..spans.len().saturating_sub(1) {
+			winding += spans[i].3;
+			let inside = match rule {
+				FillRule::NonZero => winding != 0,
+				FillRule::EvenOdd => winding % 2 != 0
+			};
+			if inside {
+				let (left, right) = (spans[i], spans[i + 1]);
+				mesh.quad(
+					Point {x: left.1, y: top},
+					Point {x: right.1, y: top},
+					Point {x: left.2, y: bottom},
+					Point {x: right.2, y: bottom}
+				);
+			}
+		}
+	}
+}
+
+// Parses SVG path data, returning the character index of an error.
+struct Parser<'a> {
+	data: &'a str,
+	chars: Peekable<CharIndices<'a>>
+}
+
+impl<'a> Parser<'a> {
+	fn new(data: &'a str) -> Parser<'a> {
+		Parser {
+			data,
+			chars: data.char_indices().peekable()
+		}
+	}
+
+	fn index(&mut self) -> usize {
+		let length = self.data.len();
+		self.chars.peek().map_or(length, |&(index, _)| index)
+	}
+
+	fn skip_separators(&mut self) {
+		while let Some(&(_, c)) = self.chars.peek() {
+			if c.is_whitespace() || c == ',' {
+				self.chars.next();
+			} else {
+				break;
+			}
+		}
+	}
+
+	fn number(&mut self) -> Result<f64, usize> {
+		self.skip_separators();
+		let start = self.index();
+		let mut end = start;
+		let mut dot = false;
+		let mut exponent = false;
+		while let Some(&(index, c)) = self.chars.peek() {
+			let valid = match c {
+				'0'..='9' => true,
+				'+' | '-' => index == start || self.data[..index].ends_with(|c| c == 'e' || c == 'E'),
+				'.' if !dot && !exponent => {
+					dot = true;
+					true
+				},
+				'e' | 'E' if !exponent && index > start => {
+					exponent = true;
+					true
+				},
+				_ => false
+			};
+			if !valid {
+				break;
+			}
+			self.chars.next();
+			end = index + c.len_utf8();
+		}
+		match self.data[start..end].parse::<f64>() {
+			Ok(number) if number.is_finite() => Ok(number),
+			_ => Err(start)
+		}
+	}
+
+	fn flag(&mut self) -> Result<bool, usize> {
+		self.skip_separators();
+		match self.chars.next() {
+			Some((_, '0')) => Ok(false),
+			Some((_, '1')) => Ok(true),
+			Some((index, _)) => Err(index),
+			None => Err(self.data.len())
+		}
+	}
+
+	fn point(&mut self, relative: bool, current: Point) -> Result<Point, usize> {
+		let point = Point {
+			x: self.number()?,
+			y: self.number()?
+		};
+		Ok(if relative {current + point} else {point})
+	}
+
+	fn parse(&mut self) -> Result<Path, usize> {
+		let mut path = Path::new();
+		let mut current = Point::default();
+		let mut start = Point::default();
+		// The last control point, for the smooth curve commands.
+		let mut last_cubic = None;
+		let mut last_quad = None;
+		let mut command = None;
+
+		loop {
+			self.skip_separators();
+			let index = self.index();
+			let next = match self.chars.peek() {
+				None => break,
+				Some(&(_, c)) if c.is_ascii_alphabetic() => {
+					self.chars.next();
+					c
+				},
+				// Numbers without a command repeat the last command.
+				Some(_) => match command {
+					Some('M') => 'L',
+					Some('m') => 'l',
+					Some(c) if c != 'Z' && c != 'z' => c,
+					_ => return Err(index)
+				}
+			};
+			command = Some(next);
+
+			let relative = next.is_ascii_lowercase();
+			let mut cubic = None;
+			let mut quad = None;
+			match next.to_ascii_uppercase() {
+				'M' => {
+					current = self.point(relative, current)?;
+					start = current;
+					path.move_to(current);
+				},
+				'L' => {
+					current = self.point(relative, current)?;
+					path.line_to(current);
+				},
+				'H' => {
+					let x = self.number()?;
+					current.x = if relative {current.x + x} else {x};
+					path.line_to(current);
+				},
+				'V' => {
+					let y = self.number()?;
+					current.y = if relative {current.y + y} else {y};
+					path.line_to(current);
+				},
+				'C' => {
+					let control1 = self.point(relative, current)?;
+					let control2 = self.point(relative, current)?;
+					let point = self.point(relative, current)?;
+					path.cubic_to(control1, control2, point);
+					cubic = Some(control2);
+					current = point;
+				},
+				'S' => {
+					let control1 = last_cubic.map_or(current, |control| current * 2.0 - control);
+					let control2 = self.point(relative, current)?;
+					let point = self.point(relative, current)?;
+					path.cubic_to(control1, control2, point);
+					cubic = Some(control2);
+					current = point;
+				},
+				'Q' => {
+					let control = self.point(relative, current)?;
+					let point = self.point(relative, current)?;
+					path.quad_to(control, point);
+					quad = Some(control);
+					current = point;
+				},
+				'T' => {
+					let control = last_quad.map_or(current, |control| current * 2.0 - control);
+					let point = self.point(relative, current)?;
+					path.quad_to(control, point);
+					quad = Some(control);
+					current = point;
+				},
+				'A' => {
+					let radius = Size {
+						width: self.number()?,
+						height: self.number()?
+					};
+					let rotation = Angle::from_degrees(self.number()?);
+					let large_arc = self.flag()?;
+					let clockwise = self.flag()?;
+					let point = self.point(relative, current)?;
+					path.arc_to(radius, rotation, large_arc, clockwise, point);
+					current = point;
+				},
+				'Z' => {
+					path.close();
+					current = start;
+				},
+				_ => return Err(index)
+			}
+			last_cubic = cubic;
+			last_quad = quad;
+		}
+		Ok(path)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn area(mesh: &Mesh) -> f64 {
+		mesh.indices.chunks(3).map(|triangle| {
+			let (a, b, c) = (mesh.points[triangle[0]], mesh.points[triangle[1]], mesh.points[triangle[2]]);
+			(b - a).cross(c - a).abs() / 2.0
+		}).sum()
+	}
+
+	fn square(x: f64, y: f64, size: f64) -> Vec<Point> {
+		vec![Point::new(x, y), Point::new(x + size, y), Point::new(x + size, y + size), Point::new(x, y + size)]
+	}
+
+	#[test]
+	fn svg_commands_match_the_builder() {
+		let mut expected = Path::new();
+		expected.move_to(Point::new(10.0, 20.0))
+			.line_to(Point::new(15.0, 20.0))
+			.line_to(Point::new(15.0, 30.0))
+			.line_to(Point::new(5.0, 30.0))
+			.line_to(Point::new(5.0, 25.0))
+			.cubic_to(Point::new(5.0, 20.0), Point::new(0.0, 20.0), Point::new(0.0, 25.0))
+			.cubic_to(Point::new(0.0, 30.0), Point::new(-5.0, 30.0), Point::new(-5.0, 25.0))
+			.quad_to(Point::new(0.0, 0.0), Point::new(5.0, 5.0))
+			.quad_to(Point::new(10.0, 10.0), Point::new(15.0, 5.0))
+			.arc_to(Size::new(2.0, 3.0), Angle::from_degrees(45.0), true, false, Point::new(20.0, 5.0))
+			.close()
+			.move_to(Point::new(11.0, 21.0))
+			.line_to(Point::new(13.0, 23.0));
+		let path = Path::from_svg("M10,20 h5 V30 H5 v-5 C5 20 0 20 0 25 s-5 5 -5 0 Q0,0 5,5 T15,5 a2 3 45 1 0 5 0 z m1 1 2 2").unwrap();
+		assert_eq!(path, expected);
+	}
+
+	#[test]
+	fn svg_numbers_can_be_packed_together() {
+		let mut expected = Path::new();
+		expected.move_to(Point::new(0.5, 0.25))
+			.line_to(Point::new(-1.0, 100.0))
+			.line_to(Point::new(0.5, 0.5));
+		assert_eq!(Path::from_svg("M.5.25L-1 1e2.5.5").unwrap(), expected);
+	}
+
+	#[test]
+	fn svg_errors_report_the_character() {
+		let error = |data: &str| match Path::from_svg(data) {
+			Err(GraphicsError::InvalidPath(message, _)) => message,
+			other => panic!("{:?} parsed as {:?}", data, other)
+		};
+		assert_eq!(error("10 10"), "Invalid SVG path data at character 0");
+		assert_eq!(error("M 10"), "Invalid SVG path data at character 4");
+		assert_eq!(error("M 0 0 X 1"), "Invalid SVG path data at character 6");
+		assert_eq!(error("M 0 0 A 1 1 0 2 0 1 1"), "Invalid SVG path data at character 14");
+		assert_eq!(error("M 0 0 Z 1 1"), "Invalid SVG path data at character 8");
+		assert_eq!(error("M 1e999 0"), "Invalid SVG path data at character 2");
+	}
+
+	#[test]
+	fn fill_rules_decide_overlaps() {
+		// A square inside a square, both going clockwise.
+		let polygons = vec![square(0.0, 0.0, 4.0), square(1.0, 1.0, 2.0)];
+
+		let mut mesh = Mesh::new();
+		fill(&mut mesh, &polygons, FillRule::NonZero);
+		assert_eq!(area(&mesh), 16.0);
+
+		let mut mesh = Mesh::new();
+		fill(&mut mesh, &polygons, FillRule::EvenOdd);
+		assert_eq!(area(&mesh), 12.0);
+	}
+
+	#[test]
+	fn fill_splits_self_intersections() {
+		// A bow tie, which crosses itself in the middle.
+		let polygons = vec![vec![Point::new(0.0, 0.0), Point::new(2.0, 2.0), Point::new(2.0, 0.0), Point::new(0.0, 2.0)]];
+		let mut mesh = Mesh::new();
+		fill(&mut mesh, &polygons, FillRule::NonZero);
+		assert_eq!(area(&mesh), 2.0);
+	}
+
+	#[test]
+	fn fill_skips_points_that_are_not_finite() {
+		let polygons = vec![
+			vec![Point::new(0.0, 0.0), Point::new(::std::f64::NAN, 1.0), Point::new(1.0, ::std::f64::INFINITY)],
+			square(0.0, 0.0, 1.0)
+		];
+		let mut mesh = Mesh::new();
+		fill(&mut mesh, &polygons, FillRule::NonZero);
+		assert!(mesh.points.iter().all(|point| point.x.is_finite() && point.y.is_finite()));
+	}
+}
\ No newline at end of file
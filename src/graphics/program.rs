@@ -0,0 +1,143 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::gl::{self, Gl, types::*};
+use super::opengl::GlApi;
+
+use std::ffi::CString;
+use std::ptr;
+
+const VERT_SOURCE: &str = include_str!("shader/vert.glsl");
+const FRAG_SOURCE: &str = include_str!("shader/frag.glsl");
+const MAIN_SOURCE: &str = include_str!("shader/main.glsl");
+
+// The shaders are written in GLSL ES 1.00 and upgraded with these headers.
+// The fragment color is written to FRAG_COLOR, since redefining gl_FragColor
+// is rejected by strict compilers.
+const GL_VERT_HEADER: &str = "#version 150
+#define attribute in
+#define varying out
+";
+
+const GL_FRAG_HEADER: &str = "#version 150
+#define varying in
+#define texture2D texture
+out vec4 frag_output;
+#define FRAG_COLOR frag_output
+";
+
+const GLES3_VERT_HEADER: &str = "#version 300 es
+#define attribute in
+#define varying out
+";
+
+const GLES3_FRAG_HEADER: &str = "#version 300 es
+precision mediump float;
+#define varying in
+#define texture2D texture
+out vec4 frag_output;
+#define FRAG_COLOR frag_output
+";
+
+const GLES2_VERT_HEADER: &str = "#version 100
+";
+
+const GLES2_FRAG_HEADER: &str = "#version 100
+precision mediump float;
+#define FRAG_COLOR gl_FragColor
+";
+
+/// The shader program used for all drawing.
+pub struct Program {
+	pub program: GLuint,
+	pub projection: GLint,
+	pub image: GLint,
+	pub premultiply: GLint
+}
+
+impl Program {
+	pub unsafe fn new(gl: &Gl, api: GlApi) -> Program {
+		let (vert_header, frag_header) = match api {
+			GlApi::Gl => (GL_VERT_HEADER, GL_FRAG_HEADER),
+			GlApi::Gles3 => (GLES3_VERT_HEADER, GLES3_FRAG_HEADER),
+			GlApi::Gles2 => (GLES2_VERT_HEADER, GLES2_FRAG_HEADER)
+		};
+		let vert = compile(gl, gl::VERTEX_SHADER, &[vert_header, VERT_SOURCE]);
+		let frag = compile(gl, gl::FRAGMENT_SHADER, &[frag_header, FRAG_SOURCE, MAIN_SOURCE]);
+
+		let program = gl.CreateProgram();
+		gl.AttachShader(program, vert);
+		gl.AttachShader(program, frag);
+
+		let point = CString::new("point").unwrap();
+		let texcoord = CString::new("texcoord").unwrap();
+		let color = CString::new("color").unwrap();
+		gl.BindAttribLocation(program, 0, point.as_ptr());
+		gl.BindAttribLocation(program, 1, texcoord.as_ptr());
+		gl.BindAttribLocation(program, 2, color.as_ptr());
+		if api == GlApi::Gl {
+			let output = CString::new("frag_output").unwrap();
+			gl.BindFragDataLocation(program, 0, output.as_ptr());
+		}
+
+		gl.LinkProgram(program);
+		gl.DetachShader(program, vert);
+		gl.DetachShader(program, frag);
+		gl.DeleteShader(vert);
+		gl.DeleteShader(frag);
+
+		let mut status = 0;
+		gl.GetProgramiv(program, gl::LINK_STATUS, &mut status);
+		if status == 0 {
+			let mut length = 0;
+			gl.GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut length);
+			let mut log = vec![0u8; length.max(1) as usize];
+			gl.GetProgramInfoLog(program, length, ptr::null_mut(), log.as_mut_ptr() as *mut _);
+			panic!("Could not link shader program: {}", String::from_utf8_lossy(&log));
+		}
+
+		let projection = CString::new("projection").unwrap();
+		let image = CString::new("image").unwrap();
+		let premultiply = CString::new("premultiply").unwrap();
+		Program {
+			program,
+			projection: gl.GetUniformLocation(program, projection.as_ptr()),
+			image: gl.GetUniformLocation(program, image.as_ptr()),
+			premultiply: gl.GetUniformLocation(program, premultiply.as_ptr())
+		}
+	}
+
+	pub unsafe fn delete(&self, gl: &Gl) {
+		gl.DeleteProgram(self.program);
+	}
+}
+
+unsafe fn compile(gl: &Gl, kind: GLenum, sources: &[&str]) -> GLuint {
+	let shader = gl.CreateShader(kind);
+	let pointers: Vec<_> = sources.iter().map(|source| source.as_ptr() as *const GLchar).collect();
+	let lengths: Vec<_> = sources.iter().map(|source| source.len() as GLint).collect();
+	gl.ShaderSource(shader, sources.len() as GLsizei, pointers.as_ptr(), lengths.as_ptr());
+	gl.CompileShader(shader);
+
+	let mut status = 0;
+	gl.GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+	if status == 0 {
+		let mut length = 0;
+		gl.GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut length);
+		let mut log = vec![0u8; length.max(1) as usize];
+		gl.GetShaderInfoLog(shader, length, ptr::null_mut(), log.as_mut_ptr() as *mut _);
+		panic!("Could not compile shader: {}", String::from_utf8_lossy(&log));
+	}
+	shader
+}
@@ -12,16 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#version 150
-in vec2 frag_texcoord;
-in vec4 frag_color;
+uniform float premultiply;
 
-out vec4 color;
-
-#define texture2D texture
+varying vec2 frag_texcoord;
+varying vec4 frag_color;
 
 vec4 pixel(vec2);
 
 void main() {
-	color = pixel(frag_texcoord) * frag_color;
+	vec4 color = pixel(frag_texcoord) * frag_color;
+	FRAG_COLOR = vec4(color.rgb * mix(1.0, color.a, premultiply), color.a);
 }
\ No newline at end of file
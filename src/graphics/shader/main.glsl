@@ -12,8 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-uniform sampler2D texture;
+uniform sampler2D image;
 
 vec4 pixel(vec2 texcoord) {
-	return texture2D(texure, texcoord);
+	return texture2D(image, texcoord);
 }
\ No newline at end of file
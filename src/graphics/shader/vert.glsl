@@ -12,16 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#version 150
-in vec2 point;
-in vec2 texcoord;
-in vec4 color;
+uniform mat3 projection;
 
-out vec2 frag_texcoord;
-out vec4 frag_color;
+attribute vec2 point;
+attribute vec2 texcoord;
+attribute vec4 color;
+
+varying vec2 frag_texcoord;
+varying vec4 frag_color;
 
 void main() {
 	frag_texcoord = texcoord;
 	frag_color = color;
-	gl_Position = point;
+	gl_Position = vec4((projection * vec3(point, 1.0)).xy, 0.0, 1.0);
 }
\ No newline at end of file
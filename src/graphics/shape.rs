@@ -0,0 +1,469 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ::{Point, Size, Rect};
+
+use std::f64::consts::PI;
+
+// The most segments an arc is split into, however large it is.
+const MAX_ARC_SEGMENTS: usize = 1024;
+
+/// The shape drawn where two stroked segments meet.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LineJoin {
+	/// The outer edges are extended until they meet.
+	/// Falls back to [`Bevel`](#variant.Bevel) when the point would be further than
+	/// [`Stroke.miter_limit`](struct.Stroke.html#structfield.miter_limit).
+	Miter,
+
+	/// The outer edges are joined with an arc.
+	Round,
+
+	/// The outer edges are joined with a straight line.
+	Bevel
+}
+
+/// The shape drawn at the ends of an open stroke.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LineCap {
+	/// The stroke ends exactly at the end point.
+	Butt,
+
+	/// The stroke ends with a half circle around the end point.
+	Round,
+
+	/// The stroke is extended past the end point by half the width.
+	Square
+}
+
+/// How lines and outlines are stroked.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stroke {
+	/// The width of the stroke, centered on the line.
+	pub width: f64,
+
+	/// The shape where segments meet.
+	pub join: LineJoin,
+
+	/// The shape at the ends of open strokes.
+	pub cap: LineCap,
+
+	/// The longest a miter join can be, as a multiple of half the width.
+	pub miter_limit: f64
+}
+
+impl Default for Stroke {
+	fn default() -> Stroke {
+		Stroke {
+			width: 1.0,
+			join: LineJoin::Miter,
+			cap: LineCap::Butt,
+			miter_limit: 4.0
+		}
+	}
+}
+
+/// Triangles built from shapes before they are transformed and batched.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+	pub points: Vec<Point>,
+	pub indices: Vec<usize>
+}
+
+impl Mesh {
+	pub fn new() -> Mesh {
+		Mesh::default()
+	}
+
+	fn point(&mut self, point: Point) -> usize {
+		self.points.push(point);
+		self.points.len() - 1
+	}
+
+	fn triangle(&mut self, a: Point, b: Point, c: Point) {
+		let a = self.point(a);
+		let b = self.point(b);
+		let c = self.point(c);
+		self.indices.extend_from_slice(&[a, b, c]);
+	}
+
+	pub fn quad(&mut self, a: Point, b: Point, c: Point, d: Point) {
+		let a = self.point(a);
+		let b = self.point(b);
+		let c = self.point(c);
+		let d = self.point(d);
+		self.indices.extend_from_slice(&[a, b, c, c, b, d]);
+	}
+
+	/// Adds a triangle fan around the center through the arc from the start angle.
+	/// A positive sweep goes clockwise since y points down.
+	pub fn arc_fan(&mut self, center: Point, radius: Size, start: f64, sweep: f64, tolerance: f64) {
+		let points = arc(center, radius, start, sweep, tolerance);
+		let center = self.point(center);
+		let base = self.points.len();
+		self.points.extend_from_slice(&points);
+		for i in 1..points.len() {
+			self.indices.extend_from_slice(&[center, base + i - 1, base + i]);
+		}
+	}
+}
+
+/// Gets the points along an elliptical arc, including both ends.
+/// A positive sweep goes clockwise since y points down.
+pub fn arc(center: Point, radius: Size, start: f64, sweep: f64, tolerance: f64) -> Vec<Point> {
+	let segments = arc_segments(radius.width.abs().max(radius.height.abs()), sweep, tolerance);
+	(0..segments + 1).map(|i| {
+		let angle = start + sweep * i as f64 / segments as f64;
+		Point {
+			x: center.x + angle.cos() * radius.width,
+			y: center.y + angle.sin() * radius.height
+		}
+	}).collect()
+}
+
+/// Gets the points around an ellipse, without repeating the first point.
+pub fn ellipse(center: Point, radius: Size, tolerance: f64) -> Vec<Point> {
+	let mut points = arc(center, radius, 0.0, 2.0 * PI, tolerance);
+	points.pop();
+	points
+}
+
+/// Gets the points around a rectangle with rounded corners.
+/// The radius is limited to half the smallest side.
+pub fn rounded_rect(rect: Rect, radius: f64, tolerance: f64) -> Vec<Point> {
+	let radius = radius.max(0.0)
+		.min(rect.size.width.abs() / 2.0)
+		.min(rect.size.height.abs() / 2.0);
+	if radius == 0.0 {
+		return vec![rect.top_left(), rect.top_right(), rect.bottom_right(), rect.bottom_left()];
+	}
+
+	let size = Size {width: radius, height: radius};
+	let inner = rect.expand(-radius);
+	let mut points = Vec::new();
+	points.extend(arc(inner.top_left(), size, PI, PI / 2.0, tolerance));
+	points.extend(arc(inner.top_right(), size, PI * 1.5, PI / 2.0, tolerance));
+	points.extend(arc(inner.bottom_right(), size, 0.0, PI / 2.0, tolerance));
+	points.extend(arc(inner.bottom_left(), size, PI / 2.0, PI / 2.0, tolerance));
+	points
+}
+
+/// The amount of segments needed so an arc never differs from
+/// the true curve by more than the tolerance, up to 1024.
+pub fn arc_segments(radius: f64, sweep: f64, tolerance: f64) -> usize {
+	// Tiny arcs still use a segment for every quarter turn.
+	let step = if radius <= tolerance {
+		PI / 2.0
+	} else {
+		2.0 * (1.0 - tolerance / radius).acos()
+	};
+	((sweep.abs() / step).ceil() as usize).max(1).min(MAX_ARC_SEGMENTS)
+}
+
+// Removes repeated points, which have no direction.
+fn dedup(points: &[Point], closed: bool) -> Vec<Point> {
+	let mut result: Vec<Point> = Vec::with_capacity(points.len());
+	for &point in points {
+		if result.last() != Some(&point) {
+			result.push(point);
+		}
+	}
+	if closed && result.len() > 1 && result.first() == result.last() {
+		result.pop();
+	}
+	result
+}
+
+fn signed_area(points: &[Point]) -> f64 {
+	let mut area = 0.0;
+	for i in 0..points.len() {
+		area += points[i].cross(points[(i + 1) % points.len()]);
+	}
+	area / 2.0
+}
+
+fn in_triangle(point: Point, a: Point, b: Point, c: Point) -> bool {
+	let ab = (b - a).cross(point - a);
+	let bc = (c - b).cross(point - b);
+	let ca = (a - c).cross(point - c);
+	ab >= 0.0 && bc >= 0.0 && ca >= 0.0
+}
+
+/// Fills a convex polygon with a fan from the first point,
+/// which is faster than [`fill`](fn.fill.html).
+pub fn fill_convex(mesh: &mut Mesh, points: &[Point]) {
+	if points.len() < 3 {
+		return;
+	}
+	let base = mesh.points.len();
+	mesh.points.extend_from_slice(points);
+	for i in 2..points.len() {
+		mesh.indices.extend_from_slice(&[base, base + i - 1, base + i]);
+	}
+}
+
+/// Fills a simple polygon, which can be concave, using ear clipping.
+/// Self-intersecting polygons are still filled but may have gaps or overlaps.
+pub fn fill(mesh: &mut Mesh, points: &[Point]) {
+	let points = dedup(points, true);
+	if points.len() < 3 {
+		return;
+	}
+
+	let base = mesh.points.len();
+	mesh.points.extend_from_slice(&points);
+
+	// Ear clipping expects a consistent winding.
+	let mut remaining: Vec<usize> = (0..points.len()).collect();
+	if signed_area(&points) < 0.0 {
+		remaining.reverse();
+	}
+
+	while remaining.len() > 3 {
+		let count = remaining.len();
+		let ear = (0..count).find(|&i| {
+			let a = points[remaining[(i + count - 1) % count]];
+			let b = points[remaining[i]];
+			let c = points[remaining[(i + 1) % count]];
+			if (b - a).cross(c - b) <= 0.0 {
+				return false;
+			}
+			!remaining.iter()
+				.map(|&index| points[index])
+				.any(|point| point != a && point != b && point != c && in_triangle(point, a, b, c))
+		}).unwrap_or(0);
+
+		mesh.indices.extend_from_slice(&[
+			base + remaining[(ear + count - 1) % count],
+			base + remaining[ear],
+			base + remaining[(ear + 1) % count]
+		]);
+		remaining.remove(ear);
+	}
+	mesh.indices.extend(remaining.iter().map(|&index| base + index));
+}
+
+fn join(mesh: &mut Mesh, stroke: &Stroke, point: Point, before: Point, after: Point, tolerance: f64) {
+	let half = stroke.width / 2.0;
+	let cross = before.cross(after);
+	if cross.abs() < 1e-9 && before.dot(after) > 0.0 {
+		return;
+	}
+
+	// The join is only needed on the outside of the turn.
+	let side = if cross > 0.0 {-1.0} else {1.0};
+	let outer0 = before.perpendicular() * side;
+	let outer1 = after.perpendicular() * side;
+	let a = point + outer0 * half;
+	let b = point + outer1 * half;
+
+	match stroke.join {
+		LineJoin::Bevel => mesh.triangle(point, a, b),
+		LineJoin::Miter => {
+			let direction = (outer0 + outer1).normalize();
+			let cos = direction.dot(outer0);
+			if cos > 1e-9 && 1.0 / cos <= stroke.miter_limit {
+				let tip = point + direction * (half / cos);
+				mesh.triangle(point, a, tip);
+				mesh.triangle(point, tip, b);
+			} else {
+				mesh.triangle(point, a, b);
+			}
+		},
+		LineJoin::Round => {
+			let start = outer0.y.atan2(outer0.x);
+			let mut sweep = outer1.y.atan2(outer1.x) - start;
+			if sweep > PI {
+				sweep -= 2.0 * PI;
+			} else if sweep < -PI {
+				sweep += 2.0 * PI;
+			}
+			mesh.arc_fan(point, Size {width: half, height: half}, start, sweep, tolerance);
+		}
+	}
+}
+
+/// Gets how far back along two segments their inner edges cross,
+/// or infinity if they never do.
+fn inner_trim(before: Point, after: Point, half: f64) -> f64 {
+	let cross = before.cross(after);
+	let dot = before.dot(after);
+	if cross.abs() < 1e-9 {
+		return if dot > 0.0 {0.0} else {f64::INFINITY};
+	}
+	half * cross.abs() / (1.0 + dot)
+}
+
+fn cap(mesh: &mut Mesh, stroke: &Stroke, point: Point, direction: Point, tolerance: f64) {
+	let half = stroke.width / 2.0;
+	let normal = direction.perpendicular() * half;
+	match stroke.cap {
+		LineCap::Butt => (),
+		LineCap::Square => {
+			let offset = direction * half;
+			mesh.quad(point + normal, point + normal + offset, point - normal, point - normal + offset);
+		},
+		LineCap::Round => {
+			let start = normal.y.atan2(normal.x);
+			mesh.arc_fan(point, Size {width: half, height: half}, start, -PI, tolerance);
+		}
+	}
+}
+
+/// Strokes the line through the points.
+/// If closed, the last point is also joined to the first point.
+///
+/// Segments are cut where their inner edges meet so a translucent stroke
+/// is only blended once. They still overlap where the line crosses itself,
+/// or where a turn is too sharp for the segments next to it.
+pub fn stroke(mesh: &mut Mesh, stroke: &Stroke, points: &[Point], closed: bool, tolerance: f64) {
+	let points = dedup(points, closed);
+	let half = stroke.width / 2.0;
+	if points.is_empty() || half <= 0.0 {
+		return;
+	}
+
+	if points.len() == 1 {
+		// A single point only draws its caps.
+		if !closed {
+			cap(mesh, stroke, points[0], Point {x: -1.0, y: 0.0}, tolerance);
+			cap(mesh, stroke, points[0], Point {x: 1.0, y: 0.0}, tolerance);
+		}
+		return;
+	}
+
+	let count = points.len();
+	let segments = if closed {count} else {count - 1};
+	let directions: Vec<Point> = (0..segments)
+		.map(|i| (points[(i + 1) % count] - points[i]).normalize())
+		.collect();
+
+	// Where the inner edges of the segments meet at each point,
+	// if both segments are long enough to be cut back that far.
+	let lengths: Vec<f64> = (0..segments)
+		.map(|i| points[i].distance(points[(i + 1) % count]))
+		.collect();
+	let turns: Vec<Option<(usize, usize)>> = (0..count).map(|i| match i {
+		_ if closed => Some(((i + segments - 1) % segments, i)),
+		0 => None,
+		i if i == count - 1 => None,
+		i => Some((i - 1, i))
+	}).collect();
+	let trims: Vec<f64> = turns.iter().map(|turn| match *turn {
+		Some((before, after)) => inner_trim(directions[before], directions[after], half),
+		None => 0.0
+	}).collect();
+	let corners: Vec<Option<Point>> = turns.iter().enumerate().map(|(i, turn)| {
+		let (before, after) = (*turn)?;
+		let fits = trims[i] + trims[before] <= lengths[before] && trims[i] + trims[(after + 1) % count] <= lengths[after];
+		if trims[i] > 0.0 && fits {
+			let side = if directions[before].cross(directions[after]) > 0.0 {1.0} else {-1.0};
+			Some(points[i] + directions[before].perpendicular() * (side * half) - directions[before] * trims[i])
+		} else {
+			None
+		}
+	}).collect();
+
+	for i in 0..segments {
+		let (a, b) = (points[i], points[(i + 1) % count]);
+		let normal = directions[i].perpendicular() * half;
+
+		// Goes around the segment, ending at the inner corners instead of
+		// crossing the segments next to it.
+		let mut outline = Vec::with_capacity(6);
+		match corners[(i + 1) % count] {
+			Some(corner) if (corner - b).dot(normal) > 0.0 => outline.extend_from_slice(&[corner, b, b - normal]),
+			Some(corner) => outline.extend_from_slice(&[b + normal, b, corner]),
+			None => outline.extend_from_slice(&[b + normal, b - normal])
+		}
+		match corners[i] {
+			Some(corner) if (corner - a).dot(normal) > 0.0 => outline.extend_from_slice(&[a - normal, a, corner]),
+			Some(corner) => outline.extend_from_slice(&[corner, a, a + normal]),
+			None => outline.extend_from_slice(&[a - normal, a + normal])
+		}
+		fill_convex(mesh, &outline);
+	}
+
+	if closed {
+		for i in 0..count {
+			let before = directions[(i + segments - 1) % segments];
+			join(mesh, stroke, points[i], before, directions[i], tolerance);
+		}
+	} else {
+		for i in 1..count - 1 {
+			join(mesh, stroke, points[i], directions[i - 1], directions[i], tolerance);
+		}
+		cap(mesh, stroke, points[0], -directions[0], tolerance);
+		cap(mesh, stroke, points[count - 1], directions[segments - 1], tolerance);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ::graphics::{Graphics, Color};
+
+	#[test]
+	fn translucent_strokes_blend_once() {
+		let points = [
+			Point::new(4.0, 4.0),
+			Point::new(28.0, 4.0),
+			Point::new(28.0, 28.0),
+			Point::new(16.0, 12.0),
+			Point::new(4.0, 28.0)
+		];
+		for &join in &[LineJoin::Miter, LineJoin::Round, LineJoin::Bevel] {
+			for &closed in &[false, true] {
+				let mut graphics = Graphics::software(32, 32);
+				graphics.color(Color::TRANSPARENT);
+				graphics.clear();
+				graphics.color(Color {alpha: 0.5, ..Color::WHITE});
+				graphics.stroke(Stroke {width: 5.0, join, ..Stroke::default()});
+				if closed {
+					graphics.stroke_polygon(&points);
+				} else {
+					graphics.polyline(&points);
+				}
+
+				let image = graphics.read_pixels();
+				let mut alphas: Vec<u8> = image.get_data().chunks(4).map(|pixel| pixel[3]).collect();
+				alphas.sort();
+				alphas.dedup();
+				assert_eq!(alphas, vec![0, 128], "{:?} closed: {}", join, closed);
+			}
+		}
+	}
+
+	#[test]
+	fn segments_end_at_the_inner_corner() {
+		let mut mesh = Mesh::new();
+		let bevel = Stroke {width: 2.0, join: LineJoin::Bevel, ..Stroke::default()};
+		stroke(&mut mesh, &bevel, &[Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0)], false, 0.1);
+		assert!(mesh.points.contains(&Point::new(9.0, 1.0)));
+		assert!(!mesh.points.contains(&Point::new(10.0, 1.0)));
+	}
+
+	#[test]
+	fn arc_segments_are_capped() {
+		assert_eq!(arc_segments(0.1, PI, 0.25), 2);
+		assert_eq!(arc_segments(0.1, 0.0, 0.25), 1);
+		assert_eq!(arc_segments(0.1, 1e300, 0.25), MAX_ARC_SEGMENTS);
+		assert_eq!(arc_segments(100.0, -1e300, 0.25), MAX_ARC_SEGMENTS);
+		assert_eq!(arc_segments(100.0, ::std::f64::INFINITY, 0.25), MAX_ARC_SEGMENTS);
+		assert!(arc_segments(100.0, PI, 0.25) < MAX_ARC_SEGMENTS);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,629 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::backend::{RenderBackend, DrawState, MaskMode, Vertex, Point32, Color8, TextureFilter};
+use super::opengl::GlBackend;
+use super::{Color, Image, GraphicsError, BlendMode};
+use super::blend;
+
+use ::Size;
+use ::core::WindowError;
+
+// The amount of sub-pixel precision bits used when rasterizing.
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL_ONE: i64 = 1 << SUBPIXEL_BITS;
+// The furthest a point can be from the origin, in pixels.
+const MAX_COORD: f32 = (1u64 << 53) as f32;
+
+/// Draws on the CPU into an RGBA buffer.
+///
+/// The rasterizer only uses integer and well-defined floating point operations,
+/// so the output is the same on every machine. This makes it useful for
+/// rendering tests on machines without OpenGL.
+#[derive(Clone, Debug)]
+pub struct SoftwareBackend {
+	width: u32,
+	height: u32,
+	pixels: Vec<u8>,
+	mask: Vec<bool>,
+	textures: Vec<Option<(Image, TextureFilter)>>
+}
+
+impl SoftwareBackend {
+	/// Creates a backend with a transparent black target of the provided size in pixels.
+	pub fn new(width: u32, height: u32) -> SoftwareBackend {
+		SoftwareBackend {
+			width,
+			height,
+			pixels: vec![0; width as usize * height as usize * 4],
+			mask: vec![false; width as usize * height as usize],
+			textures: Vec::new()
+		}
+	}
+
+	/// Resizes the target, which also clears it to transparent black.
+	pub fn resize(&mut self, width: u32, height: u32) {
+		self.width = width;
+		self.height = height;
+		self.pixels = vec![0; width as usize * height as usize * 4];
+		self.mask = vec![false; width as usize * height as usize];
+	}
+
+	/// Gets the RGBA pixels of the target, from the top-left.
+	pub fn get_pixels(&self) -> &[u8] {
+		&self.pixels
+	}
+
+	fn triangle(&mut self, texture: Option<&(Image, TextureFilter)>, state: &DrawState, vertices: [&Vertex; 3]) {
+		// The area that can be drawn to, from the top-left to the bottom-right.
+		let (left, top, right, bottom) = match state.clip {
+			Some(clip) => {
+				let clamp = |value: f64, max: u32| value.max(0.0).min(max as f64) as i64;
+				(
+					clamp(clip.left(), self.width),
+					clamp(clip.top(), self.height),
+					clamp(clip.left() + clip.size.width, self.width),
+					clamp(clip.top() + clip.size.height, self.height)
+				)
+			},
+			None => (0, 0, self.width as i64, self.height as i64)
+		};
+
+		// Skips triangles that are outside of the area before converting to fixed point,
+		// which also skips any with points that are not finite.
+		let points = [vertices[0].point, vertices[1].point, vertices[2].point];
+		let inside = |coords: [f32; 3], start: i64, end: i64| {
+			let min = coords.iter().cloned().fold(f32::INFINITY, f32::min);
+			let max = coords.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+			min.is_finite() && max.is_finite() && max >= start as f32 && min < end as f32
+		};
+		if !inside([points[0].x, points[1].x, points[2].x], left, right)
+			|| !inside([points[0].y, points[1].y, points[2].y], top, bottom) {
+			return;
+		}
+
+		// Points far outside of the target are clamped so the edge functions fit in an i128.
+		// At that distance an f32 has no sub-pixel precision left anyway.
+		let fixed = |point: Point32| (
+			(point.x.max(-MAX_COORD).min(MAX_COORD) * SUBPIXEL_ONE as f32).round() as i128,
+			(point.y.max(-MAX_COORD).min(MAX_COORD) * SUBPIXEL_ONE as f32).round() as i128
+		);
+
+		let mut v = vertices;
+		let mut p = [fixed(points[0]), fixed(points[1]), fixed(points[2])];
+		let mut area = edge(p[0], p[1], p[2]);
+		if area == 0 {
+			return;
+		}
+
+		// Use the same winding for every triangle.
+		if area < 0 {
+			v.swap(1, 2);
+			p.swap(1, 2);
+			area = -area;
+		}
+
+		let bias = [
+			if is_top_left(p[1], p[2]) {0} else {-1},
+			if is_top_left(p[2], p[0]) {0} else {-1},
+			if is_top_left(p[0], p[1]) {0} else {-1}
+		];
+
+		let min_x = p.iter().map(|p| p.0).min().unwrap();
+		let max_x = p.iter().map(|p| p.0).max().unwrap();
+		let min_y = p.iter().map(|p| p.1).min().unwrap();
+		let max_y = p.iter().map(|p| p.1).max().unwrap();
+		let min_x = (min_x >> SUBPIXEL_BITS).max(left as i128) as i64;
+		let min_y = (min_y >> SUBPIXEL_BITS).max(top as i128) as i64;
+		let max_x = (max_x >> SUBPIXEL_BITS).min(right as i128 - 1) as i64;
+		let max_y = (max_y >> SUBPIXEL_BITS).min(bottom as i128 - 1) as i64;
+
+		for y in min_y..max_y + 1 {
+			for x in min_x..max_x + 1 {
+				// Sample at the center of the pixel.
+				let center = (
+					(x * SUBPIXEL_ONE + SUBPIXEL_ONE / 2) as i128,
+					(y * SUBPIXEL_ONE + SUBPIXEL_ONE / 2) as i128
+				);
+				let w = [
+					edge(p[1], p[2], center),
+					edge(p[2], p[0], center),
+					edge(p[0], p[1], center)
+				];
+				if w[0] + bias[0] < 0 || w[1] + bias[1] < 0 || w[2] + bias[2] < 0 {
+					continue;
+				}
+
+				let pixel = y as usize * self.width as usize + x as usize;
+				match state.mask {
+					MaskMode::Disabled => (),
+					MaskMode::Write => {
+						self.mask[pixel] = true;
+						continue;
+					},
+					MaskMode::Inside => if !self.mask[pixel] {
+						continue;
+					},
+					MaskMode::Outside => if self.mask[pixel] {
+						continue;
+					}
+				}
+
+				let l = [
+					w[0] as f32 / area as f32,
+					w[1] as f32 / area as f32,
+					w[2] as f32 / area as f32
+				];
+				let interpolate = |a: f32, b: f32, c: f32| l[0] * a + l[1] * b + l[2] * c;
+				let channel = |a: u8, b: u8, c: u8| {
+					interpolate(a as f32, b as f32, c as f32).round().max(0.0).min(255.0) as u8
+				};
+
+				let color = Color8 {
+					red:   channel(v[0].color.red,   v[1].color.red,   v[2].color.red),
+					green: channel(v[0].color.green, v[1].color.green, v[2].color.green),
+					blue:  channel(v[0].color.blue,  v[1].color.blue,  v[2].color.blue),
+					alpha: channel(v[0].color.alpha, v[1].color.alpha, v[2].color.alpha)
+				};
+
+				let texel = match texture {
+					Some(&(ref image, filter)) => sample(image, filter, Point32 {
+						x: interpolate(v[0].texcoord.x, v[1].texcoord.x, v[2].texcoord.x),
+						y: interpolate(v[0].texcoord.y, v[1].texcoord.y, v[2].texcoord.y)
+					}),
+					None => Color8 {red: 255, green: 255, blue: 255, alpha: 255}
+				};
+
+				let source = Color8 {
+					red:   multiply(texel.red,   color.red),
+					green: multiply(texel.green, color.green),
+					blue:  multiply(texel.blue,  color.blue),
+					alpha: multiply(texel.alpha, color.alpha)
+				};
+				let index = pixel * 4;
+				blend::blend(state.blend, source, &mut self.pixels[index..index + 4]);
+			}
+		}
+	}
+}
+
+impl RenderBackend for SoftwareBackend {
+	fn get_size(&self) -> Size {
+		Size {
+			width: self.width as f64,
+			height: self.height as f64
+		}
+	}
+
+	fn create_texture(&mut self, image: &Image) -> u32 {
+		self.create_texture_with_filter(image, TextureFilter::Nearest)
+	}
+
+	fn create_texture_with_filter(&mut self, image: &Image, filter: TextureFilter) -> u32 {
+		let index = match self.textures.iter().position(Option::is_none) {
+			Some(index) => {
+				self.textures[index] = Some((image.clone(), filter));
+				index
+			},
+			None => {
+				self.textures.push(Some((image.clone(), filter)));
+				self.textures.len() - 1
+			}
+		};
+		// Zero is never a valid texture, like in OpenGL.
+		index as u32 + 1
+	}
+
+	fn update_texture(&mut self, texture: u32, x: u32, y: u32, image: &Image) {
+		if let Some(&mut Some((ref mut target, _))) = self.textures.get_mut(texture.wrapping_sub(1) as usize) {
+			target.blit(image, x, y);
+		}
+	}
+
+	fn delete_texture(&mut self, texture: u32) {
+		if let Some(slot) = self.textures.get_mut(texture.wrapping_sub(1) as usize) {
+			*slot = None;
+		}
+	}
+
+	fn clear(&mut self, color: Color) {
+		let color = color.to_rgba8();
+		for pixel in self.pixels.chunks_mut(4) {
+			pixel.copy_from_slice(&color);
+		}
+	}
+
+	fn clear_mask(&mut self) {
+		for pixel in &mut self.mask {
+			*pixel = false;
+		}
+	}
+
+	fn draw(&mut self, state: &DrawState, vertices: &[Vertex], elements: &[u16]) {
+		let texture = state.texture;
+		let image = self.textures.get_mut(texture.wrapping_sub(1) as usize)
+			.and_then(Option::take);
+		for triangle in elements.chunks(3) {
+			if triangle.len() == 3 {
+				self.triangle(image.as_ref(), state, [
+					&vertices[triangle[0] as usize],
+					&vertices[triangle[1] as usize],
+					&vertices[triangle[2] as usize]
+				]);
+			}
+		}
+
+		if let Some(image) = image {
+			self.textures[texture.wrapping_sub(1) as usize] = Some(image);
+		}
+	}
+
+	fn read_pixels(&mut self) -> Image {
+		Image::from_rgba(self.pixels.clone(), self.width, self.height).unwrap()
+	}
+}
+
+fn edge(a: (i128, i128), b: (i128, i128), point: (i128, i128)) -> i128 {
+	(b.0 - a.0) * (point.1 - a.1) - (b.1 - a.1) * (point.0 - a.0)
+}
+
+// Pixels exactly on an edge are only drawn for top and left edges,
+// so triangles that share an edge never draw the same pixel twice.
+fn is_top_left(a: (i128, i128), b: (i128, i128)) -> bool {
+	(a.1 == b.1 && b.0 > a.0) || b.1 < a.1
+}
+
+fn multiply(a: u8, b: u8) -> u8 {
+	((a as u32 * b as u32 + 127) / 255) as u8
+}
+
+fn texel(image: &Image, x: u32, y: u32) -> [u32; 4] {
+	let index = (y as usize * image.get_width() as usize + x as usize) * 4;
+	let data = image.get_data();
+	[data[index] as u32, data[index + 1] as u32, data[index + 2] as u32, data[index + 3] as u32]
+}
+
+fn sample(image: &Image, filter: TextureFilter, texcoord: Point32) -> Color8 {
+	let width = image.get_width();
+	let height = image.get_height();
+	if width == 0 || height == 0 {
+		return Color8::default();
+	}
+
+	let color = match filter {
+		TextureFilter::Nearest => {
+			let coord = |coord: f32, size: u32| {
+				((coord * size as f32).floor().max(0.0) as u32).min(size - 1)
+			};
+			texel(image, coord(texcoord.x, width), coord(texcoord.y, height))
+		},
+		TextureFilter::Linear => {
+			// The weights use 8-bit fixed point so the result is exact.
+			let coord = |coord: f32, size: u32| {
+				let fixed = ((coord * size as f32 - 0.5) * 256.0).round() as i64;
+				let clamp = |value: i64| value.max(0).min(size as i64 - 1) as u32;
+				(clamp(fixed >> 8), clamp((fixed >> 8) + 1), (fixed & 255) as u32)
+			};
+			let (x0, x1, fx) = coord(texcoord.x, width);
+			let (y0, y1, fy) = coord(texcoord.y, height);
+			let (a, b) = (texel(image, x0, y0), texel(image, x1, y0));
+			let (c, d) = (texel(image, x0, y1), texel(image, x1, y1));
+			let mut color = [0; 4];
+			for i in 0..4 {
+				let top = a[i] * (256 - fx) + b[i] * fx;
+				let bottom = c[i] * (256 - fx) + d[i] * fx;
+				color[i] = (top * (256 - fy) + bottom * fy + (1 << 15)) >> 16;
+			}
+			color
+		}
+	};
+
+	Color8 {
+		red:   color[0] as u8,
+		green: color[1] as u8,
+		blue:  color[2] as u8,
+		alpha: color[3] as u8
+	}
+}
+
+/// Draws with the software backend and shows the result through OpenGL.
+/// Only a single textured quad is drawn with OpenGL each frame,
+/// which works around most driver bugs.
+pub struct SoftwareWindowBackend {
+	software: SoftwareBackend,
+	gl: GlBackend,
+	// The texture the pixels are shown with, and its size.
+	texture: Option<(u32, u32, u32)>
+}
+
+impl SoftwareWindowBackend {
+	pub fn new(gl: GlBackend) -> SoftwareWindowBackend {
+		let size = gl.get_size();
+		SoftwareWindowBackend {
+			software: SoftwareBackend::new(size.width as u32, size.height as u32),
+			gl,
+			texture: None
+		}
+	}
+}
+
+impl RenderBackend for SoftwareWindowBackend {
+	fn get_size(&self) -> Size {
+		self.software.get_size()
+	}
+
+	fn create_texture(&mut self, image: &Image) -> u32 {
+		self.software.create_texture(image)
+	}
+
+	fn create_texture_with_filter(&mut self, image: &Image, filter: TextureFilter) -> u32 {
+		self.software.create_texture_with_filter(image, filter)
+	}
+
+	fn update_texture(&mut self, texture: u32, x: u32, y: u32, image: &Image) {
+		self.software.update_texture(texture, x, y, image)
+	}
+
+	fn delete_texture(&mut self, texture: u32) {
+		self.software.delete_texture(texture)
+	}
+
+	fn clear(&mut self, color: Color) {
+		self.software.clear(color)
+	}
+
+	fn clear_mask(&mut self) {
+		self.software.clear_mask()
+	}
+
+	fn draw(&mut self, state: &DrawState, vertices: &[Vertex], elements: &[u16]) {
+		self.software.draw(state, vertices, elements)
+	}
+
+	fn read_pixels(&mut self) -> Image {
+		self.software.read_pixels()
+	}
+
+	fn present(&mut self) -> Result<(), WindowError> {
+		// The texture is only created again when the size changes.
+		let image = self.software.read_pixels();
+		let (width, height) = (image.get_width(), image.get_height());
+		let texture = match self.texture {
+			Some((texture, texture_width, texture_height)) if texture_width == width && texture_height == height => {
+				self.gl.update_texture(texture, 0, 0, &image);
+				texture
+			},
+			previous => {
+				if let Some((texture, ..)) = previous {
+					self.gl.delete_texture(texture);
+				}
+				let texture = self.gl.create_texture(&image);
+				self.texture = Some((texture, width, height));
+				texture
+			}
+		};
+
+		let size = self.gl.get_size();
+		let white = Color8 {red: 255, green: 255, blue: 255, alpha: 255};
+		let vertex = |x: f64, y: f64| Vertex {
+			point: Point32 {
+				x: (x * size.width) as f32,
+				y: (y * size.height) as f32
+			},
+			texcoord: Point32 {
+				x: x as f32,
+				y: y as f32
+			},
+			color: white
+		};
+
+		self.gl.clear(Color::TRANSPARENT);
+		self.gl.draw(&DrawState {
+			texture,
+			blend: BlendMode::Replace,
+			clip: None,
+			mask: MaskMode::Disabled
+		}, &[
+			vertex(0.0, 0.0),
+			vertex(1.0, 0.0),
+			vertex(0.0, 1.0),
+			vertex(1.0, 1.0)
+		], &[0, 1, 2, 2, 1, 3]);
+		self.gl.present()?;
+
+		let size = self.gl.get_size();
+		if size != self.software.get_size() {
+			self.software.resize(size.width as u32, size.height as u32);
+		}
+		Ok(())
+	}
+
+	fn take_error(&mut self) -> Option<GraphicsError> {
+		self.gl.take_error()
+	}
+
+	fn set_call(&mut self, call: &'static str) {
+		self.gl.set_call(call)
+	}
+}
+
+impl Drop for SoftwareWindowBackend {
+	fn drop(&mut self) {
+		if let Some((texture, ..)) = self.texture.take() {
+			self.gl.delete_texture(texture);
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ::Rect;
+	use ::graphics::Graphics;
+
+	const WHITE: Color8 = Color8 {red: 255, green: 255, blue: 255, alpha: 255};
+
+	fn state(texture: u32, blend: BlendMode) -> DrawState {
+		DrawState {
+			texture,
+			blend,
+			clip: None,
+			mask: MaskMode::Disabled
+		}
+	}
+
+	fn vertex(x: f32, y: f32, u: f32, v: f32, color: Color8) -> Vertex {
+		Vertex {
+			point: Point32 {x, y},
+			texcoord: Point32 {x: u, y: v},
+			color
+		}
+	}
+
+	/// Draws a quad from the top-left to the bottom-right as two triangles.
+	fn quad(backend: &mut SoftwareBackend, state: &DrawState, rect: Rect, color: Color8) {
+		let (left, top) = (rect.left() as f32, rect.top() as f32);
+		let (right, bottom) = (rect.right() as f32, rect.bottom() as f32);
+		backend.draw(state, &[
+			vertex(left, top, 0.0, 0.0, color),
+			vertex(right, top, 1.0, 0.0, color),
+			vertex(left, bottom, 0.0, 1.0, color),
+			vertex(right, bottom, 1.0, 1.0, color)
+		], &[0, 1, 2, 2, 1, 3]);
+	}
+
+	fn pixel(pixels: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+		let index = (y * width + x) as usize * 4;
+		[pixels[index], pixels[index + 1], pixels[index + 2], pixels[index + 3]]
+	}
+
+	/// Shows which pixels have been drawn, one string per row.
+	fn coverage(image: &Image) -> Vec<String> {
+		image.get_data().chunks(image.get_width() as usize * 4)
+			.map(|row| row.chunks(4).map(|pixel| if pixel[3] > 0 {'#'} else {'.'}).collect())
+			.collect()
+	}
+
+	#[test]
+	fn fill_rule_includes_top_and_left_edges() {
+		let mut backend = SoftwareBackend::new(4, 3);
+		// Every edge passes through pixel centers, but only the top and left edges are drawn.
+		backend.draw(&state(0, BlendMode::Alpha), &[
+			vertex(0.5, 0.5, 0.0, 0.0, WHITE),
+			vertex(2.5, 0.5, 0.0, 0.0, WHITE),
+			vertex(0.5, 2.5, 0.0, 0.0, WHITE)
+		], &[0, 1, 2]);
+		assert_eq!(coverage(&backend.read_pixels()), vec!["##..", "#...", "...."]);
+	}
+
+	#[test]
+	fn shared_edges_are_drawn_once() {
+		let mut backend = SoftwareBackend::new(4, 4);
+		let color = Color8 {red: 255, green: 255, blue: 255, alpha: 128};
+		quad(&mut backend, &state(0, BlendMode::Alpha), Rect::new(0.0, 0.0, 4.0, 4.0), color);
+		for pixel in backend.get_pixels().chunks(4) {
+			assert_eq!(pixel, &[128, 128, 128, 128]);
+		}
+	}
+
+	#[test]
+	fn textures_are_sampled_with_the_filter() {
+		let image = Image::from_rgba(vec![0, 0, 0, 255, 255, 255, 255, 255], 2, 1).unwrap();
+		let mut backend = SoftwareBackend::new(4, 2);
+		let nearest = backend.create_texture(&image);
+		let linear = backend.create_texture_with_filter(&image, TextureFilter::Linear);
+		quad(&mut backend, &state(nearest, BlendMode::Alpha), Rect::new(0.0, 0.0, 4.0, 1.0), WHITE);
+		quad(&mut backend, &state(linear, BlendMode::Alpha), Rect::new(0.0, 1.0, 4.0, 1.0), WHITE);
+
+		let red = |x, y| pixel(backend.get_pixels(), 4, x, y)[0];
+		assert_eq!([red(0, 0), red(1, 0), red(2, 0), red(3, 0)], [0, 0, 255, 255]);
+		assert_eq!([red(0, 1), red(1, 1), red(2, 1), red(3, 1)], [0, 64, 191, 255]);
+	}
+
+	#[test]
+	fn blend_modes_match_the_gl_equations() {
+		let source = Color8 {red: 200, green: 100, blue: 50, alpha: 128};
+		let cases = [
+			(BlendMode::Alpha, [150, 125, 125, 255]),
+			(BlendMode::Premultiplied, [250, 175, 150, 255]),
+			(BlendMode::Additive, [200, 200, 225, 255]),
+			(BlendMode::Multiply, [89, 104, 119, 255]),
+			(BlendMode::Screen, [161, 171, 205, 255]),
+			(BlendMode::Replace, [200, 100, 50, 128])
+		];
+		for &(blend, expected) in &cases {
+			let mut backend = SoftwareBackend::new(1, 1);
+			backend.pixels.copy_from_slice(&[100, 150, 200, 255]);
+			quad(&mut backend, &state(0, blend), Rect::new(0.0, 0.0, 1.0, 1.0), source);
+			assert_eq!(pixel(backend.get_pixels(), 1, 0, 0), expected, "{:?}", blend);
+		}
+	}
+
+	#[test]
+	fn clip_limits_drawing() {
+		let mut graphics = Graphics::software(4, 4);
+		graphics.clip(Rect::new(1.0, 1.0, 2.0, 2.0));
+		graphics.rect(Rect::new(0.0, 0.0, 4.0, 4.0));
+		assert_eq!(coverage(&graphics.read_pixels()), vec!["....", ".##.", ".##.", "...."]);
+	}
+
+	#[test]
+	fn masks_draw_inside_or_outside() {
+		let mut graphics = Graphics::software(4, 2);
+		graphics.begin_mask();
+		graphics.rect(Rect::new(0.0, 0.0, 1.0, 2.0));
+		graphics.end_mask();
+		graphics.rect(Rect::new(0.0, 0.0, 2.0, 1.0));
+		graphics.mask(MaskMode::Outside);
+		graphics.rect(Rect::new(2.0, 1.0, 2.0, 1.0));
+		graphics.rect(Rect::new(0.0, 1.0, 1.0, 1.0));
+		assert_eq!(coverage(&graphics.read_pixels()), vec!["#...", "..##"]);
+	}
+
+	#[test]
+	fn huge_and_infinite_points_are_safe() {
+		let mut backend = SoftwareBackend::new(4, 2);
+		let state = state(0, BlendMode::Alpha);
+		// A triangle far larger than the target still covers it.
+		backend.draw(&state, &[
+			vertex(-1e15, -1e15, 0.0, 0.0, WHITE),
+			vertex(3e15, -1e15, 0.0, 0.0, WHITE),
+			vertex(-1e15, 3e15, 0.0, 0.0, WHITE)
+		], &[0, 1, 2]);
+		assert_eq!(coverage(&backend.read_pixels()), vec!["####", "####"]);
+
+		let mut backend = SoftwareBackend::new(4, 2);
+		quad(&mut backend, &state, Rect::new(-2e7, -2e7, 4e7, 4e7), WHITE);
+		assert_eq!(coverage(&backend.read_pixels()), vec!["####", "####"]);
+
+		// Points beyond the largest coordinate are clamped instead of overflowing.
+		let mut backend = SoftwareBackend::new(4, 2);
+		backend.draw(&state, &[
+			vertex(-1e30, -1e30, 0.0, 0.0, WHITE),
+			vertex(1e30, 1e30, 0.0, 0.0, WHITE),
+			vertex(1e30, 0.0, 0.0, 0.0, WHITE)
+		], &[0, 1, 2]);
+		assert_eq!(coverage(&backend.read_pixels()), vec!["####", ".###"]);
+
+		let mut backend = SoftwareBackend::new(4, 2);
+		backend.draw(&state, &[
+			vertex(0.0, 0.0, 0.0, 0.0, WHITE),
+			vertex(::std::f32::INFINITY, 0.0, 0.0, 0.0, WHITE),
+			vertex(0.0, 4.0, 0.0, 0.0, WHITE),
+			vertex(::std::f32::NAN, 4.0, 0.0, 0.0, WHITE)
+		], &[0, 1, 2, 0, 2, 3]);
+		assert_eq!(coverage(&backend.read_pixels()), vec!["....", "...."]);
+	}
+}
\ No newline at end of file
@@ -12,86 +12,99 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ::{Size, Point};
+use ::{Size, Point, Rect};
+use super::{Color, Angle, Stroke, Gradient, BlendMode, MaskMode};
 
-use std::f64::consts::PI;
+use std::rc::Rc;
 
-const RAD_PER_DEG: f64 = PI / 180.0;
+#[derive(Clone, PartialEq, Debug)]
+pub struct State {
+	color: Color,
+	gradient: Option<Rc<Gradient>>,
+	blend: BlendMode,
+	clip: Option<Rect>,
+	mask: MaskMode,
+	stroke: Stroke,
+	t0: (f64, f64, f64),
+	t1: (f64, f64, f64)
+}
 
-/// An RGBA color of something.
-#[derive(Copy, Clone, PartialEq, Debug)]
-pub struct Color {
-	/// The red channel.
-	/// Should be between 0 and 1.
-	pub red: f64,
+impl State {
+	pub fn color(&mut self, color: Color) {
+		self.color = color
+	}
 
-	/// The green channel.
-	/// Should be between 0 and 1.
-	pub green: f64,
+	pub fn tint(&mut self, color: Color) {
+		self.color *= color;
+	}
 
-	/// The blue channel.
-	/// Should be between 0 and 1.
-	pub blue: f64,
+	pub fn get_color(&self) -> Color {
+		self.color
+	}
 
-	/// The alpha channel.
-	/// Should be between 0 and 1.
-	pub alpha: f64
-}
+	pub fn gradient(&mut self, gradient: Option<Rc<Gradient>>) {
+		self.gradient = gradient;
+	}
 
-impl Color {
-	/// A utility method that makes it neater to create a color.
-	pub fn new(red: f64, green: f64, blue: f64, alpha: f64) -> Color {
-		Color {red, green, blue, alpha}
+	pub fn get_gradient(&self) -> Option<&Rc<Gradient>> {
+		self.gradient.as_ref()
 	}
-}
 
-/// An angle of something, either in degrees or radians.
-#[derive(Copy, Clone, PartialEq, Debug)]
-pub struct Angle(f64);
+	pub fn blend(&mut self, blend: BlendMode) {
+		self.blend = blend;
+	}
 
-impl Angle {
-	/// Creates an angle from radians.
-	pub fn from_radians(rad: f64) -> Angle {
-		Angle(rad)
+	pub fn get_blend(&self) -> BlendMode {
+		self.blend
 	}
 
-	/// Creates an angle from degrees.
-	pub fn from_degrees(deg: f64) -> Angle {
-		Angle(deg * RAD_PER_DEG)
+	/// Intersects the clip with the bounds of the transformed rectangle,
+	/// rounded to whole pixels.
+	pub fn clip(&mut self, rect: Rect) {
+		let corners = [
+			self.transform(rect.top_left()),
+			self.transform(rect.top_right()),
+			self.transform(rect.bottom_left()),
+			self.transform(rect.bottom_right())
+		];
+		let left = corners.iter().map(|point| point.x).fold(f64::INFINITY, f64::min).round();
+		let top = corners.iter().map(|point| point.y).fold(f64::INFINITY, f64::min).round();
+		let right = corners.iter().map(|point| point.x).fold(f64::NEG_INFINITY, f64::max).round();
+		let bottom = corners.iter().map(|point| point.y).fold(f64::NEG_INFINITY, f64::max).round();
+		let bounds = Rect::from_points(Point {x: left, y: top}, Point {x: right, y: bottom});
+
+		self.clip = Some(match self.clip {
+			Some(clip) => clip.intersection(&bounds).unwrap_or(Rect::new(0.0, 0.0, 0.0, 0.0)),
+			None => bounds
+		});
 	}
 
-	/// Converts an angle to radians.
-	pub fn as_radians(&self) -> f64 {
-		self.0
+	pub fn reset_clip(&mut self) {
+		self.clip = None;
 	}
 
-	/// Converts an angle to degrees.
-	pub fn as_degrees(&self) -> f64 {
-		self.0 / RAD_PER_DEG
+	pub fn get_clip(&self) -> Option<Rect> {
+		self.clip
 	}
-}
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-pub struct State {
-	color: Color,
-	t0: (f64, f64, f64),
-	t1: (f64, f64, f64)
-}
+	pub fn mask(&mut self, mask: MaskMode) {
+		self.mask = mask;
+	}
 
-impl State {
-	pub fn color(&mut self, color: Color) {
-		self.color = color
+	pub fn get_mask(&self) -> MaskMode {
+		self.mask
 	}
 
-	pub fn tint(&mut self, color: Color) {
-		self.color.red   *= color.red;
-		self.color.green *= color.green;
-		self.color.blue  *= color.blue;
-		self.color.alpha *= color.alpha;
+	pub fn stroke(&mut self, stroke: Stroke) {
+		self.stroke = stroke;
 	}
 
-	pub fn get_color(&self) -> Color {
-		self.color
+	pub fn line_width(&mut self, width: f64) {
+		self.stroke.width = width;
+	}
+
+	pub fn get_stroke(&self) -> Stroke {
+		self.stroke
 	}
 
 	pub fn identity(&mut self) {
@@ -115,10 +128,22 @@ impl State {
 	pub fn rotate(&mut self, angle: Angle) {
 		let c = angle.as_radians().cos();
 		let s = angle.as_radians().sin();
-		self.t0.0 = self.t0.0 * c + self.t0.1 * s;
-		self.t0.1 = self.t0.1 * c - self.t0.0 * s;
-		self.t1.0 = self.t1.0 * c + self.t1.1 * s;
-		self.t1.1 = self.t1.1 * c - self.t1.0 * s;
+		let (t0, t1) = (self.t0, self.t1);
+		self.t0.0 = t0.0 * c + t0.1 * s;
+		self.t0.1 = t0.1 * c - t0.0 * s;
+		self.t1.0 = t1.0 * c + t1.1 * s;
+		self.t1.1 = t1.1 * c - t1.0 * s;
+	}
+
+	/// The most the transform scales by in any direction,
+	/// used to choose how finely curves are split.
+	pub fn get_scale(&self) -> f64 {
+		// The largest singular value of the 2x2 part of the matrix.
+		let (a, b, c, d) = (self.t0.0, self.t0.1, self.t1.0, self.t1.1);
+		let sum = a * a + b * b + c * c + d * d;
+		let determinant = a * d - b * c;
+		let discriminant = (sum * sum - 4.0 * determinant * determinant).max(0.0);
+		((sum + discriminant.sqrt()) / 2.0).sqrt()
 	}
 
 	pub fn transform(&self, point: Point) -> Point {
@@ -132,14 +157,62 @@ impl State {
 impl Default for State {
 	fn default() -> State {
 		State {
-			color: Color {
-				red:   1.0,
-				green: 1.0,
-				blue:  1.0,
-				alpha: 1.0
-			},
+			color: Color::WHITE,
+			gradient: None,
+			blend: BlendMode::Alpha,
+			clip: None,
+			mask: MaskMode::Disabled,
+			stroke: Stroke::default(),
 			t0: (1.0, 0.0, 0.0),
 			t1: (0.0, 1.0, 0.0)
 		}
 	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_near(a: f64, b: f64) {
+		assert!((a - b).abs() < 1e-9, "{} is not {}", a, b);
+	}
+
+	#[test]
+	fn scale_is_the_largest_stretch() {
+		let mut state = State::default();
+		assert_near(state.get_scale(), 1.0);
+
+		state.scale(Size {width: 4.0, height: 0.25});
+		assert_near(state.get_scale(), 4.0);
+
+		// Rotating doesn't change how much the transform stretches.
+		state.rotate(Angle::from_degrees(30.0));
+		assert_near(state.get_scale(), 4.0);
+
+		// A degenerate transform still stretches along one axis.
+		let mut state = State::default();
+		state.scale(Size {width: 3.0, height: 0.0});
+		assert_near(state.get_scale(), 3.0);
+	}
+
+	#[test]
+	fn rotate_uses_the_transform_before_it() {
+		// Each component has to be computed from the transform before the rotation,
+		// not from components that were already rotated.
+		let mut state = State::default();
+		state.scale(Size {width: 2.0, height: 1.0});
+		state.rotate(Angle::from_degrees(90.0));
+		let point = state.transform(Point {x: 0.0, y: 1.0});
+		assert_near(point.x, -2.0);
+		assert_near(point.y, 0.0);
+
+		let mut halves = State::default();
+		halves.rotate(Angle::from_degrees(45.0));
+		halves.rotate(Angle::from_degrees(45.0));
+		let mut whole = State::default();
+		whole.rotate(Angle::from_degrees(90.0));
+		let point = Point {x: 3.0, y: -1.0};
+		assert_near(halves.transform(point).x, whole.transform(point).x);
+		assert_near(halves.transform(point).y, whole.transform(point).y);
+	}
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ::Size;
+use super::GraphicsImpl;
+
+use std::rc::{Rc, Weak};
+use std::cell::RefCell;
+
+/// An image that has been uploaded so it can be drawn.
+/// Created with [`Graphics.create_texture`](struct.Graphics.html#method.create_texture).
+pub struct Texture {
+	graphics: Weak<RefCell<GraphicsImpl>>,
+	id: u32,
+	size: Size
+}
+
+impl Texture {
+	pub(crate) fn new(graphics: Weak<RefCell<GraphicsImpl>>, id: u32, size: Size) -> Texture {
+		Texture {graphics, id, size}
+	}
+
+	pub(crate) fn get_id(&self) -> u32 {
+		self.id
+	}
+
+	/// Checks if the texture was created by the graphics.
+	pub(crate) fn is_from(&self, graphics: &Rc<RefCell<GraphicsImpl>>) -> bool {
+		self.graphics.upgrade().map_or(false, |rc| Rc::ptr_eq(&rc, graphics))
+	}
+
+	/// Gets the size of the texture in pixels.
+	pub fn get_size(&self) -> Size {
+		self.size
+	}
+}
+
+impl Drop for Texture {
+	fn drop(&mut self) {
+		if let Some(graphics) = self.graphics.upgrade() {
+			graphics.borrow_mut().delete_texture(self.id);
+		}
+	}
+}
@@ -0,0 +1,1366 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate backtrace;
+use self::backtrace::Backtrace;
+
+use ::Point;
+use super::{Path, GraphicsError};
+
+// How deep composite glyphs and subroutines can nest, which stops loops in broken fonts.
+const MAX_DEPTH: usize = 10;
+
+fn invalid(error: &str) -> GraphicsError {
+	GraphicsError::InvalidFont(error.to_string(), Backtrace::new())
+}
+
+fn read_u8(data: &[u8], offset: usize) -> Option<u8> {
+	data.get(offset).cloned()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+	Some((read_u8(data, offset)? as u16) << 8 | read_u8(data, offset + 1)? as u16)
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+	read_u16(data, offset).map(|value| value as i16)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+	Some((read_u16(data, offset)? as u32) << 16 | read_u16(data, offset + 2)? as u32)
+}
+
+/// Reads a big-endian number of 1 to 4 bytes.
+fn read_sized(data: &[u8], offset: usize, size: usize) -> Option<usize> {
+	(0..size).fold(Some(0), |value, index| Some(value? << 8 | read_u8(data, offset + index)? as usize))
+}
+
+/// Applies a 2D affine transform, ordered `[xx, xy, yx, yy, x, y]`.
+fn apply(transform: &[f64; 6], x: f64, y: f64) -> Point {
+	Point {
+		x: transform[0] * x + transform[2] * y + transform[4],
+		y: transform[1] * x + transform[3] * y + transform[5]
+	}
+}
+
+/// A CFF INDEX, which is a list of byte ranges.
+#[derive(Copy, Clone, Default, Debug)]
+struct Index {
+	offset: usize,
+	count: usize,
+	offset_size: usize,
+	// Where the data starts, minus one since offsets start at 1.
+	data: usize,
+	end: usize
+}
+
+impl Index {
+	fn new(data: &[u8], offset: usize) -> Option<Index> {
+		let count = read_u16(data, offset)? as usize;
+		if count == 0 {
+			return Some(Index {offset, end: offset + 2, ..Index::default()});
+		}
+		let offset_size = read_u8(data, offset + 2)? as usize;
+		let mut index = Index {
+			offset,
+			count,
+			offset_size,
+			data: offset + 2 + (count + 1) * offset_size,
+			end: 0
+		};
+		index.end = index.data + read_sized(data, offset + 3 + count * offset_size, offset_size)?;
+		Some(index)
+	}
+
+	fn get<'a>(&self, data: &'a [u8], index: usize) -> Option<&'a [u8]> {
+		if index >= self.count {
+			return None;
+		}
+		let start = read_sized(data, self.offset + 3 + index * self.offset_size, self.offset_size)?;
+		let end = read_sized(data, self.offset + 3 + (index + 1) * self.offset_size, self.offset_size)?;
+		data.get(self.data + start..self.data + end)
+	}
+
+	/// The amount added to subroutine numbers.
+	fn bias(&self) -> i32 {
+		match self.count {
+			0..=1239 => 107,
+			1240..=33899 => 1131,
+			_ => 32768
+		}
+	}
+}
+
+/// Parses the operands and operators of a CFF DICT.
+fn parse_dict(data: &[u8]) -> Vec<(u16, Vec<f64>)> {
+	let mut entries = Vec::new();
+	let mut operands = Vec::new();
+	let mut offset = 0;
+	while offset < data.len() {
+		let byte = data[offset];
+		offset += 1;
+		let byte1 = || data.get(offset).cloned().unwrap_or(0) as f64;
+		match byte {
+			0..=11 | 13..=21 => entries.push((byte as u16, operands.split_off(0))),
+			12 => {
+				entries.push((1200 + byte1() as u16, operands.split_off(0)));
+				offset += 1;
+			},
+			28 => {
+				operands.push(read_i16(data, offset).unwrap_or(0) as f64);
+				offset += 2;
+			},
+			29 => {
+				operands.push(read_u32(data, offset).unwrap_or(0) as i32 as f64);
+				offset += 4;
+			},
+			30 => {
+				// Real numbers are stored as nibbles, which are only skipped
+				// since no operators that are used need them.
+				while offset < data.len() {
+					let nibbles = data[offset];
+					offset += 1;
+					if nibbles & 0x0F == 0x0F || nibbles & 0xF0 == 0xF0 {
+						break;
+					}
+				}
+				operands.push(0.0);
+			},
+			32..=246 => operands.push(byte as f64 - 139.0),
+			247..=250 => {
+				operands.push((byte as f64 - 247.0) * 256.0 + byte1() + 108.0);
+				offset += 1;
+			},
+			251..=254 => {
+				operands.push(-(byte as f64 - 251.0) * 256.0 - byte1() - 108.0);
+				offset += 1;
+			},
+			_ => ()
+		}
+	}
+	entries
+}
+
+fn dict_get(dict: &[(u16, Vec<f64>)], operator: u16) -> Option<&[f64]> {
+	dict.iter()
+		.find(|entry| entry.0 == operator)
+		.map(|entry| entry.1.as_slice())
+}
+
+/// The outlines of a CFF font, used by most `.otf` files.
+#[derive(Clone, Debug)]
+struct Cff {
+	char_strings: Index,
+	global_subrs: Index,
+	// The local subroutines of each font dict.
+	local_subrs: Vec<Index>,
+	// Which font dict each glyph uses, for CID fonts.
+	fd_select: Option<usize>
+}
+
+impl Cff {
+	fn new(data: &[u8], cff: usize) -> Option<Cff> {
+		let header_size = read_u8(data, cff + 2)? as usize;
+		let names = Index::new(data, cff + header_size)?;
+		let top_dicts = Index::new(data, names.end)?;
+		let strings = Index::new(data, top_dicts.end)?;
+		let global_subrs = Index::new(data, strings.end)?;
+		let top_dict = parse_dict(top_dicts.get(data, 0)?);
+
+		let char_strings = Index::new(data, cff + *dict_get(&top_dict, 17)?.get(0)? as usize)?;
+		let private_subrs = |private: &[f64]| -> Option<Index> {
+			let (size, offset) = (*private.get(0)? as usize, cff + *private.get(1)? as usize);
+			let private_dict = parse_dict(data.get(offset..offset + size)?);
+			match dict_get(&private_dict, 19) {
+				Some(subrs) => Index::new(data, offset + *subrs.get(0)? as usize),
+				None => Some(Index::default())
+			}
+		};
+
+		let (local_subrs, fd_select) = match dict_get(&top_dict, 1236) {
+			Some(fd_array) => {
+				let fd_array = Index::new(data, cff + *fd_array.get(0)? as usize)?;
+				let mut local_subrs = Vec::new();
+				for index in 0..fd_array.count {
+					let font_dict = parse_dict(fd_array.get(data, index)?);
+					local_subrs.push(match dict_get(&font_dict, 18) {
+						Some(private) => private_subrs(private)?,
+						None => Index::default()
+					});
+				}
+				let fd_select = cff + *dict_get(&top_dict, 1237)?.get(0)? as usize;
+				(local_subrs, Some(fd_select))
+			},
+			None => {
+				let subrs = match dict_get(&top_dict, 18) {
+					Some(private) => private_subrs(private)?,
+					None => Index::default()
+				};
+				(vec![subrs], None)
+			}
+		};
+
+		Some(Cff {char_strings, global_subrs, local_subrs, fd_select})
+	}
+
+	fn font_dict(&self, data: &[u8], glyph: u16) -> Option<usize> {
+		let fd_select = match self.fd_select {
+			Some(fd_select) => fd_select,
+			None => return Some(0)
+		};
+		match read_u8(data, fd_select)? {
+			0 => Some(read_u8(data, fd_select + 1 + glyph as usize)? as usize),
+			3 => {
+				let ranges = read_u16(data, fd_select + 1)? as usize;
+				for range in 0..ranges {
+					let offset = fd_select + 3 + range * 3;
+					let next = read_u16(data, offset + 3)?;
+					if glyph >= read_u16(data, offset)? && glyph < next {
+						return Some(read_u8(data, offset + 2)? as usize);
+					}
+				}
+				None
+			},
+			_ => None
+		}
+	}
+
+	fn outline(&self, data: &[u8], glyph: u16, path: &mut Path, transform: &[f64; 6]) -> Option<()> {
+		let char_string = self.char_strings.get(data, glyph as usize)?;
+		let local_subrs = self.local_subrs.get(self.font_dict(data, glyph)?)?;
+		let mut interpreter = CharString {
+			data,
+			global_subrs: &self.global_subrs,
+			local_subrs,
+			path,
+			transform,
+			stack: Vec::new(),
+			stems: 0,
+			width_parsed: false,
+			open: false,
+			point: (0.0, 0.0)
+		};
+		interpreter.run(char_string, 0)?;
+		interpreter.close();
+		Some(())
+	}
+}
+
+/// Runs a Type 2 charstring to build the outline of a glyph.
+struct CharString<'a> {
+	data: &'a [u8],
+	global_subrs: &'a Index,
+	local_subrs: &'a Index,
+	path: &'a mut Path,
+	transform: &'a [f64; 6],
+	stack: Vec<f64>,
+	stems: usize,
+	width_parsed: bool,
+	open: bool,
+	point: (f64, f64)
+}
+
+impl<'a> CharString<'a> {
+	/// The first operator that clears the stack can have the width before its operands,
+	/// which is removed since the width is read from the metrics instead.
+	fn skip_width(&mut self, has_width: bool) {
+		if !self.width_parsed && has_width && !self.stack.is_empty() {
+			self.stack.remove(0);
+		}
+		self.width_parsed = true;
+	}
+
+	fn close(&mut self) {
+		if self.open {
+			self.path.close();
+			self.open = false;
+		}
+	}
+
+	fn move_to(&mut self, dx: f64, dy: f64) {
+		self.close();
+		self.point = (self.point.0 + dx, self.point.1 + dy);
+		self.path.move_to(apply(self.transform, self.point.0, self.point.1));
+		self.open = true;
+	}
+
+	fn line_to(&mut self, dx: f64, dy: f64) {
+		self.point = (self.point.0 + dx, self.point.1 + dy);
+		self.path.line_to(apply(self.transform, self.point.0, self.point.1));
+	}
+
+	fn curve_to(&mut self, d: [f64; 6]) {
+		let (x, y) = self.point;
+		let control1 = (x + d[0], y + d[1]);
+		let control2 = (control1.0 + d[2], control1.1 + d[3]);
+		self.point = (control2.0 + d[4], control2.1 + d[5]);
+		self.path.cubic_to(
+			apply(self.transform, control1.0, control1.1),
+			apply(self.transform, control2.0, control2.1),
+			apply(self.transform, self.point.0, self.point.1)
+		);
+	}
+
+	/// Curves that alternate between starting horizontally and vertically.
+	fn alternating_curves(&mut self, mut horizontal: bool) {
+		let stack = self.stack.split_off(0);
+		let mut index = 0;
+		while index + 4 <= stack.len() {
+			let last = if stack.len() - index == 5 {stack[index + 4]} else {0.0};
+			let s = &stack[index..];
+			if horizontal {
+				self.curve_to([s[0], 0.0, s[1], s[2], last, s[3]]);
+			} else {
+				self.curve_to([0.0, s[0], s[1], s[2], s[3], last]);
+			}
+			index += 4;
+			horizontal = !horizontal;
+		}
+	}
+
+	fn run(&mut self, code: &[u8], depth: usize) -> Option<bool> {
+		if depth > MAX_DEPTH {
+			return None;
+		}
+
+		let mut offset = 0;
+		while offset < code.len() {
+			let byte = code[offset];
+			offset += 1;
+			match byte {
+				32..=246 => self.stack.push(byte as f64 - 139.0),
+				247..=250 => {
+					self.stack.push((byte as f64 - 247.0) * 256.0 + *code.get(offset)? as f64 + 108.0);
+					offset += 1;
+				},
+				251..=254 => {
+					self.stack.push(-(byte as f64 - 251.0) * 256.0 - *code.get(offset)? as f64 - 108.0);
+					offset += 1;
+				},
+				28 => {
+					self.stack.push(read_i16(code, offset)? as f64);
+					offset += 2;
+				},
+				255 => {
+					self.stack.push(read_u32(code, offset)? as i32 as f64 / 65536.0);
+					offset += 4;
+				},
+				// hstem, vstem, hstemhm and vstemhm
+				1 | 3 | 18 | 23 => {
+					let has_width = self.stack.len() % 2 == 1;
+					self.skip_width(has_width);
+					self.stems += self.stack.len() / 2;
+					self.stack.clear();
+				},
+				// hintmask and cntrmask
+				19 | 20 => {
+					let has_width = self.stack.len() % 2 == 1;
+					self.skip_width(has_width);
+					self.stems += self.stack.len() / 2;
+					self.stack.clear();
+					offset += (self.stems + 7) / 8;
+				},
+				// rmoveto
+				21 => {
+					let has_width = self.stack.len() > 2;
+					self.skip_width(has_width);
+					self.move_to(*self.stack.get(0)?, *self.stack.get(1)?);
+					self.stack.clear();
+				},
+				// hmoveto
+				22 => {
+					let has_width = self.stack.len() > 1;
+					self.skip_width(has_width);
+					self.move_to(*self.stack.get(0)?, 0.0);
+					self.stack.clear();
+				},
+				// vmoveto
+				4 => {
+					let has_width = self.stack.len() > 1;
+					self.skip_width(has_width);
+					self.move_to(0.0, *self.stack.get(0)?);
+					self.stack.clear();
+				},
+				// rlineto
+				5 => {
+					for pair in self.stack.split_off(0).chunks(2).filter(|pair| pair.len() == 2) {
+						self.line_to(pair[0], pair[1]);
+					}
+				},
+				// hlineto and vlineto
+				6 | 7 => {
+					let mut horizontal = byte == 6;
+					for &distance in &self.stack.split_off(0) {
+						if horizontal {
+							self.line_to(distance, 0.0);
+						} else {
+							self.line_to(0.0, distance);
+						}
+						horizontal = !horizontal;
+					}
+				},
+				// rrcurveto
+				8 => {
+					for d in self.stack.split_off(0).chunks(6).filter(|d| d.len() == 6) {
+						self.curve_to([d[0], d[1], d[2], d[3], d[4], d[5]]);
+					}
+				},
+				// rcurveline
+				24 => {
+					let stack = self.stack.split_off(0);
+					let curves = stack.len().saturating_sub(2) / 6 * 6;
+					for d in stack[..curves].chunks(6) {
+						self.curve_to([d[0], d[1], d[2], d[3], d[4], d[5]]);
+					}
+					self.line_to(*stack.get(curves)?, *stack.get(curves + 1)?);
+				},
+				// rlinecurve
+				25 => {
+					let stack = self.stack.split_off(0);
+					let lines = stack.len().saturating_sub(6) / 2 * 2;
+					for pair in stack[..lines].chunks(2) {
+						self.line_to(pair[0], pair[1]);
+					}
+					let d = stack.get(lines..lines + 6)?;
+					self.curve_to([d[0], d[1], d[2], d[3], d[4], d[5]]);
+				},
+				// vvcurveto
+				26 => {
+					let stack = self.stack.split_off(0);
+					let mut dx = if stack.len() % 4 == 1 {stack[0]} else {0.0};
+					for d in stack[stack.len() % 4..].chunks(4) {
+						self.curve_to([dx, d[0], d[1], d[2], 0.0, d[3]]);
+						dx = 0.0;
+					}
+				},
+				// hhcurveto
+				27 => {
+					let stack = self.stack.split_off(0);
+					let mut dy = if stack.len() % 4 == 1 {stack[0]} else {0.0};
+					for d in stack[stack.len() % 4..].chunks(4) {
+						self.curve_to([d[0], dy, d[1], d[2], d[3], 0.0]);
+						dy = 0.0;
+					}
+				},
+				// vhcurveto and hvcurveto
+				30 => self.alternating_curves(false),
+				31 => self.alternating_curves(true),
+				// callsubr and callgsubr
+				10 | 29 => {
+					let subrs = if byte == 10 {self.local_subrs} else {self.global_subrs};
+					let number = self.stack.pop()? as i32 + subrs.bias();
+					let subr = subrs.get(self.data, number as usize)?;
+					if self.run(subr, depth + 1)? {
+						return Some(true);
+					}
+				},
+				// return
+				11 => return Some(false),
+				// endchar
+				14 => {
+					let has_width = self.stack.len() == 1 || self.stack.len() == 5;
+					self.skip_width(has_width);
+					self.stack.clear();
+					return Some(true);
+				},
+				12 => {
+					let escape = *code.get(offset)?;
+					offset += 1;
+					let s = self.stack.split_off(0);
+					match escape {
+						// flex
+						35 if s.len() >= 12 => {
+							self.curve_to([s[0], s[1], s[2], s[3], s[4], s[5]]);
+							self.curve_to([s[6], s[7], s[8], s[9], s[10], s[11]]);
+						},
+						// hflex
+						34 if s.len() >= 7 => {
+							self.curve_to([s[0], 0.0, s[1], s[2], s[3], 0.0]);
+							self.curve_to([s[4], 0.0, s[5], -s[2], s[6], 0.0]);
+						},
+						// hflex1
+						36 if s.len() >= 9 => {
+							self.curve_to([s[0], s[1], s[2], s[3], s[4], 0.0]);
+							self.curve_to([s[5], 0.0, s[6], s[7], s[8], -(s[1] + s[3] + s[7])]);
+						},
+						// flex1
+						37 if s.len() >= 11 => {
+							let dx = s[0] + s[2] + s[4] + s[6] + s[8];
+							let dy = s[1] + s[3] + s[5] + s[7] + s[9];
+							let (dx6, dy6) = if dx.abs() > dy.abs() {(s[10], -dy)} else {(-dx, s[10])};
+							self.curve_to([s[0], s[1], s[2], s[3], s[4], s[5]]);
+							self.curve_to([s[6], s[7], s[8], s[9], dx6, dy6]);
+						},
+						_ => ()
+					}
+				},
+				_ => self.stack.clear()
+			}
+		}
+		Some(false)
+	}
+}
+
+#[derive(Clone, Debug)]
+enum Outlines {
+	Glyf {
+		loca: usize,
+		glyf: usize,
+		long_offsets: bool
+	},
+	Cff(Cff)
+}
+
+/// A parsed TrueType or OpenType font.
+#[derive(Clone, Debug)]
+pub(crate) struct TrueType {
+	data: Vec<u8>,
+	units_per_em: f64,
+	ascent: f64,
+	descent: f64,
+	line_gap: f64,
+	glyph_count: u16,
+	metric_count: u16,
+	hmtx: usize,
+	cmap: usize,
+	// The pairs of a format 0 kerning subtable, and how many there are.
+	kern: Option<(usize, usize)>,
+	outlines: Outlines
+}
+
+impl TrueType {
+	/// Parses the font, using the first font of a collection.
+	pub fn new(data: Vec<u8>) -> Result<TrueType, GraphicsError> {
+		let error = || invalid("Invalid TrueType or OpenType font data");
+		let font = match data.get(0..4) {
+			Some(b"ttcf") => read_u32(&data, 12).ok_or_else(error)? as usize,
+			Some(_) => 0,
+			None => return Err(error())
+		};
+
+		let table_count = read_u16(&data, font + 4).ok_or_else(error)? as usize;
+		let table = |tag: &[u8]| -> Option<usize> {
+			(0..table_count)
+				.map(|index| font + 12 + index * 16)
+				.find(|&record| data.get(record..record + 4) == Some(tag))
+				.and_then(|record| read_u32(&data, record + 8))
+				.map(|offset| offset as usize)
+		};
+
+		let head = table(b"head").ok_or_else(error)?;
+		let hhea = table(b"hhea").ok_or_else(error)?;
+		let units_per_em = read_u16(&data, head + 18).ok_or_else(error)? as f64;
+		if units_per_em == 0.0 {
+			return Err(error());
+		}
+
+		let outlines = match (table(b"glyf"), table(b"CFF ")) {
+			(Some(glyf), _) => Outlines::Glyf {
+				loca: table(b"loca").ok_or_else(error)?,
+				glyf,
+				long_offsets: read_i16(&data, head + 50).ok_or_else(error)? != 0
+			},
+			(None, Some(cff)) => Outlines::Cff(Cff::new(&data, cff).ok_or_else(error)?),
+			(None, None) => return Err(invalid("Font has no glyph outlines"))
+		};
+
+		let cmap = table(b"cmap")
+			.and_then(|cmap| TrueType::find_cmap(&data, cmap))
+			.ok_or_else(|| invalid("Font has no Unicode character map"))?;
+
+		let kern = table(b"kern").and_then(|kern| {
+			let tables = read_u16(&data, kern + 2)?;
+			let mut offset = kern + 4;
+			for _ in 0..tables {
+				let length = read_u16(&data, offset + 2)? as usize;
+				let coverage = read_u16(&data, offset + 4)?;
+				// Format 0 with horizontal kerning values.
+				if coverage >> 8 == 0 && coverage & 0x07 == 0x01 {
+					return Some((offset + 14, read_u16(&data, offset + 6)? as usize));
+				}
+				offset += length;
+			}
+			None
+		});
+
+		let ascent = read_i16(&data, hhea + 4).ok_or_else(error)? as f64;
+		let descent = read_i16(&data, hhea + 6).ok_or_else(error)? as f64;
+		let line_gap = read_i16(&data, hhea + 8).ok_or_else(error)? as f64;
+		let metric_count = read_u16(&data, hhea + 34).ok_or_else(error)?;
+		let glyph_count = table(b"maxp").and_then(|maxp| read_u16(&data, maxp + 4)).ok_or_else(error)?;
+		let hmtx = table(b"hmtx").ok_or_else(error)?;
+
+		Ok(TrueType {
+			data,
+			units_per_em,
+			ascent,
+			descent,
+			line_gap,
+			glyph_count,
+			metric_count,
+			hmtx,
+			cmap,
+			kern,
+			outlines
+		})
+	}
+
+	/// Finds a Unicode subtable in a format that is supported.
+	fn find_cmap(data: &[u8], cmap: usize) -> Option<usize> {
+		let tables = read_u16(data, cmap + 2)?;
+		let mut best = None;
+		for index in 0..tables {
+			let record = cmap + 4 + index as usize * 8;
+			let platform = read_u16(data, record)?;
+			let encoding = read_u16(data, record + 2)?;
+			let subtable = cmap + read_u32(data, record + 4)? as usize;
+			let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+			match read_u16(data, subtable)? {
+				12 if unicode => return Some(subtable),
+				4 if unicode => best = Some(subtable),
+				_ => ()
+			}
+		}
+		best
+	}
+
+	/// Gets the scale from font units to pixels for a size in pixels per em.
+	pub fn get_scale(&self, size: f64) -> f64 {
+		size / self.units_per_em
+	}
+
+	/// Gets the distance from the baseline to the top of the tallest glyphs, in font units.
+	pub fn get_ascent(&self) -> f64 {
+		self.ascent
+	}
+
+	/// Gets the distance between the top of each line, in font units.
+	pub fn get_line_height(&self) -> f64 {
+		self.ascent - self.descent + self.line_gap
+	}
+
+	/// Gets the glyph for a character, where 0 is the missing character glyph.
+	pub fn get_glyph(&self, c: char) -> u16 {
+		self.find_glyph(c as u32).unwrap_or(0)
+	}
+
+	fn find_glyph(&self, c: u32) -> Option<u16> {
+		let data = &self.data;
+		let cmap = self.cmap;
+		match read_u16(data, cmap)? {
+			4 => {
+				if c > 0xFFFF {
+					return None;
+				}
+				let segments = read_u16(data, cmap + 6)? as usize / 2;
+				let ends = cmap + 14;
+				let starts = ends + segments * 2 + 2;
+				let deltas = starts + segments * 2;
+				let range_offsets = deltas + segments * 2;
+
+				// The segments are sorted by their end.
+				let (mut low, mut high) = (0, segments);
+				while low < high {
+					let middle = (low + high) / 2;
+					if (read_u16(data, ends + middle * 2)? as u32) < c {
+						low = middle + 1;
+					} else {
+						high = middle;
+					}
+				}
+				let segment = low;
+				if segment >= segments {
+					return None;
+				}
+				let start = read_u16(data, starts + segment * 2)? as u32;
+				if c < start {
+					return None;
+				}
+				let delta = read_u16(data, deltas + segment * 2)?;
+				let range_offset = range_offsets + segment * 2;
+				let glyph = match read_u16(data, range_offset)? as usize {
+					0 => c as u16,
+					offset => match read_u16(data, range_offset + offset + (c - start) as usize * 2)? {
+						0 => return None,
+						glyph => glyph
+					}
+				};
+				Some(glyph.wrapping_add(delta))
+			},
+			12 => {
+				let groups = read_u32(data, cmap + 12)? as usize;
+				let (mut low, mut high) = (0, groups);
+				while low < high {
+					let middle = (low + high) / 2;
+					let group = cmap + 16 + middle * 12;
+					if c < read_u32(data, group)? {
+						high = middle;
+					} else if c > read_u32(data, group + 4)? {
+						low = middle + 1;
+					} else {
+						let glyph = read_u32(data, group + 8)?.checked_add(c - read_u32(data, group)?)?;
+						return if glyph <= 0xFFFF {Some(glyph as u16)} else {None};
+					}
+				}
+				None
+			},
+			_ => None
+		}
+	}
+
+	/// Gets how far to move after the glyph, in font units.
+	pub fn get_advance(&self, glyph: u16) -> f64 {
+		let index = glyph.min(self.metric_count.saturating_sub(1)) as usize;
+		read_u16(&self.data, self.hmtx + index * 4).unwrap_or(0) as f64
+	}
+
+	/// Gets the extra distance between two glyphs, in font units.
+	pub fn get_kerning(&self, first: u16, second: u16) -> f64 {
+		let (pairs, count) = match self.kern {
+			Some(kern) => kern,
+			None => return 0.0
+		};
+		let key = (first as u32) << 16 | second as u32;
+		let (mut low, mut high) = (0, count);
+		while low < high {
+			let middle = (low + high) / 2;
+			let pair = pairs + middle * 6;
+			match read_u32(&self.data, pair) {
+				Some(value) if value < key => low = middle + 1,
+				Some(value) if value > key => high = middle,
+				Some(_) => return read_i16(&self.data, pair + 4).unwrap_or(0) as f64,
+				None => return 0.0
+			}
+		}
+		0.0
+	}
+
+	/// Gets the outline of the glyph in pixels for the scale,
+	/// with the origin on the baseline and y going down.
+	pub fn get_outline(&self, glyph: u16, scale: f64) -> Path {
+		let mut path = Path::new();
+		if glyph < self.glyph_count {
+			let transform = [scale, 0.0, 0.0, -scale, 0.0, 0.0];
+			match self.outlines {
+				Outlines::Glyf {..} => self.glyf_outline(glyph, &mut path, &transform, 0),
+				Outlines::Cff(ref cff) => cff.outline(&self.data, glyph, &mut path, &transform)
+			};
+		}
+		path
+	}
+
+	fn glyf_outline(&self, glyph: u16, path: &mut Path, transform: &[f64; 6], depth: usize) -> Option<()> {
+		if depth > MAX_DEPTH {
+			return None;
+		}
+		let data = &self.data;
+		let (loca, glyf, long_offsets) = match self.outlines {
+			Outlines::Glyf {loca, glyf, long_offsets} => (loca, glyf, long_offsets),
+			_ => return None
+		};
+		let (start, end) = if long_offsets {
+			(read_u32(data, loca + glyph as usize * 4)? as usize, read_u32(data, loca + glyph as usize * 4 + 4)? as usize)
+		} else {
+			(read_u16(data, loca + glyph as usize * 2)? as usize * 2, read_u16(data, loca + glyph as usize * 2 + 2)? as usize * 2)
+		};
+		if start >= end {
+			return Some(());
+		}
+		let offset = glyf + start;
+
+		let contours = read_i16(data, offset)?;
+		if contours < 0 {
+			return self.composite_outline(offset + 10, path, transform, depth);
+		}
+
+		let contours = contours as usize;
+		if contours == 0 {
+			return Some(());
+		}
+		let ends = offset + 10;
+		let point_count = read_u16(data, ends + contours * 2 - 2)? as usize + 1;
+		let instructions = read_u16(data, ends + contours * 2)? as usize;
+		let mut cursor = ends + contours * 2 + 2 + instructions;
+
+		let mut flags = Vec::with_capacity(point_count);
+		while flags.len() < point_count {
+			let flag = read_u8(data, cursor)?;
+			cursor += 1;
+			flags.push(flag);
+			if flag & 0x08 != 0 {
+				let repeat = read_u8(data, cursor)?;
+				cursor += 1;
+				for _ in 0..repeat {
+					flags.push(flag);
+				}
+			}
+		}
+
+		// Reads the x or y coordinates, which are deltas with flags for their size and sign.
+		let mut coordinates = |short: u8, same: u8| -> Option<Vec<f64>> {
+			let mut value = 0i32;
+			let mut values = Vec::with_capacity(point_count);
+			for &flag in &flags[..point_count] {
+				if flag & short != 0 {
+					let delta = read_u8(data, cursor)? as i32;
+					cursor += 1;
+					value += if flag & same != 0 {delta} else {-delta};
+				} else if flag & same == 0 {
+					value += read_i16(data, cursor)? as i32;
+					cursor += 2;
+				}
+				values.push(value as f64);
+			}
+			Some(values)
+		};
+		let xs = coordinates(0x02, 0x10)?;
+		let ys = coordinates(0x04, 0x20)?;
+
+		let mut first = 0;
+		for contour in 0..contours {
+			let last = read_u16(data, ends + contour * 2)? as usize;
+			if last < first || last >= point_count {
+				return None;
+			}
+			let points: Vec<(Point, bool)> = (first..last + 1)
+				.map(|index| (apply(transform, xs[index], ys[index]), flags[index] & 0x01 != 0))
+				.collect();
+			quadratic_contour(path, &points);
+			first = last + 1;
+		}
+		Some(())
+	}
+
+	fn composite_outline(&self, mut offset: usize, path: &mut Path, transform: &[f64; 6], depth: usize) -> Option<()> {
+		let data = &self.data;
+		loop {
+			let flags = read_u16(data, offset)?;
+			let glyph = read_u16(data, offset + 2)?;
+			offset += 4;
+
+			let (dx, dy) = if flags & 0x0001 != 0 {
+				offset += 4;
+				(read_i16(data, offset - 4)? as f64, read_i16(data, offset - 2)? as f64)
+			} else {
+				offset += 2;
+				(read_u8(data, offset - 2)? as i8 as f64, read_u8(data, offset - 1)? as i8 as f64)
+			};
+			// Components positioned by matching points are rare and placed at the origin.
+			let (dx, dy) = if flags & 0x0002 != 0 {(dx, dy)} else {(0.0, 0.0)};
+
+			let fixed = |offset: usize| read_i16(data, offset).map(|value| value as f64 / 16384.0);
+			let (xx, xy, yx, yy) = if flags & 0x0008 != 0 {
+				offset += 2;
+				let scale = fixed(offset - 2)?;
+				(scale, 0.0, 0.0, scale)
+			} else if flags & 0x0040 != 0 {
+				offset += 4;
+				(fixed(offset - 4)?, 0.0, 0.0, fixed(offset - 2)?)
+			} else if flags & 0x0080 != 0 {
+				offset += 8;
+				(fixed(offset - 8)?, fixed(offset - 6)?, fixed(offset - 4)?, fixed(offset - 2)?)
+			} else {
+				(1.0, 0.0, 0.0, 1.0)
+			};
+
+			let t = transform;
+			let component = [
+				t[0] * xx + t[2] * xy,
+				t[1] * xx + t[3] * xy,
+				t[0] * yx + t[2] * yy,
+				t[1] * yx + t[3] * yy,
+				t[0] * dx + t[2] * dy + t[4],
+				t[1] * dx + t[3] * dy + t[5]
+			];
+			self.glyf_outline(glyph, path, &component, depth + 1)?;
+
+			if flags & 0x0020 == 0 {
+				return Some(());
+			}
+		}
+	}
+}
+
+/// Adds a TrueType contour, where two points off the curve
+/// have an implied point on the curve halfway between them.
+fn quadratic_contour(path: &mut Path, points: &[(Point, bool)]) {
+	if points.is_empty() {
+		return;
+	}
+
+	// Start on a point that is on the curve, or between the first two if there are none.
+	let start = match points.iter().position(|point| point.1) {
+		Some(index) => index,
+		None => {
+			let start = points[0].0.lerp(points[1 % points.len()].0, 0.5);
+			path.move_to(start);
+			add_quadratic_points(path, points.iter().cycle().skip(1).take(points.len()), start);
+			path.close();
+			return;
+		}
+	};
+	let first = points[start].0;
+	path.move_to(first);
+	add_quadratic_points(path, points.iter().cycle().skip(start + 1).take(points.len() - 1), first);
+	path.close();
+}
+
+fn add_quadratic_points<'a, I: Iterator<Item = &'a (Point, bool)>>(path: &mut Path, points: I, end: Point) {
+	let mut control = None;
+	for &(point, on_curve) in points {
+		match (control, on_curve) {
+			(Some(control_point), true) => {
+				path.quad_to(control_point, point);
+				control = None;
+			},
+			(Some(control_point), false) => {
+				path.quad_to(control_point, control_point.lerp(point, 0.5));
+				control = Some(point);
+			},
+			(None, true) => {
+				path.line_to(point);
+			},
+			(None, false) => control = Some(point)
+		}
+	}
+	match control {
+		Some(control) => path.quad_to(control, end),
+		None => path.line_to(end)
+	};
+}
+
+/// Draws the polygons into an alpha image using the area each pixel covers,
+/// filling with the non-zero rule.
+pub(crate) fn rasterize(polygons: &[(Vec<Point>, bool)], width: usize, height: usize) -> Vec<u8> {
+	// Each pixel accumulates the change in coverage, which is summed along each row.
+	let mut accumulation = vec![0.0f32; width * height + 2];
+	for &(ref points, _) in polygons {
+		for (index, &from) in points.iter().enumerate() {
+			let to = points[(index + 1) % points.len()];
+			accumulate_line(&mut accumulation, width, height, from, to);
+		}
+	}
+
+	let mut sum = 0.0;
+	accumulation.iter().take(width * height).map(|&value| {
+		sum += value;
+		(sum.abs().min(1.0) * 255.0).round() as u8
+	}).collect()
+}
+
+fn accumulate_line(accumulation: &mut [f32], width: usize, height: usize, from: Point, to: Point) {
+	let (from, to) = ((from.x as f32, from.y as f32), (to.x as f32, to.y as f32));
+	if (from.1 - to.1).abs() <= ::std::f32::EPSILON {
+		return;
+	}
+	let (direction, from, to) = if from.1 < to.1 {(1.0, from, to)} else {(-1.0, to, from)};
+	let dxdy = (to.0 - from.0) / (to.1 - from.1);
+	let mut x = from.0;
+	if from.1 < 0.0 {
+		x -= from.1 * dxdy;
+	}
+
+	let mut add = |index: isize, value: f32| {
+		if index >= 0 && (index as usize) < accumulation.len() {
+			accumulation[index as usize] += value;
+		}
+	};
+	let start = from.1.max(0.0) as usize;
+	let end = (to.1.ceil().max(0.0) as usize).min(height);
+	for y in start..end {
+		let row = (y * width) as isize;
+		let dy = ((y + 1) as f32).min(to.1) - (y as f32).max(from.1);
+		let next = x + dxdy * dy;
+		let d = dy * direction;
+		let (x0, x1) = if x < next {(x, next)} else {(next, x)};
+		let x0_floor = x0.floor();
+		let x0_index = x0_floor as isize;
+		let x1_ceil = x1.ceil();
+		let x1_index = x1_ceil as isize;
+
+		if x1_index <= x0_index + 1 {
+			// The line stays within one pixel on this row.
+			let middle = 0.5 * (x + next) - x0_floor;
+			add(row + x0_index, d - d * middle);
+			add(row + x0_index + 1, d * middle);
+		} else {
+			let s = (x1 - x0).recip();
+			let x0_fraction = x0 - x0_floor;
+			let a0 = 0.5 * s * (1.0 - x0_fraction) * (1.0 - x0_fraction);
+			let x1_fraction = x1 - x1_ceil + 1.0;
+			let am = 0.5 * s * x1_fraction * x1_fraction;
+			add(row + x0_index, d * a0);
+			if x1_index == x0_index + 2 {
+				add(row + x0_index + 1, d * (1.0 - a0 - am));
+			} else {
+				let a1 = s * (1.5 - x0_fraction);
+				add(row + x0_index + 1, d * (a1 - a0));
+				for index in x0_index + 2..x1_index - 1 {
+					add(row + index, d * s);
+				}
+				let a2 = a1 + (x1_index - x0_index - 3) as f32 * s;
+				add(row + x1_index - 1, d * (1.0 - a2 - am));
+			}
+			add(row + x1_index, d * am);
+		}
+		x = next;
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+
+	// Builds small fonts in memory, with big-endian values like the real tables.
+	fn u16s(values: &[u16]) -> Vec<u8> {
+		values.iter().flat_map(|value| value.to_be_bytes().to_vec()).collect()
+	}
+
+	fn u32s(values: &[u32]) -> Vec<u8> {
+		values.iter().flat_map(|value| value.to_be_bytes().to_vec()).collect()
+	}
+
+	/// Joins the tables into a font file, sorted by tag.
+	fn sfnt(version: &[u8; 4], mut tables: Vec<(&[u8; 4], Vec<u8>)>) -> Vec<u8> {
+		tables.sort_by_key(|table| *table.0);
+		let mut data = version.to_vec();
+		data.extend(u16s(&[tables.len() as u16, 0, 0, 0]));
+		let mut offset = 12 + tables.len() * 16;
+		for &(tag, ref table) in &tables {
+			data.extend_from_slice(tag);
+			data.extend(u32s(&[0, offset as u32, table.len() as u32]));
+			offset += (table.len() + 3) / 4 * 4;
+		}
+		for (_, mut table) in tables {
+			while table.len() % 4 != 0 {
+				table.push(0);
+			}
+			data.extend(table);
+		}
+		data
+	}
+
+	/// The tables every font needs, with 1000 units per em.
+	fn metrics(long_offsets: bool, glyph_count: u16, advances: &[u16]) -> Vec<(&'static [u8; 4], Vec<u8>)> {
+		let mut head = vec![0; 54];
+		head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+		head[50..52].copy_from_slice(&(long_offsets as u16).to_be_bytes());
+		let mut hhea = vec![0; 36];
+		hhea[4..10].copy_from_slice(&u16s(&[800, -200i16 as u16, 100]));
+		hhea[34..36].copy_from_slice(&(advances.len() as u16).to_be_bytes());
+		let mut maxp = u32s(&[0x5000]);
+		maxp.extend(u16s(&[glyph_count]));
+		let hmtx = advances.iter().flat_map(|&advance| u16s(&[advance, 0])).collect();
+		vec![(b"head", head), (b"hhea", hhea), (b"maxp", maxp), (b"hmtx", hmtx)]
+	}
+
+	/// A Windows Unicode format 4 subtable mapping `A` to `D` to glyphs 1 to 4,
+	/// `a` to glyph 2 through the glyph array, and nothing to `b`.
+	pub(crate) fn cmap4() -> Vec<u8> {
+		let mut subtable = u16s(&[4, 0, 0, 6, 0, 0, 0]);
+		subtable.extend(u16s(&[0x44, 0x62, 0xFFFF, 0]));
+		subtable.extend(u16s(&[0x41, 0x61, 0xFFFF]));
+		subtable.extend(u16s(&[(-0x40i16) as u16, 0, 1]));
+		// The range offset of the second segment points past the third to the glyph array.
+		subtable.extend(u16s(&[0, 4, 0]));
+		subtable.extend(u16s(&[2, 0]));
+		let mut cmap = u16s(&[0, 1, 3, 1]);
+		cmap.extend(u32s(&[12]));
+		cmap.extend(subtable);
+		cmap
+	}
+
+	/// A Windows full Unicode format 12 subtable, with a group
+	/// whose glyphs would go past the largest glyph index.
+	fn cmap12() -> Vec<u8> {
+		let groups: &[[u32; 3]] = &[[0x41, 0x44, 1], [0x1F600, 0x1F600, 3], [0x1F601, 0x10FFFF, 0xFFFF_FFF0]];
+		let mut subtable = u16s(&[12, 0]);
+		subtable.extend(u32s(&[16 + groups.len() as u32 * 12, 0, groups.len() as u32]));
+		for group in groups {
+			subtable.extend(u32s(group));
+		}
+		let mut cmap = u16s(&[0, 1, 3, 10]);
+		cmap.extend(u32s(&[12]));
+		cmap.extend(subtable);
+		cmap
+	}
+
+	fn kern() -> Vec<u8> {
+		let mut kern = u16s(&[0, 1, 0, 14 + 2 * 6, 0x0001, 2, 0, 0, 0]);
+		kern.extend(u16s(&[1, 2, (-50i16) as u16]));
+		kern.extend(u16s(&[2, 1, 30]));
+		kern
+	}
+
+	/// TrueType outlines:
+	/// 1 is a square from (100, 0) to (600, 500) with repeated flags and word coordinates,
+	/// 2 is a diamond of points off the curve with byte coordinates, which makes a circle,
+	/// 3 is 1 moved right by 1000 with 2 at half the size, and 4 includes itself.
+	pub(crate) fn glyf_font(long_offsets: bool, cmap: Vec<u8>) -> Vec<u8> {
+		let mut square = u16s(&[1, 100, 0, 600, 500, 3, 0]);
+		square.extend_from_slice(&[0x09, 3]);
+		square.extend(u16s(&[100, 500, 0, (-500i16) as u16, 0, 0, 500, 0]));
+
+		let mut diamond = u16s(&[1, 50, 0, 250, 200, 3, 0]);
+		diamond.extend_from_slice(&[0x32, 0x36, 0x26, 0x06]);
+		diamond.extend_from_slice(&[150, 100, 100, 100]);
+		diamond.extend_from_slice(&[100, 100, 100]);
+
+		let mut composite = u16s(&[(-1i16) as u16, 0, 0, 0, 0]);
+		composite.extend(u16s(&[0x0001 | 0x0002 | 0x0020, 1, 1000, 0]));
+		composite.extend(u16s(&[0x0002 | 0x0008, 2]));
+		composite.extend_from_slice(&[10, (-10i8) as u8]);
+		composite.extend(u16s(&[8192]));
+
+		let mut recursive = u16s(&[(-1i16) as u16, 0, 0, 0, 0]);
+		recursive.extend(u16s(&[0x0002, 4, 0]));
+
+		let mut glyf = Vec::new();
+		let mut offsets = vec![0];
+		for mut glyph in vec![Vec::new(), square, diamond, composite, recursive] {
+			if glyph.len() % 2 != 0 {
+				glyph.push(0);
+			}
+			glyf.extend(glyph);
+			offsets.push(glyf.len() as u32);
+		}
+		let loca = if long_offsets {
+			u32s(&offsets)
+		} else {
+			offsets.iter().flat_map(|&offset| u16s(&[(offset / 2) as u16])).collect()
+		};
+
+		let mut tables = metrics(long_offsets, 5, &[500, 600, 700]);
+		tables.extend(vec![(b"glyf", glyf), (b"loca", loca), (b"cmap", cmap), (b"kern", kern())]);
+		sfnt(&[0, 1, 0, 0], tables)
+	}
+
+	fn index(items: &[&[u8]]) -> Vec<u8> {
+		if items.is_empty() {
+			return u16s(&[0]);
+		}
+		let mut data = u16s(&[items.len() as u16]);
+		data.push(4);
+		let mut offset = 1;
+		data.extend(u32s(&[offset]));
+		for item in items {
+			offset += item.len() as u32;
+			data.extend(u32s(&[offset]));
+		}
+		for item in items {
+			data.extend_from_slice(item);
+		}
+		data
+	}
+
+	fn number(value: i32) -> Vec<u8> {
+		if value >= -107 && value <= 107 {
+			vec![(value + 139) as u8]
+		} else {
+			let mut data = vec![28];
+			data.extend(u16s(&[value as i16 as u16]));
+			data
+		}
+	}
+
+	fn dict_number(value: u32) -> Vec<u8> {
+		let mut data = vec![29];
+		data.extend(u32s(&[value]));
+		data
+	}
+
+	/// CFF outlines:
+	/// 1 is a square from (100, 0) to (600, 500) drawn with hints and both kinds of subroutine,
+	/// 2 is a circle from (0, 0) to (500, 500) drawn with alternating curves.
+	pub(crate) fn cff_font() -> Vec<u8> {
+		let code = |parts: &[(&[i32], &[u8])]| -> Vec<u8> {
+			parts.iter().flat_map(|&(numbers, operators)| {
+				let mut data: Vec<u8> = numbers.iter().flat_map(|&value| number(value)).collect();
+				data.extend_from_slice(operators);
+				data
+			}).collect()
+		};
+		let square = code(&[
+			(&[0, 50], &[1]),
+			(&[0, 10], &[3]),
+			(&[], &[19, 0xC0]),
+			(&[100, 0], &[21]),
+			(&[500], &[6]),
+			(&[-107], &[29]),
+			(&[-107], &[10]),
+			(&[], &[14])
+		]);
+		let circle = code(&[
+			(&[0, 250], &[21]),
+			(&[138, 112, 112, 138, 138, 112, -112, -138, -138, -112, -112, -138, -138, -112, 112, 138], &[30]),
+			(&[], &[14])
+		]);
+		let global_subrs = index(&[&code(&[(&[500], &[7, 11])])]);
+		let local_subrs = index(&[&code(&[(&[-500], &[6, 11])])]);
+		let char_strings = index(&[&[14], &square, &circle]);
+
+		let header = [1, 0, 4, 4];
+		let names = index(&[b"Test"]);
+		let strings = index(&[]);
+		let mut private = dict_number(0);
+		private.push(19);
+		let private_size = private.len() as u32;
+		let top_dict = |char_strings: u32, private: u32| {
+			let mut dict = dict_number(char_strings);
+			dict.push(17);
+			dict.extend(dict_number(private_size));
+			dict.extend(dict_number(private));
+			dict.push(18);
+			dict
+		};
+		let top_dict_size = index(&[&top_dict(0, 0)]).len();
+
+		let char_strings_offset = (header.len() + names.len() + top_dict_size + strings.len() + global_subrs.len()) as u32;
+		let private_offset = char_strings_offset + char_strings.len() as u32;
+		// The local subroutines follow the private dict, which points to them relative to itself.
+		let mut private = dict_number(private_size);
+		private.push(19);
+
+		let mut cff = header.to_vec();
+		cff.extend(names);
+		cff.extend(index(&[&top_dict(char_strings_offset, private_offset)]));
+		cff.extend(strings);
+		cff.extend(global_subrs);
+		cff.extend(char_strings);
+		cff.extend(private);
+		cff.extend(local_subrs);
+
+		let mut tables = metrics(false, 3, &[500, 700, 1000]);
+		tables.extend(vec![(b"CFF ", cff), (b"cmap", cmap4())]);
+		sfnt(b"OTTO", tables)
+	}
+
+	/// Gets the left, top, right and bottom of the outline.
+	fn bounds(path: &Path) -> (f64, f64, f64, f64) {
+		let polygons = path.flatten(0.01);
+		let points = || polygons.iter().flat_map(|polygon| polygon.0.iter());
+		(
+			points().map(|point| point.x).fold(::std::f64::INFINITY, f64::min),
+			points().map(|point| point.y).fold(::std::f64::INFINITY, f64::min),
+			points().map(|point| point.x).fold(::std::f64::NEG_INFINITY, f64::max),
+			points().map(|point| point.y).fold(::std::f64::NEG_INFINITY, f64::max)
+		)
+	}
+
+	fn assert_bounds(path: &Path, expected: (f64, f64, f64, f64)) {
+		let actual = bounds(path);
+		let near = |a: f64, b: f64| (a - b).abs() < 0.05;
+		assert!(near(actual.0, expected.0) && near(actual.1, expected.1) && near(actual.2, expected.2) && near(actual.3, expected.3),
+			"{:?} is not {:?}", actual, expected);
+	}
+
+	/// Uses every part of the font, which must not panic however broken it is.
+	fn exercise(font: &TrueType) {
+		for &c in &['A', 'B', 'D', 'a', 'b', 'z', '\u{1F600}', '\u{1F601}', '\u{10FFFF}'] {
+			let glyph = font.get_glyph(c);
+			font.get_advance(glyph);
+			font.get_kerning(glyph, 2);
+		}
+		for glyph in 0..8 {
+			font.get_outline(glyph, 0.01).flatten(0.1);
+		}
+		font.get_line_height();
+	}
+
+	#[test]
+	fn metrics_are_in_font_units() {
+		let font = TrueType::new(glyf_font(true, cmap4())).unwrap();
+		assert_eq!(font.get_scale(20.0), 0.02);
+		assert_eq!(font.get_ascent(), 800.0);
+		assert_eq!(font.get_line_height(), 1100.0);
+		assert_eq!(font.get_advance(1), 600.0);
+		// Glyphs past the metrics use the last advance.
+		assert_eq!(font.get_advance(4), 700.0);
+	}
+
+	#[test]
+	fn glyf_outlines_are_flipped_and_scaled() {
+		for &long_offsets in &[false, true] {
+			let font = TrueType::new(glyf_font(long_offsets, cmap4())).unwrap();
+			assert!(font.get_outline(0, 0.01).flatten(0.1).is_empty());
+			assert_bounds(&font.get_outline(1, 0.01), (1.0, -5.0, 6.0, 0.0));
+			// The curve passes halfway between the points off the curve, bulging towards them.
+			assert_bounds(&font.get_outline(2, 0.01), (0.75, -1.75, 2.25, -0.25));
+			assert_bounds(&font.get_outline(3, 0.01), (0.475, -5.0, 16.0, 0.0));
+			assert!(font.get_outline(4, 0.01).flatten(0.1).is_empty());
+			assert!(font.get_outline(5, 0.01).flatten(0.1).is_empty());
+		}
+	}
+
+	#[test]
+	fn cff_outlines_are_flipped_and_scaled() {
+		let font = TrueType::new(cff_font()).unwrap();
+		assert!(font.get_outline(0, 0.01).flatten(0.1).is_empty());
+		assert_bounds(&font.get_outline(1, 0.01), (1.0, -5.0, 6.0, 0.0));
+		assert_bounds(&font.get_outline(2, 0.01), (0.0, -5.0, 5.0, 0.0));
+		assert_eq!(font.get_glyph('B'), 2);
+	}
+
+	#[test]
+	fn cmap_format_4_maps_characters() {
+		let font = TrueType::new(glyf_font(true, cmap4())).unwrap();
+		let glyphs: Vec<u16> = "ABDEab\u{1F600}".chars().map(|c| font.get_glyph(c)).collect();
+		assert_eq!(glyphs, vec![1, 2, 4, 0, 2, 0, 0]);
+	}
+
+	#[test]
+	fn cmap_format_12_maps_characters() {
+		let font = TrueType::new(glyf_font(true, cmap12())).unwrap();
+		let glyphs: Vec<u16> = "ACE\u{1F600}\u{1F601}\u{10FFFF}".chars().map(|c| font.get_glyph(c)).collect();
+		// Glyph indices past the largest one, or that don't fit in 32 bits, are missing.
+		assert_eq!(glyphs, vec![1, 3, 0, 3, 0, 0]);
+	}
+
+	#[test]
+	fn kerning_finds_pairs() {
+		let font = TrueType::new(glyf_font(true, cmap4())).unwrap();
+		assert_eq!(font.get_kerning(1, 2), -50.0);
+		assert_eq!(font.get_kerning(2, 1), 30.0);
+		assert_eq!(font.get_kerning(1, 1), 0.0);
+		assert_eq!(TrueType::new(cff_font()).unwrap().get_kerning(1, 2), 0.0);
+	}
+
+	#[test]
+	fn truncated_fonts_are_errors() {
+		assert!(TrueType::new(Vec::new()).is_err());
+		assert!(TrueType::new(b"not a font at all".to_vec()).is_err());
+		for data in vec![glyf_font(false, cmap4()), glyf_font(true, cmap12()), cff_font()] {
+			for length in 0..data.len() {
+				if let Ok(font) = TrueType::new(data[..length].to_vec()) {
+					exercise(&font);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn garbage_fonts_do_not_panic() {
+		for data in vec![glyf_font(false, cmap4()), glyf_font(true, cmap12()), cff_font()] {
+			for index in 0..data.len() {
+				for &byte in &[0x00, 0x7F, 0x80, 0xFF] {
+					let mut data = data.clone();
+					data[index] = byte;
+					if let Ok(font) = TrueType::new(data) {
+						exercise(&font);
+					}
+				}
+			}
+		}
+	}
+}
\ No newline at end of file
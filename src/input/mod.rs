@@ -2,17 +2,23 @@
 //! and the cursor.
 
 extern crate glutin;
-pub use self::glutin::{VirtualKeyCode as Key, MouseButton as Button};
+pub use self::glutin::{VirtualKeyCode as Key, MouseButton as Button, MouseCursor as CursorIcon};
 use self::glutin::dpi::LogicalPosition;
 
 extern crate backtrace;
 use self::backtrace::Backtrace;
 
+mod names;
+pub use self::names::*;
+
 use ::Point;
 use ::core::RcWindow;
+use ::graphics::Image;
 
 use std::fmt::{Display, Formatter, Error};
+use std::error;
 use std::collections::HashSet;
+use std::rc::Rc;
 
 /// Possible errors that can occur from input related actions.
 #[derive(Clone, Debug)]
@@ -29,12 +35,25 @@ impl Display for InputError {
 	}
 }
 
+impl error::Error for InputError {}
+
+impl InputError {
+	/// Gets the backtrace captured when the error occurred.
+	pub fn backtrace(&self) -> &Backtrace {
+		match self {
+			&InputError::InternalError(_, ref backtrace) => backtrace
+		}
+	}
+}
+
 /// Contains all input related methods and data.
 pub struct Input {
 	window: RcWindow,
 	pub(crate) keys: HashSet<Key>,
 	pub(crate) buttons: HashSet<Button>,
-	pub(crate) cursor: Point
+	pub(crate) cursor: Point,
+	pub(crate) cursor_hidden: bool,
+	pub(crate) cursor_image: Option<(Rc<Image>, Point)>
 }
 
 impl Input {
@@ -43,7 +62,9 @@ impl Input {
 			window,
 			keys: HashSet::new(),
 			buttons: HashSet::new(),
-			cursor: Point::default()
+			cursor: Point::default(),
+			cursor_hidden: false,
+			cursor_image: None
 		}
 	}
 
@@ -82,6 +103,34 @@ impl Input {
 	/// Sets the cursor as hidden
 	/// or visible.
 	pub fn set_cursor_hidden(&mut self, hidden: bool) {
+		self.cursor_hidden = hidden;
+		self.update_cursor_hidden();
+	}
+
+	/// Sets the cursor to one of the standard system cursors.
+	/// This removes any image set with
+	/// [`set_cursor_image`](#method.set_cursor_image).
+	pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+		self.cursor_image = None;
+		self.update_cursor_hidden();
+		self.window.borrow().window.set_cursor(icon);
+	}
+
+	/// Sets the cursor to a custom image.
+	/// The hotspot is the point in the image that is placed at the cursor position.
+	///
+	/// The system cursor is hidden and the image is drawn over everything else
+	/// at the end of each frame, since the windowing library has no custom cursors.
+	/// This means the image lags a frame behind the real cursor,
+	/// and moves less smoothly when frames take longer to draw.
+	/// It is also not drawn outside of the window.
+	pub fn set_cursor_image(&mut self, image: Image, hotspot: Point) {
+		self.cursor_image = Some((Rc::new(image), hotspot));
+		self.update_cursor_hidden();
+	}
+
+	fn update_cursor_hidden(&self) {
+		let hidden = self.cursor_hidden || self.cursor_image.is_some();
 		self.window.borrow().window.hide_cursor(hidden);
 	}
 }
\ No newline at end of file
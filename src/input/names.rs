@@ -0,0 +1,179 @@
+// Copyright 2018 Joshua Minter
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Key, Button};
+
+// The names are part of the save format and must never change.
+macro_rules! key_names {
+	($($key:ident),*) => {
+		/// Gets the stable name of a key, which is the name of its variant.
+		pub fn key_name(key: Key) -> &'static str {
+			match key {
+				$(Key::$key => stringify!($key)),*
+			}
+		}
+
+		/// Gets a key from a name returned by [`key_name`](fn.key_name.html).
+		pub fn key_from_name(name: &str) -> Option<Key> {
+			match name {
+				$(stringify!($key) => Some(Key::$key),)*
+				_ => None
+			}
+		}
+
+		#[cfg(test)]
+		const KEYS: &[Key] = &[$(Key::$key),*];
+	}
+}
+
+key_names!(
+	Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0,
+	A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+	Escape,
+	F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15,
+	Snapshot, Scroll, Pause,
+	Insert, Home, Delete, End, PageDown, PageUp,
+	Left, Up, Right, Down,
+	Back, Return, Space,
+	Compose, Caret,
+	Numlock, Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+	AbntC1, AbntC2, Add, Apostrophe, Apps, At, Ax, Backslash, Calculator, Capital, Colon, Comma, Convert,
+	Decimal, Divide, Equals, Grave, Kana, Kanji, LAlt, LBracket, LControl, LShift, LWin, Mail,
+	MediaSelect, MediaStop, Minus, Multiply, Mute, MyComputer, NavigateForward, NavigateBackward,
+	NextTrack, NoConvert, NumpadComma, NumpadEnter, NumpadEquals, OEM102, Period, PlayPause, Power,
+	PrevTrack, RAlt, RBracket, RControl, RShift, RWin, Semicolon, Slash, Sleep, Stop, Subtract,
+	Sysrq, Tab, Underline, Unlabeled, VolumeDown, VolumeUp, Wake, WebBack, WebFavorites,
+	WebForward, WebHome, WebRefresh, WebSearch, WebStop, Yen, Copy, Paste, Cut
+);
+
+/// Gets the stable name of a mouse button.
+/// Other buttons are named `Button` followed by their number.
+pub fn button_name(button: Button) -> String {
+	match button {
+		Button::Left => "Left".to_string(),
+		Button::Right => "Right".to_string(),
+		Button::Middle => "Middle".to_string(),
+		Button::Other(number) => format!("Button{}", number)
+	}
+}
+
+/// Gets a mouse button from a name returned by [`button_name`](fn.button_name.html).
+pub fn button_from_name(name: &str) -> Option<Button> {
+	match name {
+		"Left" => Some(Button::Left),
+		"Right" => Some(Button::Right),
+		"Middle" => Some(Button::Middle),
+		_ => name.strip_prefix("Button")
+			.and_then(|number| number.parse().ok())
+			.map(Button::Other)
+	}
+}
+
+/// Serializes a [`Key`](enum.Key.html) by its name.
+/// Use with `#[serde(with = "mint2d::input::serde_key")]`.
+#[cfg(feature = "serde")]
+pub mod serde_key {
+	use ::input::Key;
+	use serde::{Serializer, Deserializer, Deserialize};
+	use serde::de::Error;
+
+	/// Serializes the key as its name.
+	pub fn serialize<S: Serializer>(key: &Key, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(super::key_name(*key))
+	}
+
+	/// Deserializes a key from its name.
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Key, D::Error> {
+		let name = String::deserialize(deserializer)?;
+		super::key_from_name(&name)
+			.ok_or_else(|| D::Error::custom(format!("unknown key: {}", name)))
+	}
+}
+
+/// Serializes a [`Button`](enum.Button.html) by its name.
+/// Use with `#[serde(with = "mint2d::input::serde_button")]`.
+#[cfg(feature = "serde")]
+pub mod serde_button {
+	use ::input::Button;
+	use serde::{Serializer, Deserializer, Deserialize};
+	use serde::de::Error;
+
+	/// Serializes the button as its name.
+	pub fn serialize<S: Serializer>(button: &Button, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&super::button_name(*button))
+	}
+
+	/// Deserializes a button from its name.
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Button, D::Error> {
+		let name = String::deserialize(deserializer)?;
+		super::button_from_name(&name)
+			.ok_or_else(|| D::Error::custom(format!("unknown button: {}", name)))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[cfg(feature = "serde")]
+	extern crate serde_json;
+
+	#[test]
+	fn key_names_round_trip() {
+		// key_name matches every variant, so a missing key doesn't compile.
+		let mut names: Vec<&str> = KEYS.iter().map(|&key| key_name(key)).collect();
+		for &key in KEYS {
+			assert_eq!(key_name(key), format!("{:?}", key));
+			assert_eq!(key_from_name(key_name(key)), Some(key));
+		}
+		names.sort();
+		names.dedup();
+		assert_eq!(names.len(), KEYS.len());
+		assert_eq!(key_from_name("a"), None);
+		assert_eq!(key_from_name(""), None);
+	}
+
+	#[test]
+	fn button_names_round_trip() {
+		let mut buttons = vec![Button::Left, Button::Right, Button::Middle];
+		buttons.extend((0..=255).map(Button::Other));
+		for &button in &buttons {
+			assert_eq!(button_from_name(&button_name(button)), Some(button));
+		}
+		assert_eq!(button_name(Button::Other(4)), "Button4");
+		for &name in &["left", "Button", "Button256", "Button-1", "Buttonx"] {
+			assert_eq!(button_from_name(name), None);
+		}
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn serde_uses_the_names() {
+		#[derive(Serialize, Deserialize, PartialEq, Debug)]
+		struct Binding {
+			#[serde(with = "serde_key")]
+			key: Key,
+			#[serde(with = "serde_button")]
+			button: Button
+		}
+
+		let binding = Binding {key: Key::Key1, button: Button::Other(4)};
+		let json = serde_json::to_string(&binding).unwrap();
+		assert_eq!(json, r#"{"key":"Key1","button":"Button4"}"#);
+		assert_eq!(serde_json::from_str::<Binding>(&json).unwrap(), binding);
+		assert!(serde_json::from_str::<Binding>(r#"{"key":"Key11","button":"Left"}"#).is_err());
+		assert!(serde_json::from_str::<Binding>(r#"{"key":"A","button":"Button"}"#).is_err());
+	}
+}
\ No newline at end of file
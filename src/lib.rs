@@ -15,12 +15,116 @@
 #[macro_use]
 extern crate memoffset;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
 pub mod core;
 pub mod input;
 pub mod graphics;
 
+extern crate backtrace;
+use backtrace::Backtrace;
+
+use ::core::{WindowError, ConfigError};
+use ::input::InputError;
+use ::graphics::{StackError, GraphicsError, Angle};
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+
+/// An error from any part of the library.
+/// Every error of the other modules can be converted into this,
+/// so `?` can be used across them.
+#[derive(Clone, Debug)]
+pub enum Error {
+	/// A window related error.
+	Window(WindowError),
+
+	/// A config loading or saving error.
+	Config(ConfigError),
+
+	/// An input related error.
+	Input(InputError),
+
+	/// A graphics state stack error.
+	Stack(StackError),
+
+	/// An OpenGL error.
+	Graphics(GraphicsError)
+}
+
+impl Error {
+	/// Gets the backtrace captured when the error occurred.
+	pub fn backtrace(&self) -> &Backtrace {
+		match self {
+			&Error::Window(ref error) => error.backtrace(),
+			&Error::Config(ref error) => error.backtrace(),
+			&Error::Input(ref error) => error.backtrace(),
+			&Error::Stack(ref error) => error.backtrace(),
+			&Error::Graphics(ref error) => error.backtrace()
+		}
+	}
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			&Error::Window(ref error) => write!(f, "Window error: {}", error),
+			&Error::Config(ref error) => write!(f, "Config error: {}", error),
+			&Error::Input(ref error) => write!(f, "Input error: {}", error),
+			&Error::Stack(ref error) => write!(f, "Stack error: {}", error),
+			&Error::Graphics(ref error) => write!(f, "Graphics error: {}", error)
+		}
+	}
+}
+
+impl error::Error for Error {
+	fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+		match self {
+			&Error::Window(ref error) => Some(error),
+			&Error::Config(ref error) => Some(error),
+			&Error::Input(ref error) => Some(error),
+			&Error::Stack(ref error) => Some(error),
+			&Error::Graphics(ref error) => Some(error)
+		}
+	}
+}
+
+impl From<WindowError> for Error {
+	fn from(error: WindowError) -> Error {
+		Error::Window(error)
+	}
+}
+
+impl From<ConfigError> for Error {
+	fn from(error: ConfigError) -> Error {
+		Error::Config(error)
+	}
+}
+
+impl From<InputError> for Error {
+	fn from(error: InputError) -> Error {
+		Error::Input(error)
+	}
+}
+
+impl From<StackError> for Error {
+	fn from(error: StackError) -> Error {
+		Error::Stack(error)
+	}
+}
+
+impl From<GraphicsError> for Error {
+	fn from(error: GraphicsError) -> Error {
+		Error::Graphics(error)
+	}
+}
+
 /// A simple struct that represents the size of an object.
 #[derive(Copy, Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Size {
 	/// The width of the object.
 	pub width: f64,
@@ -31,10 +135,558 @@ pub struct Size {
 
 /// A simple struct that represents a point or position.
 #[derive(Copy, Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Point {
 	/// The x-coordinate of the point.
 	pub x: f64,
 
 	/// The y-coordinate of the point.
 	pub y: f64
+}
+
+// Implements the component-wise operators shared by `Point` and `Size`.
+macro_rules! vector_ops {
+	($type:ident, $x:ident, $y:ident) => {
+		impl Add for $type {
+			type Output = $type;
+			fn add(self, other: $type) -> $type {
+				$type {$x: self.$x + other.$x, $y: self.$y + other.$y}
+			}
+		}
+
+		impl Sub for $type {
+			type Output = $type;
+			fn sub(self, other: $type) -> $type {
+				$type {$x: self.$x - other.$x, $y: self.$y - other.$y}
+			}
+		}
+
+		impl Mul for $type {
+			type Output = $type;
+			fn mul(self, other: $type) -> $type {
+				$type {$x: self.$x * other.$x, $y: self.$y * other.$y}
+			}
+		}
+
+		impl Div for $type {
+			type Output = $type;
+			fn div(self, other: $type) -> $type {
+				$type {$x: self.$x / other.$x, $y: self.$y / other.$y}
+			}
+		}
+
+		impl Mul<f64> for $type {
+			type Output = $type;
+			fn mul(self, scalar: f64) -> $type {
+				$type {$x: self.$x * scalar, $y: self.$y * scalar}
+			}
+		}
+
+		impl Mul<$type> for f64 {
+			type Output = $type;
+			fn mul(self, vector: $type) -> $type {
+				vector * self
+			}
+		}
+
+		impl Div<f64> for $type {
+			type Output = $type;
+			fn div(self, scalar: f64) -> $type {
+				$type {$x: self.$x / scalar, $y: self.$y / scalar}
+			}
+		}
+
+		impl Neg for $type {
+			type Output = $type;
+			fn neg(self) -> $type {
+				$type {$x: -self.$x, $y: -self.$y}
+			}
+		}
+
+		impl AddAssign for $type {
+			fn add_assign(&mut self, other: $type) {
+				*self = *self + other;
+			}
+		}
+
+		impl SubAssign for $type {
+			fn sub_assign(&mut self, other: $type) {
+				*self = *self - other;
+			}
+		}
+
+		impl MulAssign<f64> for $type {
+			fn mul_assign(&mut self, scalar: f64) {
+				*self = *self * scalar;
+			}
+		}
+
+		impl DivAssign<f64> for $type {
+			fn div_assign(&mut self, scalar: f64) {
+				*self = *self / scalar;
+			}
+		}
+
+		impl From<(f64, f64)> for $type {
+			fn from(tuple: (f64, f64)) -> $type {
+				$type {$x: tuple.0, $y: tuple.1}
+			}
+		}
+
+		impl From<$type> for (f64, f64) {
+			fn from(vector: $type) -> (f64, f64) {
+				(vector.$x, vector.$y)
+			}
+		}
+	}
+}
+
+vector_ops!(Point, x, y);
+vector_ops!(Size, width, height);
+
+impl Point {
+	/// A utility method that makes it neater to create a point.
+	pub fn new(x: f64, y: f64) -> Point {
+		Point {x, y}
+	}
+
+	/// The dot product of two points as vectors.
+	pub fn dot(self, other: Point) -> f64 {
+		self.x * other.x + self.y * other.y
+	}
+
+	/// The z-component of the cross product of two points as vectors.
+	/// Positive if `other` is clockwise from `self`, since y points down.
+	pub fn cross(self, other: Point) -> f64 {
+		self.x * other.y - self.y * other.x
+	}
+
+	/// The distance from the origin.
+	pub fn length(self) -> f64 {
+		self.length_squared().sqrt()
+	}
+
+	/// The squared distance from the origin, which avoids a square root.
+	pub fn length_squared(self) -> f64 {
+		self.dot(self)
+	}
+
+	/// The distance between two points.
+	pub fn distance(self, other: Point) -> f64 {
+		(other - self).length()
+	}
+
+	/// Scales the point to a length of 1.
+	/// A point at the origin stays at the origin.
+	pub fn normalize(self) -> Point {
+		let length = self.length();
+		if length == 0.0 {
+			self
+		} else {
+			self / length
+		}
+	}
+
+	/// Linearly interpolates between two points,
+	/// where an amount of 0 is `self` and 1 is `other`.
+	pub fn lerp(self, other: Point, amount: f64) -> Point {
+		self + (other - self) * amount
+	}
+
+	/// Rotates the point clockwise around the origin,
+	/// matching [`Graphics.rotate`](graphics/struct.Graphics.html#method.rotate).
+	pub fn rotate(self, angle: Angle) -> Point {
+		let (sin, cos) = angle.as_radians().sin_cos();
+		Point {
+			x: self.x * cos - self.y * sin,
+			y: self.x * sin + self.y * cos
+		}
+	}
+
+	/// Rotates the point a quarter turn clockwise around the origin.
+	pub fn perpendicular(self) -> Point {
+		Point {x: -self.y, y: self.x}
+	}
+
+	/// Converts the point to a size with the same components.
+	pub fn to_size(self) -> Size {
+		Size {width: self.x, height: self.y}
+	}
+}
+
+impl Size {
+	/// A utility method that makes it neater to create a size.
+	pub fn new(width: f64, height: f64) -> Size {
+		Size {width, height}
+	}
+
+	/// The area covered by the size.
+	pub fn area(self) -> f64 {
+		self.width * self.height
+	}
+
+	/// Linearly interpolates between two sizes,
+	/// where an amount of 0 is `self` and 1 is `other`.
+	pub fn lerp(self, other: Size, amount: f64) -> Size {
+		self + (other - self) * amount
+	}
+
+	/// Converts the size to a point with the same components.
+	pub fn to_point(self) -> Point {
+		Point {x: self.width, y: self.height}
+	}
+}
+
+impl Add<Size> for Point {
+	type Output = Point;
+	fn add(self, size: Size) -> Point {
+		self + size.to_point()
+	}
+}
+
+impl Sub<Size> for Point {
+	type Output = Point;
+	fn sub(self, size: Size) -> Point {
+		self - size.to_point()
+	}
+}
+
+impl From<Size> for Point {
+	fn from(size: Size) -> Point {
+		size.to_point()
+	}
+}
+
+impl From<Point> for Size {
+	fn from(point: Point) -> Size {
+		point.to_size()
+	}
+}
+
+/// An axis-aligned rectangle with its origin at the top-left.
+///
+/// The methods expect the size to not be negative.
+/// [`Rect::new`](#method.new) and [`Rect::from_points`](#method.from_points)
+/// move the origin so it is, but setting the fields directly does not.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rect {
+	/// The top-left corner of the rectangle.
+	pub origin: Point,
+
+	/// The size of the rectangle.
+	pub size: Size
+}
+
+impl Rect {
+	/// A utility method that makes it neater to create a rectangle.
+	/// A negative width or height extends the rectangle left or up from the point.
+	pub fn new(x: f64, y: f64, width: f64, height: f64) -> Rect {
+		Rect::from_points(Point {x, y}, Point {x: x + width, y: y + height})
+	}
+
+	/// Creates the smallest rectangle that contains both points.
+	pub fn from_points(a: Point, b: Point) -> Rect {
+		let min = Point {x: a.x.min(b.x), y: a.y.min(b.y)};
+		let max = Point {x: a.x.max(b.x), y: a.y.max(b.y)};
+		Rect {
+			origin: min,
+			size: (max - min).to_size()
+		}
+	}
+
+	/// The x-coordinate of the left edge.
+	pub fn left(&self) -> f64 {
+		self.origin.x
+	}
+
+	/// The x-coordinate of the right edge.
+	pub fn right(&self) -> f64 {
+		self.origin.x + self.size.width
+	}
+
+	/// The y-coordinate of the top edge.
+	pub fn top(&self) -> f64 {
+		self.origin.y
+	}
+
+	/// The y-coordinate of the bottom edge.
+	pub fn bottom(&self) -> f64 {
+		self.origin.y + self.size.height
+	}
+
+	/// Gets the point at a fraction of the size from the origin,
+	/// where `(0, 0)` is the top-left and `(1, 1)` is the bottom-right.
+	pub fn anchor(&self, anchor: Point) -> Point {
+		self.origin + self.size * anchor.to_size()
+	}
+
+	/// The top-left corner.
+	pub fn top_left(&self) -> Point {
+		self.origin
+	}
+
+	/// The top-right corner.
+	pub fn top_right(&self) -> Point {
+		Point {x: self.right(), y: self.top()}
+	}
+
+	/// The bottom-left corner.
+	pub fn bottom_left(&self) -> Point {
+		Point {x: self.left(), y: self.bottom()}
+	}
+
+	/// The bottom-right corner.
+	pub fn bottom_right(&self) -> Point {
+		Point {x: self.right(), y: self.bottom()}
+	}
+
+	/// The center of the rectangle.
+	pub fn center(&self) -> Point {
+		self.origin + self.size / 2.0
+	}
+
+	/// Whether or not the point is inside the rectangle.
+	/// The top and left edges are inside, the bottom and right edges are not.
+	pub fn contains(&self, point: Point) -> bool {
+		point.x >= self.left() && point.x < self.right() &&
+			point.y >= self.top() && point.y < self.bottom()
+	}
+
+	/// Whether or not the rectangles overlap.
+	/// Rectangles that only share an edge do not overlap.
+	pub fn intersects(&self, other: &Rect) -> bool {
+		self.left() < other.right() && other.left() < self.right() &&
+			self.top() < other.bottom() && other.top() < self.bottom()
+	}
+
+	/// The area covered by both rectangles, if they overlap.
+	pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+		if self.intersects(other) {
+			Some(Rect::from_points(
+				Point {x: self.left().max(other.left()), y: self.top().max(other.top())},
+				Point {x: self.right().min(other.right()), y: self.bottom().min(other.bottom())}
+			))
+		} else {
+			None
+		}
+	}
+
+	/// The smallest rectangle that contains both rectangles.
+	pub fn union(&self, other: &Rect) -> Rect {
+		Rect::from_points(
+			Point {x: self.left().min(other.left()), y: self.top().min(other.top())},
+			Point {x: self.right().max(other.right()), y: self.bottom().max(other.bottom())}
+		)
+	}
+
+	/// Moves every edge outwards by the amount.
+	/// A negative amount shrinks the rectangle, down to nothing at its center.
+	pub fn expand(&self, amount: f64) -> Rect {
+		let width = (self.size.width + amount * 2.0).max(0.0);
+		let height = (self.size.height + amount * 2.0).max(0.0);
+		let center = self.center();
+		Rect {
+			origin: Point {x: center.x - width / 2.0, y: center.y - height / 2.0},
+			size: Size {width, height}
+		}
+	}
+
+	/// Moves the rectangle by the offset.
+	pub fn translate(&self, offset: Point) -> Rect {
+		Rect {
+			origin: self.origin + offset,
+			size: self.size
+		}
+	}
+}
+
+impl From<(Point, Size)> for Rect {
+	fn from((origin, size): (Point, Size)) -> Rect {
+		Rect {origin, size}
+	}
+}
+
+impl From<Rect> for (Point, Size) {
+	fn from(rect: Rect) -> (Point, Size) {
+		(rect.origin, rect.size)
+	}
+}
+
+impl From<Size> for Rect {
+	fn from(size: Size) -> Rect {
+		Rect {
+			origin: Point::default(),
+			size
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ::graphics::Graphics;
+
+	fn assert_near(a: Point, b: Point) {
+		assert!((a - b).length() < 1e-9, "{:?} is not {:?}", a, b);
+	}
+
+	#[test]
+	fn point_operators_are_component_wise() {
+		let a = Point::new(6.0, 8.0);
+		let b = Point::new(2.0, -4.0);
+		assert_eq!(a + b, Point::new(8.0, 4.0));
+		assert_eq!(a - b, Point::new(4.0, 12.0));
+		assert_eq!(a * b, Point::new(12.0, -32.0));
+		assert_eq!(a / b, Point::new(3.0, -2.0));
+		assert_eq!(a * 0.5, Point::new(3.0, 4.0));
+		assert_eq!(0.5 * a, Point::new(3.0, 4.0));
+		assert_eq!(a / 2.0, Point::new(3.0, 4.0));
+		assert_eq!(-a, Point::new(-6.0, -8.0));
+
+		let mut c = a;
+		c += b;
+		assert_eq!(c, a + b);
+		c -= b;
+		assert_eq!(c, a);
+		c *= 2.0;
+		assert_eq!(c, Point::new(12.0, 16.0));
+		c /= 4.0;
+		assert_eq!(c, Point::new(3.0, 4.0));
+	}
+
+	#[test]
+	fn size_operators_are_component_wise() {
+		let a = Size::new(6.0, 8.0);
+		let b = Size::new(2.0, -4.0);
+		assert_eq!(a + b, Size::new(8.0, 4.0));
+		assert_eq!(a - b, Size::new(4.0, 12.0));
+		assert_eq!(a * b, Size::new(12.0, -32.0));
+		assert_eq!(a / b, Size::new(3.0, -2.0));
+		assert_eq!(a * 0.5, Size::new(3.0, 4.0));
+		assert_eq!(0.5 * a, Size::new(3.0, 4.0));
+		assert_eq!(a / 2.0, Size::new(3.0, 4.0));
+		assert_eq!(-a, Size::new(-6.0, -8.0));
+
+		let mut c = a;
+		c += b;
+		assert_eq!(c, a + b);
+		c -= b;
+		assert_eq!(c, a);
+		c *= 2.0;
+		assert_eq!(c, Size::new(12.0, 16.0));
+		c /= 4.0;
+		assert_eq!(c, Size::new(3.0, 4.0));
+		assert_eq!(a.area(), 48.0);
+	}
+
+	#[test]
+	fn point_and_size_mix() {
+		let point = Point::new(1.0, 2.0);
+		let size = Size::new(3.0, 4.0);
+		assert_eq!(point + size, Point::new(4.0, 6.0));
+		assert_eq!(point - size, Point::new(-2.0, -2.0));
+	}
+
+	#[test]
+	fn vector_math() {
+		let a = Point::new(3.0, 4.0);
+		assert_eq!(a.length(), 5.0);
+		assert_eq!(a.length_squared(), 25.0);
+		assert_eq!(a.distance(Point::new(0.0, 8.0)), 5.0);
+		assert_eq!(a.dot(Point::new(2.0, 1.0)), 10.0);
+		assert_eq!(a.normalize(), Point::new(0.6, 0.8));
+		assert!(Point::new(1.0, 0.0).cross(Point::new(0.0, 1.0)) > 0.0);
+	}
+
+	#[test]
+	fn normalize_keeps_zero_at_the_origin() {
+		assert_eq!(Point::default().normalize(), Point::default());
+		assert_eq!(Point::new(-0.0, 0.0).normalize().length(), 0.0);
+	}
+
+	#[test]
+	fn rotate_matches_the_graphics_transform() {
+		// A quarter turn moves right to down, which is clockwise since y points down.
+		assert_near(Point::new(1.0, 0.0).rotate(Angle::from_degrees(90.0)), Point::new(0.0, 1.0));
+		assert_near(Point::new(1.0, 0.0).perpendicular(), Point::new(0.0, 1.0));
+
+		let mut graphics = Graphics::software(1, 1);
+		for &degrees in &[30.0, 90.0, 135.0, -60.0] {
+			let angle = Angle::from_degrees(degrees);
+			graphics.identity();
+			graphics.rotate(angle);
+			let point = Point::new(2.0, -3.0);
+			assert_near(point.rotate(angle), graphics.transform(point));
+		}
+	}
+
+	#[test]
+	fn lerp_reaches_both_ends() {
+		let a = Point::new(2.0, -4.0);
+		let b = Point::new(6.0, 8.0);
+		assert_eq!(a.lerp(b, 0.0), a);
+		assert_eq!(a.lerp(b, 0.5), Point::new(4.0, 2.0));
+		assert_eq!(a.lerp(b, 1.0), b);
+
+		let a = Size::new(2.0, 4.0);
+		let b = Size::new(6.0, 8.0);
+		assert_eq!(a.lerp(b, 0.0), a);
+		assert_eq!(a.lerp(b, 0.5), Size::new(4.0, 6.0));
+		assert_eq!(a.lerp(b, 1.0), b);
+	}
+
+	#[test]
+	fn conversions_keep_components() {
+		let point = Point::new(1.0, 2.0);
+		let size = Size::new(1.0, 2.0);
+		assert_eq!(point.to_size(), size);
+		assert_eq!(size.to_point(), point);
+		assert_eq!(Size::from(point), size);
+		assert_eq!(Point::from(size), point);
+		assert_eq!(Point::from((1.0, 2.0)), point);
+		assert_eq!(Size::from((1.0, 2.0)), size);
+		assert_eq!(<(f64, f64)>::from(point), (1.0, 2.0));
+		assert_eq!(<(f64, f64)>::from(size), (1.0, 2.0));
+	}
+
+	#[test]
+	fn rect_normalizes_negative_sizes() {
+		let rect = Rect::new(4.0, 6.0, -3.0, -2.0);
+		assert_eq!(rect, Rect::new(1.0, 4.0, 3.0, 2.0));
+		assert_eq!(Rect::from_points(Point::new(4.0, 1.0), Point::new(1.0, 3.0)), Rect::new(1.0, 1.0, 3.0, 2.0));
+		assert!(rect.contains(Point::new(2.0, 5.0)));
+		assert!(rect.intersects(&Rect::new(0.0, 0.0, 2.0, 5.0)));
+	}
+
+	#[test]
+	fn rect_contains_top_left_edges_only() {
+		let rect = Rect::new(1.0, 1.0, 2.0, 2.0);
+		assert!(rect.contains(Point::new(1.0, 1.0)));
+		assert!(rect.contains(Point::new(2.9, 2.9)));
+		assert!(!rect.contains(Point::new(3.0, 2.0)));
+		assert!(!rect.contains(Point::new(2.0, 3.0)));
+		assert!(!rect.contains(Point::new(0.9, 2.0)));
+	}
+
+	#[test]
+	fn rect_intersection_and_union() {
+		let a = Rect::new(0.0, 0.0, 4.0, 4.0);
+		let b = Rect::new(2.0, 3.0, 4.0, 4.0);
+		assert_eq!(a.intersection(&b), Some(Rect::new(2.0, 3.0, 2.0, 1.0)));
+		assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 6.0, 7.0));
+
+		// Sharing an edge is not overlapping.
+		let c = Rect::new(4.0, 0.0, 1.0, 1.0);
+		assert!(!a.intersects(&c));
+		assert_eq!(a.intersection(&c), None);
+	}
+
+	#[test]
+	fn rect_expand_stops_at_the_center() {
+		let rect = Rect::new(0.0, 0.0, 4.0, 2.0);
+		assert_eq!(rect.expand(1.0), Rect::new(-1.0, -1.0, 6.0, 4.0));
+		assert_eq!(rect.expand(-1.0), Rect::new(1.0, 1.0, 2.0, 0.0));
+		assert_eq!(rect.expand(-3.0), Rect::new(2.0, 1.0, 0.0, 0.0));
+	}
 }
\ No newline at end of file